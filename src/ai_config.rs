@@ -9,7 +9,7 @@ use std::sync::mpsc::Sender;
 use std::error::Error;
 use crate::logger::{self, LogContext};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// 配置相关的数据结构
 pub mod config {
@@ -33,6 +33,22 @@ pub mod config {
         
         /// Roaming 文件夹描述映射
         pub Roaming: HashMap<String, String>,
+
+        /// ~/.config 文件夹描述映射
+        #[serde(default)]
+        pub XdgConfig: HashMap<String, String>,
+
+        /// ~/.cache 文件夹描述映射
+        #[serde(default)]
+        pub XdgCache: HashMap<String, String>,
+
+        /// ~/.local/share 文件夹描述映射
+        #[serde(default)]
+        pub XdgData: HashMap<String, String>,
+
+        /// ~/.local/state 文件夹描述映射
+        #[serde(default)]
+        pub XdgState: HashMap<String, String>,
     }
 
     /// 模型和 API 配置
@@ -112,6 +128,10 @@ pub mod config {
                 Local: HashMap::new(),
                 LocalLow: HashMap::new(),
                 Roaming: HashMap::new(),
+                XdgConfig: HashMap::new(),
+                XdgCache: HashMap::new(),
+                XdgData: HashMap::new(),
+                XdgState: HashMap::new(),
             }
         }
     }
@@ -187,6 +207,7 @@ pub mod config {
 /// API 通信相关的数据结构和实现
 pub mod api {
     use super::*;
+    use crate::roots::RootKind;
     use super::config::AIConfig;
 
     /// 对话消息结构
@@ -224,7 +245,7 @@ pub mod api {
     }
 
     /// AI API 客户端
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     pub struct AIClient {
         /// 配置信息
         config: AIConfig,
@@ -254,7 +275,7 @@ pub mod api {
 
             // 创建日志上下文
             let ctx = LogContext::new("API")
-                .with_target_type(format!("{}", dir_1))
+                .with_target_type(dir_1.to_string())
                 .with_target_name(dir_2.to_string());
 
             logger::log_structured_info(&ctx, "开始生成文件夹描述");
@@ -298,10 +319,16 @@ pub mod api {
                     },
                     Message {
                         role: "user".to_string(),
-                        content: format!(
-                            "请简述Windows系统中AppData下的[{}]文件夹中的[{}]子文件夹的用途。",
-                            dir_1, dir_2
-                        ),
+                        content: match RootKind::from_name(dir_1).filter(|kind| kind.is_xdg()) {
+                            Some(kind) => format!(
+                                "请简述Linux系统中[{}]目录下的[{}]子文件夹的用途。",
+                                kind.label(), dir_2
+                            ),
+                            None => format!(
+                                "请简述Windows系统中AppData下的[{}]文件夹中的[{}]子文件夹的用途。",
+                                dir_1, dir_2
+                            ),
+                        },
                     },
                 ],
                 model: self.config.model.model.clone(),
@@ -378,7 +405,7 @@ pub mod api {
                     
                     // 根据状态码返回具体信息
                     let result = match status_code {
-                        200 => format!("连接成功 (HTTP 200 OK)"),
+                        200 => "连接成功 (HTTP 200 OK)".to_string(),
                         400 => {
                             let error_text = response.text().await?;
                             logger::log_structured_error(&ctx, &format!("请求错误: {}", error_text));
@@ -386,23 +413,23 @@ pub mod api {
                         },
                         401 => {
                             logger::log_structured_error(&ctx, "认证失败: 密钥无效");
-                            format!("认证失败 (HTTP 401): API密钥无效或已过期")
+                            "认证失败 (HTTP 401): API密钥无效或已过期".to_string()
                         },
                         403 => {
                             logger::log_structured_error(&ctx, "API权限错误: 拒绝访问");
-                            format!("拒绝访问 (HTTP 403): 没有权限访问此资源")
+                            "拒绝访问 (HTTP 403): 没有权限访问此资源".to_string()
                         },
                         404 => {
                             logger::log_structured_error(&ctx, "API地址错误: 资源不存在");
-                            format!("资源不存在 (HTTP 404): API端点URL可能不正确")
+                            "资源不存在 (HTTP 404): API端点URL可能不正确".to_string()
                         },
                         500 => {
                             logger::log_structured_error(&ctx, "API服务器错误");
-                            format!("服务器内部错误 (HTTP 500): 请联系API服务提供商")
+                            "服务器内部错误 (HTTP 500): 请联系API服务提供商".to_string()
                         },
                         503 => {
                             logger::log_structured_error(&ctx, "API服务暂时不可用");
-                            format!("服务暂时不可用 (HTTP 503): 服务器可能过载或正在维护")
+                            "服务暂时不可用 (HTTP 503): 服务器可能过载或正在维护".to_string()
                         },
                         _ => {
                             logger::log_structured_error(&ctx, &format!("未知响应状态: {}", status_code));
@@ -416,10 +443,10 @@ pub mod api {
                     // 处理网络连接问题
                     let result = if e.is_timeout() {
                         logger::log_structured_error(&ctx, &format!("连接超时: {}", e));
-                        format!("连接超时: 请检查网络连接或API服务是否可用")
+                        "连接超时: 请检查网络连接或API服务是否可用".to_string()
                     } else if e.is_connect() {
                         logger::log_structured_error(&ctx, &format!("连接失败: {}", e));
-                        format!("连接失败: 无法连接到API服务器，请检查URL是否正确")
+                        "连接失败: 无法连接到API服务器，请检查URL是否正确".to_string()
                     } else {
                         logger::log_structured_error(&ctx, &format!("请求错误: {}", e));
                         format!("请求错误: {}", e)
@@ -478,24 +505,30 @@ impl AIHandler {
         self.cancel_flag.store(false, Ordering::SeqCst);
     }
 
-    /// 检查是否应该取消当前操作
-    fn should_cancel(&self) -> bool {
-        self.cancel_flag.load(Ordering::SeqCst)
-    }
-
     /// 检查文件夹是否已有描述
     fn has_existing_description(&self, folder_name: &str, selected_folder: &str) -> bool {
         match selected_folder {
             "Local" => self.config.Local.contains_key(folder_name),
             "LocalLow" => self.config.LocalLow.contains_key(folder_name),
             "Roaming" => self.config.Roaming.contains_key(folder_name),
+            "XdgConfig" => self.config.XdgConfig.contains_key(folder_name),
+            "XdgCache" => self.config.XdgCache.contains_key(folder_name),
+            "XdgData" => self.config.XdgData.contains_key(folder_name),
+            "XdgState" => self.config.XdgState.contains_key(folder_name),
             _ => false,
         }
     }
 
+    /// 锁定共享的处理器
+    fn lock(handler: &Mutex<Self>) -> Result<std::sync::MutexGuard<'_, Self>, Box<dyn Error + Send + Sync>> {
+        handler.lock().map_err(|_| "无法获取 AI 处理器锁".into())
+    }
+
     /// 处理单个文件夹描述生成
+    ///
+    /// 只在读取客户端与写回描述时短暂持锁，请求 API 期间不占用处理器
     pub async fn generate_single_description(
-        &mut self,
+        handler: &Mutex<Self>,
         folder_name: String,
         selected_folder: String,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -505,9 +538,12 @@ impl AIHandler {
 
         logger::log_structured_info(&ctx, "开始处理");
 
-        match self.client.get_folder_description(&selected_folder, &folder_name).await {
-            Ok(description) => self.handle_success_response(&selected_folder, &folder_name, &description, &ctx),
-            Err(e) => self.handle_error_response(&selected_folder, &folder_name, e, &ctx),
+        let client = Self::lock(handler)?.client.clone();
+        let response = client.get_folder_description(&selected_folder, &folder_name).await;
+        let mut handler = Self::lock(handler)?;
+        match response {
+            Ok(description) => handler.handle_success_response(&selected_folder, &folder_name, &description, &ctx),
+            Err(e) => handler.handle_error_response(&selected_folder, &folder_name, e, &ctx),
         }
     }
 
@@ -549,21 +585,29 @@ impl AIHandler {
             "Local" => { self.config.Local.insert(folder_name.to_string(), description.to_string()); }
             "LocalLow" => { self.config.LocalLow.insert(folder_name.to_string(), description.to_string()); }
             "Roaming" => { self.config.Roaming.insert(folder_name.to_string(), description.to_string()); }
+            "XdgConfig" => { self.config.XdgConfig.insert(folder_name.to_string(), description.to_string()); }
+            "XdgCache" => { self.config.XdgCache.insert(folder_name.to_string(), description.to_string()); }
+            "XdgData" => { self.config.XdgData.insert(folder_name.to_string(), description.to_string()); }
+            "XdgState" => { self.config.XdgState.insert(folder_name.to_string(), description.to_string()); }
             _ => {}
         };
     }
 
     /// 批量处理多个文件夹
     pub async fn generate_all_descriptions(
-        &mut self,
+        handler: &Mutex<Self>,
         folder_data: Vec<(String, u64)>,
         selected_folder: String,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         let ctx = LogContext::new("批量生成")
             .with_target_type(selected_folder.clone());
 
+        // 取消标志在处理器之间共享，之后无需持锁即可检查
+        let cancel_flag = Self::lock(handler)?.cancel_flag.clone();
+        let should_cancel = || cancel_flag.load(Ordering::SeqCst);
+
         // 重置取消标志，确保新的批量操作从头开始
-        self.reset_cancel_flag();
+        cancel_flag.store(false, Ordering::SeqCst);
         
         logger::log_structured_info(&ctx, &format!("开始处理 {} 个文件夹", folder_data.len()));
             
@@ -573,7 +617,7 @@ impl AIHandler {
         
         for (i, (folder, _)) in folder_data.iter().enumerate() {
             // 检查是否应该取消操作
-            if self.should_cancel() {
+            if should_cancel() {
                 logger::log_structured_info(&ctx, "操作被用户取消");
                 break;
             }
@@ -584,7 +628,7 @@ impl AIHandler {
                 .with_target_name(folder.clone());
             
             // 检查是否已有描述，如果有则跳过
-            if self.has_existing_description(folder, &selected_folder) {
+            if Self::lock(handler)?.has_existing_description(folder, &selected_folder) {
                 logger::log_structured_info(&folder_ctx, "跳过 (已存在描述)");
                 skipped_count += 1;
                 continue;
@@ -592,13 +636,13 @@ impl AIHandler {
 
             logger::log_structured_info(&folder_ctx, "处理中");
             
-            match self.generate_single_description(folder.clone(), selected_folder.clone()).await {
+            match Self::generate_single_description(handler, folder.clone(), selected_folder.clone()).await {
                 Ok(_) => success_count += 1,
                 Err(_) => failed_count += 1,
             }
         }
         
-        if self.should_cancel() {
+        if should_cancel() {
             logger::log_structured_info(&ctx, 
                 &format!("操作已取消 - 成功: {}, 失败: {}, 跳过: {}, 未处理: {}", 
                     success_count, failed_count, skipped_count, 
@@ -610,7 +654,7 @@ impl AIHandler {
         }
         
         // 重置取消标志，以便于后续操作
-        self.reset_cancel_flag();
+        cancel_flag.store(false, Ordering::SeqCst);
         
         Ok(())
     }
@@ -638,16 +682,17 @@ impl AIHandler {
                     }
                     Ok(())
                 }
-                Err(e) => Err(e.into())
+                Err(e) => Err(e)
             }
         } else {
             Err("无法获取配置文件路径".into())
         }
     }
 
-    /// 测试 API 连接
-    pub async fn test_connection(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
-        self.client.test_connection().await
+    /// 测试 API 连接，请求期间不持锁
+    pub async fn test_connection(handler: &Mutex<Self>) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let client = Self::lock(handler)?.client.clone();
+        client.test_connection().await
     }
 
     /// 更新配置
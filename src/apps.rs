@@ -0,0 +1,140 @@
+use crate::roots::ScanRoot;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+/// 公司名后缀，"Unity Technologies" 与 "Unity" 视为同一厂商
+const COMPANY_SUFFIXES: [&str; 11] = [
+    "inc", "corp", "corporation", "ltd", "llc", "gmbh", "co", "technologies", "software", "studios", "games",
+];
+
+/// 应用在某个根目录下的一个文件夹
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppPart {
+    pub root: ScanRoot,
+    pub folder: String,
+    pub size: u64,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// 跨根目录汇总后的一个应用
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppGroup {
+    /// 分组键，见 [`group_key`]
+    pub key: String,
+    /// 显示的名称，取组内第一个文件夹名
+    pub name: String,
+    pub parts: Vec<AppPart>,
+}
+
+impl AppGroup {
+    /// 所有根目录中的合计大小
+    pub fn total(&self) -> u64 {
+        self.parts.iter().map(|part| part.size).sum()
+    }
+
+    /// 各部分中最近一次使用的时间
+    pub fn last_used(&self) -> Option<DateTime<Utc>> {
+        self.parts.iter().filter_map(|part| part.last_used).max()
+    }
+
+    /// 分布在几个根目录中
+    pub fn root_count(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|part| part.root.id.as_str())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+// 名称中的字母数字片段，已转为小写
+fn name_tokens(folder: &str) -> Vec<String> {
+    folder
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 分组键：忽略大小写与分隔符，并去掉末尾的公司名后缀
+///
+/// "Google Chrome"、"google-chrome" 与 "GoogleChrome" 得到相同的键
+pub fn group_key(folder: &str) -> String {
+    let mut tokens = name_tokens(folder);
+    while tokens.len() > 1 && tokens.last().is_some_and(|token| COMPANY_SUFFIXES.contains(&token.as_str())) {
+        tokens.pop();
+    }
+    let key = tokens.concat();
+    if key.is_empty() {
+        folder.to_lowercase()
+    } else {
+        key
+    }
+}
+
+/// 把各根目录中的文件夹按应用分组，结果按合计大小从大到小排序
+///
+/// 只有分组键（见 [`group_key`]）完全相同的文件夹归为一组；
+/// 同一厂商的不同产品，如 "Adobe" 与 "Adobe Acrobat"，仍是两个应用
+pub fn group_parts(parts: Vec<AppPart>) -> Vec<AppGroup> {
+    let mut groups: Vec<AppGroup> = Vec::new();
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+    for part in parts {
+        let key = group_key(&part.folder);
+        let index = *index_by_key.entry(key.clone()).or_insert_with(|| {
+            groups.push(AppGroup {
+                key,
+                name: part.folder.clone(),
+                parts: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        groups[index].parts.push(part);
+    }
+
+    groups.sort_by(|a, b| b.total().cmp(&a.total()).then_with(|| a.key.cmp(&b.key)));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roots::{self, RootKind};
+    use std::path::Path;
+
+    #[test]
+    fn test_group_parts_across_roots() {
+        let roots = roots::appdata_roots(Path::new("/tmp/AppData"), "test", "tester", "tester");
+        let part = |kind: RootKind, folder: &str, size: u64| AppPart {
+            root: roots.iter().find(|root| root.kind == kind).unwrap().clone(),
+            folder: folder.to_string(),
+            size,
+            last_used: None,
+        };
+        let groups = group_parts(vec![
+            part(RootKind::Roaming, "Adobe Acrobat", 300),
+            part(RootKind::Roaming, "Google Chrome", 100),
+            part(RootKind::Roaming, "Notes", 5),
+            part(RootKind::Local, "Adobe Acrobat", 700),
+            part(RootKind::Local, "google-chrome", 2000),
+            part(RootKind::Local, "Unity Technologies", 50),
+            part(RootKind::LocalLow, "Adobe", 10),
+            part(RootKind::LocalLow, "Unity", 400),
+        ]);
+
+        let summary: Vec<(&str, u64, usize, usize)> = groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.total(), group.parts.len(), group.root_count()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Google Chrome", 2100, 2, 2),
+                ("Adobe Acrobat", 1000, 2, 2),
+                ("Unity Technologies", 450, 2, 2),
+                ("Adobe", 10, 1, 1),
+                ("Notes", 5, 1, 1),
+            ]
+        );
+    }
+}
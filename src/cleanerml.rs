@@ -0,0 +1,349 @@
+use crate::roots::RootKind;
+use crate::rules::{CleaningRule, SafetyLevel};
+use std::collections::HashMap;
+
+/// 路径开头的家目录写法，统一替换为 `~`
+const HOME_ALIASES: [&str; 5] = ["$home", "${home}", "$userprofile", "${userprofile}", "%userprofile%"];
+
+/// 路径开头与根目录类别的对应关系，已统一为小写、`/` 分隔，较长的写在前面
+const ROOT_PREFIXES: [(&str, RootKind); 21] = [
+    ("~/appdata/locallow", RootKind::LocalLow),
+    ("~/appdata/local", RootKind::Local),
+    ("~/appdata/roaming", RootKind::Roaming),
+    ("$localappdata", RootKind::Local),
+    ("${localappdata}", RootKind::Local),
+    ("%localappdata%", RootKind::Local),
+    ("$appdata", RootKind::Roaming),
+    ("${appdata}", RootKind::Roaming),
+    ("%appdata%", RootKind::Roaming),
+    ("~/.config", RootKind::XdgConfig),
+    ("$xdg_config_home", RootKind::XdgConfig),
+    ("${xdg_config_home}", RootKind::XdgConfig),
+    ("~/.cache", RootKind::XdgCache),
+    ("$xdg_cache_home", RootKind::XdgCache),
+    ("${xdg_cache_home}", RootKind::XdgCache),
+    ("~/.local/share", RootKind::XdgData),
+    ("$xdg_data_home", RootKind::XdgData),
+    ("${xdg_data_home}", RootKind::XdgData),
+    ("~/.local/state", RootKind::XdgState),
+    ("$xdg_state_home", RootKind::XdgState),
+    ("${xdg_state_home}", RootKind::XdgState),
+];
+
+/// 选项 id 中出现这些词时视为可能包含用户数据
+const RISKY_OPTIONS: [&str; 7] = ["history", "cookie", "password", "session", "form", "bookmark", "storage"];
+
+/// 无法转换为清理规则的 CleanerML 动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedAction {
+    pub cleaner: String,
+    pub option: String,
+    pub command: String,
+    pub path: String,
+    pub reason: String,
+}
+
+/// 导入结果
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub rules: Vec<CleaningRule>,
+    pub unsupported: Vec<UnsupportedAction>,
+}
+
+impl ImportReport {
+    /// 合并另一个文件的导入结果
+    pub fn merge(&mut self, other: ImportReport) {
+        self.rules.extend(other.rules);
+        self.unsupported.extend(other.unsupported);
+    }
+}
+
+/// 当前系统在 CleanerML `os` 属性中的名称
+const CURRENT_OS: &str = if cfg!(windows) {
+    "windows"
+} else if cfg!(target_os = "macos") {
+    "darwin"
+} else {
+    "linux"
+};
+
+/// 解析一个 BleachBit CleanerML 文件，把 `delete` 动作转换为清理规则
+///
+/// 只支持位于 AppData 或 XDG 根目录中的路径，`os` 属性与当前系统不符的
+/// cleaner、value 与 action 忽略。`walk.all` 转换为 `目录/**`，即清理目录中的
+/// 全部内容；`walk.files` 转换为只删除文件的同一模式，子目录保留；
+/// `walk.top` 连同目录本身一起删除。其他命令、正则过滤以及无法定位的路径
+/// 记入 `unsupported`
+pub fn parse_cleanerml(xml: &str) -> Result<ImportReport, String> {
+    parse_for_os(xml, CURRENT_OS)
+}
+
+fn parse_for_os(xml: &str, os: &str) -> Result<ImportReport, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| format!("解析 CleanerML 失败: {}", e))?;
+    let cleaner = doc.root_element();
+    if !cleaner.has_tag_name("cleaner") {
+        return Err(format!("根元素不是 cleaner: {}", cleaner.tag_name().name()));
+    }
+    if !os_matches(cleaner, os) {
+        return Ok(ImportReport::default());
+    }
+    let cleaner_id = cleaner.attribute("id").unwrap_or_default().to_string();
+    let cleaner_label = child_text(cleaner, "label").unwrap_or_else(|| cleaner_id.clone());
+
+    // <var name="base"><value>...</value></var>，路径中以 $$base$$ 引用
+    let mut vars: HashMap<String, Vec<String>> = HashMap::new();
+    for var in cleaner.children().filter(|node| node.has_tag_name("var")) {
+        let values = var
+            .children()
+            .filter(|node| node.has_tag_name("value") && os_matches(*node, os))
+            .filter_map(|node| node.text())
+            .map(|text| text.trim().to_string())
+            .collect();
+        vars.insert(var.attribute("name").unwrap_or_default().to_string(), values);
+    }
+
+    let mut report = ImportReport::default();
+    let mut rule_index: HashMap<(String, RootKind, String, bool), usize> = HashMap::new();
+    for option in cleaner.children().filter(|node| node.has_tag_name("option")) {
+        let option_id = option.attribute("id").unwrap_or_default().to_string();
+        let option_label = child_text(option, "label").unwrap_or_else(|| option_id.clone());
+        let safety = if RISKY_OPTIONS.iter().any(|word| option_id.to_lowercase().contains(word)) {
+            SafetyLevel::Risky
+        } else if option.children().any(|node| node.has_tag_name("warning")) {
+            SafetyLevel::Caution
+        } else {
+            SafetyLevel::Safe
+        };
+        let description = format!("{}: {}", cleaner_label, option_label);
+
+        for action in option
+            .children()
+            .filter(|node| node.has_tag_name("action") && os_matches(*node, os))
+        {
+            let command = action.attribute("command").unwrap_or_default().to_string();
+            let raw_path = action.attribute("path").unwrap_or_default().to_string();
+            let mut unsupported = |path: &str, reason: &str| {
+                report.unsupported.push(UnsupportedAction {
+                    cleaner: cleaner_id.clone(),
+                    option: option_id.clone(),
+                    command: command.clone(),
+                    path: path.to_string(),
+                    reason: reason.to_string(),
+                })
+            };
+
+            if command != "delete" {
+                unsupported(&raw_path, "不支持的命令");
+                continue;
+            }
+            if ["regex", "nregex", "wholeregex", "nwholeregex"]
+                .iter()
+                .any(|name| action.attribute(*name).is_some())
+            {
+                unsupported(&raw_path, "不支持正则过滤");
+                continue;
+            }
+            if raw_path.is_empty() {
+                unsupported(&raw_path, "缺少路径");
+                continue;
+            }
+
+            let search = action.attribute("search").unwrap_or("file");
+            for path in expand_vars(&raw_path, &vars) {
+                let Some((kind, relative)) = resolve_root(&path) else {
+                    unsupported(&path, "不在 AppData 或 XDG 根目录中，或包含 . 与 ..");
+                    continue;
+                };
+                let (app, rest) = relative.split_once('/').unwrap_or((relative.as_str(), ""));
+                if app.is_empty() || app.contains(['*', '?', '[']) {
+                    unsupported(&path, "应用文件夹名不确定");
+                    continue;
+                }
+                let pattern = match search {
+                    "file" | "glob" | "walk.top" if !rest.is_empty() => rest.to_string(),
+                    "walk.files" | "walk.all" if rest.is_empty() => "**".to_string(),
+                    "walk.files" | "walk.all" => format!("{}/**", rest),
+                    "file" | "glob" | "walk.top" => {
+                        unsupported(&path, "不会删除整个应用文件夹");
+                        continue;
+                    }
+                    _ => {
+                        unsupported(&path, "不支持的查找方式");
+                        continue;
+                    }
+                };
+
+                let files_only = search == "walk.files";
+                let key = (app.to_string(), kind, description.clone(), files_only);
+                let index = *rule_index.entry(key).or_insert_with(|| {
+                    report.rules.push(CleaningRule {
+                        app: app.to_string(),
+                        roots: vec![kind.as_str().to_string()],
+                        safety,
+                        description: description.clone(),
+                        paths: Vec::new(),
+                        files_only,
+                    });
+                    report.rules.len() - 1
+                });
+                let paths = &mut report.rules[index].paths;
+                if !paths.contains(&pattern) {
+                    paths.push(pattern);
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+// 没有 `os` 属性时适用于所有系统；unix 与 posix 指 Windows 以外的系统
+fn os_matches(node: roxmltree::Node, os: &str) -> bool {
+    match node.attribute("os").map(str::to_lowercase) {
+        None => true,
+        Some(value) if value == "unix" || value == "posix" => os != "windows",
+        Some(value) => value == os,
+    }
+}
+
+fn child_text(node: roxmltree::Node, tag: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.has_tag_name(tag))
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+}
+
+// 展开 $$name$$ 形式的变量，变量有多个取值时得到多条路径
+fn expand_vars(path: &str, vars: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let Some(start) = path.find("$$") else {
+        return vec![path.to_string()];
+    };
+    let Some(length) = path[start + 2..].find("$$") else {
+        return vec![path.to_string()];
+    };
+    let name = &path[start + 2..start + 2 + length];
+    let after = &path[start + 4 + length..];
+    match vars.get(name) {
+        Some(values) => values
+            .iter()
+            .flat_map(|value| expand_vars(&format!("{}{}{}", &path[..start], value, after), vars))
+            .collect(),
+        None => vec![path.to_string()],
+    }
+}
+
+/// 把 CleanerML 路径解析为根目录类别与其中以 `/` 分隔的相对路径
+///
+/// 路径中含有 `.` 或 `..` 段时无法确定是否仍在根目录中，返回 None
+pub fn resolve_root(path: &str) -> Option<(RootKind, String)> {
+    let mut path = path.trim().replace('\\', "/");
+    for alias in HOME_ALIASES {
+        if starts_with_segment(&path, alias) {
+            path = format!("~{}", &path[alias.len()..]);
+            break;
+        }
+    }
+    let (prefix, kind) = ROOT_PREFIXES.iter().find(|(prefix, _)| starts_with_segment(&path, prefix))?;
+    let segments: Vec<&str> = path[prefix.len()..].split('/').filter(|segment| !segment.is_empty()).collect();
+    if segments.iter().any(|segment| *segment == "." || *segment == "..") {
+        return None;
+    }
+    Some((*kind, segments.join("/")))
+}
+
+// 不区分大小写地判断路径是否以某个完整的路径段开头
+fn starts_with_segment(path: &str, prefix: &str) -> bool {
+    path.get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+        && matches!(path.as_bytes().get(prefix.len()), None | Some(b'/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cleanerml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cleaner id="chromium" os="linux">
+  <label>Chromium</label>
+  <var name="base">
+    <value os="windows">$localappdata\Chromium\User Data</value>
+    <value os="linux">~/.config/chromium</value>
+  </var>
+  <option id="cache">
+    <label>Cache</label>
+    <action command="delete" search="walk.all" path="$$base$$/Default/Cache"/>
+    <action command="delete" search="glob" path="~/.cache/chromium/*/Cache"/>
+    <action command="delete" search="file" path="/var/tmp/chromium.tmp"/>
+    <action command="delete" search="file" path="~/.config/chromium/../../.bashrc"/>
+    <action command="delete" search="file" path="$localappdata\Chromium\Cache" os="windows"/>
+  </option>
+  <option id="logs">
+    <label>Logs</label>
+    <warning>Logs help with troubleshooting</warning>
+    <action command="delete" search="walk.files" path="%AppData%\Chromium\logs"/>
+    <action command="delete" search="walk.all" path="$$base$$" regex="\.log$"/>
+  </option>
+  <option id="history">
+    <label>History</label>
+    <action command="chrome.history" search="file" path="$$base$$/Default/History"/>
+    <action command="delete" search="file" path="$$base$$/Default/History Provider Cache"/>
+  </option>
+</cleaner>"#;
+
+        let report = parse_for_os(xml, "linux").unwrap();
+        let summary: Vec<(&str, &str, SafetyLevel, bool, Vec<&str>)> = report
+            .rules
+            .iter()
+            .map(|rule| {
+                (
+                    rule.app.as_str(),
+                    rule.roots[0].as_str(),
+                    rule.safety,
+                    rule.files_only,
+                    rule.paths.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("chromium", "XdgConfig", SafetyLevel::Safe, false, vec!["Default/Cache/**"]),
+                ("chromium", "XdgCache", SafetyLevel::Safe, false, vec!["*/Cache"]),
+                ("Chromium", "Roaming", SafetyLevel::Caution, true, vec!["logs/**"]),
+                ("chromium", "XdgConfig", SafetyLevel::Risky, false, vec!["Default/History Provider Cache"]),
+            ]
+        );
+
+        let reasons: Vec<&str> = report.unsupported.iter().map(|action| action.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "不在 AppData 或 XDG 根目录中，或包含 . 与 ..",
+                "不在 AppData 或 XDG 根目录中，或包含 . 与 ..",
+                "不支持正则过滤",
+                "不支持的命令"
+            ]
+        );
+        // 只适用于其他系统的 cleaner 整个忽略
+        assert!(parse_for_os(xml, "windows").unwrap().rules.is_empty());
+        assert!(parse_cleanerml("<rules/>").is_err());
+    }
+
+    #[test]
+    fn test_resolve_root() {
+        assert_eq!(
+            resolve_root("$USERPROFILE\\AppData\\LocalLow\\Unity"),
+            Some((RootKind::LocalLow, "Unity".to_string()))
+        );
+        assert_eq!(resolve_root("$HOME/.local/share/Trash"), Some((RootKind::XdgData, "Trash".to_string())));
+        assert_eq!(
+            resolve_root("${XDG_STATE_HOME}/app/logs"),
+            Some((RootKind::XdgState, "app/logs".to_string()))
+        );
+        assert_eq!(resolve_root("$APPDATAX/foo"), None);
+        assert_eq!(resolve_root("~/.mozilla/firefox"), None);
+        assert_eq!(resolve_root("~/.config/../.ssh"), None);
+        assert_eq!(resolve_root("%AppData%\\.\\Foo"), None);
+    }
+}
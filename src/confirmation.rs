@@ -1,8 +1,6 @@
-use crate::delete;
-use crate::logger;
-use crate::stats::Stats;
-use crate::stats_logger::StatsLogger;
-use crate::utils;
+use crate::delete::{DeleteJob, DeleteMode, DeleteWorker};
+use crate::dry_run;
+use crate::roots::ScanRoot;
 use eframe::egui;
 use std::collections::HashSet;
 
@@ -10,10 +8,55 @@ pub fn show_confirmation(
     ctx: &egui::Context,
     message: &str,
     status: &Option<String>,
+) -> Option<bool> {
+    confirmation_window(ctx, "确认操作", message, status)
+}
+
+/// 彻底删除前的第二次确认
+pub fn confirm_permanent(ctx: &egui::Context, message: &str, status: &Option<String>) -> Option<bool> {
+    let message = format!("{}\n\n再次确认：彻底删除不经过回收站或隔离区，删除后无法恢复。", message);
+    confirmation_window(ctx, "再次确认彻底删除", &message, status)
+}
+
+/// 按删除方式确认；彻底删除在第一次确认后还需通过 [`confirm_permanent`] 再确认一次
+///
+/// 第一次确认过的提示记在界面的临时数据中，提示改变时重新从第一次确认开始
+pub fn confirm_deletion(
+    ctx: &egui::Context,
+    mode: DeleteMode,
+    message: &str,
+    status: &Option<String>,
+) -> Option<bool> {
+    if mode != DeleteMode::Permanent {
+        return show_confirmation(ctx, message, status);
+    }
+    let id = egui::Id::new("permanent_delete_confirmed");
+    let confirmed_once = ctx.data(|data| data.get_temp::<String>(id)).is_some_and(|confirmed| confirmed == message);
+    if !confirmed_once {
+        return match show_confirmation(ctx, message, status) {
+            Some(true) => {
+                ctx.data_mut(|data| data.insert_temp(id, message.to_string()));
+                None
+            }
+            result => result,
+        };
+    }
+    let result = confirm_permanent(ctx, message, status);
+    if result.is_some() {
+        ctx.data_mut(|data| data.remove::<String>(id));
+    }
+    result
+}
+
+fn confirmation_window(
+    ctx: &egui::Context,
+    title: &str,
+    message: &str,
+    status: &Option<String>,
 ) -> Option<bool> {
     let mut result = None;
 
-    egui::Window::new("确认操作")
+    egui::Window::new(title)
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
@@ -38,53 +81,62 @@ pub fn show_confirmation(
     result
 }
 
+/// 等待确认的删除所针对的根目录与文件夹
+pub struct DeleteTarget<'a> {
+    pub mode: DeleteMode,                   // 删除方式
+    pub root: &'a ScanRoot,                 // 选中的根目录
+    pub folder_data: &'a [(String, u64)],   // 用于估算删除进度
+    pub selected_folders: &'a HashSet<String>, // 批量删除时选中的文件夹
+}
+
 pub fn handle_delete_confirmation(
     ctx: &egui::Context,
     confirm_delete: &mut Option<(String, bool)>,
-    selected_appdata_folder: &str,
+    target: DeleteTarget,
     status: &mut Option<String>,
-    folder_data: &mut Vec<(String, u64)>,   // 新增参数
-    selected_folders: &mut HashSet<String>, // 传入 selected_folders
-    stats: &mut Stats,                      // 新增参数
-    stats_logger: &StatsLogger,             // 新增参数
+    delete_worker: &mut Option<DeleteWorker>, // 确认后交给后台删除
 ) {
+    let DeleteTarget {
+        mode,
+        root,
+        folder_data,
+        selected_folders,
+    } = target;
     if let Some((folder_name, is_bulk)) = confirm_delete.clone() {
         let message = if is_bulk && folder_name == "BULK_DELETE" {
-            "确定要批量删除选中的文件夹吗？".to_string()
+            mode.confirm_message(&format!("选中的 {} 个文件夹", selected_folders.len()))
         } else {
-            format!("确定要彻底删除文件夹 {} 吗？", folder_name)
+            mode.confirm_message(&format!("文件夹 {} ", folder_name))
         };
 
-        if let Some(confirm) = show_confirmation(ctx, &message, status) {
+        if let Some(confirm) = confirm_deletion(ctx, mode, &message, status) {
             if confirm {
-                if is_bulk && folder_name == "BULK_DELETE" {
-                    // 执行批量删除逻辑，仅针对 selected_folders
-                    for folder in selected_folders.iter() {
-                        if let Some(base_path) = utils::get_appdata_dir(selected_appdata_folder) {
-                            let full_path = base_path.join(folder);
-                            if let Err(err) = delete::delete_folder(&full_path, stats, stats_logger)
-                            {
-                                logger::log_error(&format!("批量删除失败: {}", err));
-                            } else {
-                                logger::log_info(&format!("已删除文件夹: {}", folder));
-                            }
-                        }
-                    }
-                    folder_data.retain(|(folder, _)| !selected_folders.contains(folder)); // 从数据中移除已删除的文件夹
-                    selected_folders.clear(); // 清空选定文件夹列表
-                    *status = Some("批量删除完成".to_string());
+                if delete_worker.is_some() {
+                    // 同一时间只运行一个删除任务
+                    *status = Some("上一个删除任务仍在进行，请等待完成或取消后再试".to_string());
                 } else {
-                    // 单个删除逻辑
-                    if let Some(base_path) = utils::get_appdata_dir(selected_appdata_folder) {
-                        let full_path = base_path.join(&folder_name);
-                        if let Err(err) = delete::delete_folder(&full_path, stats, stats_logger) {
-                            logger::log_error(&format!("删除失败: {}", err));
-                        } else {
-                            logger::log_info(&format!("已删除文件夹: {}", folder_name));
-                            folder_data.retain(|(folder, _)| folder != &folder_name);
-                        }
-                        *status = Some(format!("文件夹 {} 已成功删除", folder_name));
-                    }
+                    let folders: Vec<String> = if is_bulk && folder_name == "BULK_DELETE" {
+                        // 批量删除，仅针对 selected_folders
+                        selected_folders.iter().cloned().collect()
+                    } else {
+                        vec![folder_name]
+                    };
+                    let jobs = folders
+                        .into_iter()
+                        .map(|folder| DeleteJob {
+                            path: root.path.join(&folder),
+                            profile: root.profile.clone(),
+                            size: folder_data
+                                .iter()
+                                .find(|(name, _)| *name == folder)
+                                .map(|(_, size)| *size)
+                                .unwrap_or(0),
+                            folder,
+                        })
+                        .collect();
+                    // 演练开关在确认时读取，删除过程中切换不影响本次任务
+                    *delete_worker = Some(DeleteWorker::start(jobs, mode, dry_run::is_enabled()));
+                    *status = Some(format!("正在{}...", mode.label()));
                 }
             }
             *confirm_delete = None; // 重置确认状态
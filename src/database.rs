@@ -1,20 +1,148 @@
 use crate::logger;
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Result as SqliteResult};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use std::collections::HashMap;
 use std::path::Path;
 
 #[derive(Debug, Clone)]
-#[allow(dead_code, unused_fields)]
+#[allow(dead_code)]
 pub struct FolderRecord {
     pub id: Option<i64>,
     pub folder_type: String,    // Roaming, Local, LocalLow
     pub folder_name: String,
     pub folder_size: u64,
-    pub last_modified: DateTime<Utc>,
+    pub last_modified: DateTime<Utc>,           // 文件夹内最近的修改时间
+    pub last_accessed: Option<DateTime<Utc>>,   // 文件夹内文件最近的访问时间
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub fingerprint: Option<FolderFingerprint>, // 旧版本数据库中的记录没有指纹
+    pub profile: Option<String>,                // 所属用户配置文件，旧记录为空
+    pub disk_size: Option<u64>,                 // 实际占用的磁盘空间，旧记录为空
+    pub link_target: Option<String>,            // 文件夹本身是符号链接时的目标
 }
 
+/// 文件夹指纹，指纹未变化时增量扫描直接复用缓存的大小
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FolderFingerprint {
+    pub newest_mtime: DateTime<Utc>, // 目录树中最新的目录修改时间
+    pub file_count: u64,
+    pub dir_count: u64,
+}
+
+/// 文件夹详情：最大的文件、按扩展名分类的大小与修改时间范围
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolderDetails {
+    pub largest_files: Vec<(String, u64)>,        // 相对路径与大小，从大到小
+    pub extensions: Vec<ExtensionStat>,           // 从大到小
+    pub oldest_mtime: Option<DateTime<Utc>>,
+    pub newest_mtime: Option<DateTime<Utc>>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// 某一类文件的合计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionStat {
+    pub label: String, // 扩展名或分类名，如 ".dll"、"日志"
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// 一次扫描的快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanSession {
+    pub id: i64,
+    pub folder_type: String,
+    pub profile: Option<String>,
+    pub scanned_at: DateTime<Utc>,
+    pub total_size: u64,
+    pub folder_count: u64,
+}
+
+/// 基线中记录的一个文件或目录的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileState {
+    pub is_dir: bool,
+    pub size: u64,
+    pub mtime_ms: i64, // 修改时间，Unix 毫秒
+}
+
+/// 已保存的安装基线或足迹清单的概要
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallRecord {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub item_count: u64,
+}
+
+/// 足迹中一项的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FootprintKind {
+    AddedDir,
+    AddedFile,
+    Modified,
+}
+
+impl FootprintKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FootprintKind::AddedDir => "added_dir",
+            FootprintKind::AddedFile => "added_file",
+            FootprintKind::Modified => "modified",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [FootprintKind::AddedDir, FootprintKind::AddedFile, FootprintKind::Modified]
+            .into_iter()
+            .find(|kind| kind.as_str() == name)
+    }
+
+    /// 界面上显示的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            FootprintKind::AddedDir => "新增目录",
+            FootprintKind::AddedFile => "新增文件",
+            FootprintKind::Modified => "修改的文件",
+        }
+    }
+}
+
+/// 足迹清单中的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FootprintItem {
+    pub path: String, // 绝对路径
+    pub kind: FootprintKind,
+    pub size: u64,    // 新增目录为其中所有文件的合计
+    pub contents: Vec<String>, // 新增目录中记录时已有的文件与子目录，以 `/` 分隔的相对路径
+}
+
+/// 隔离区中的一个文件夹
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantineEntry {
+    pub id: i64,
+    pub original_path: String,
+    pub stored_path: String, // 在隔离区中的路径，其上级目录由隔离区独占
+    pub profile: String,
+    pub size: u64,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// 保存的删除报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteReportRecord {
+    pub path: String,
+    pub profile: String,
+    pub operation: String, // 移到回收站、移到隔离区或彻底删除
+    pub deleted_at: DateTime<Utc>,
+    pub bytes_freed: u64,
+    pub files_removed: u64,
+    pub failures: Vec<(String, String)>, // 失败的路径与原因
+}
+
+/// 基线中的全部条目：根目录路径 -> (相对路径 -> 状态)
+pub type BaselineEntries = HashMap<String, HashMap<String, FileState>>;
+
 pub struct Database {
     conn: Connection,
 }
@@ -39,11 +167,181 @@ impl Database {
                 last_modified TEXT NOT NULL,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
+                newest_mtime TEXT,
+                file_count INTEGER,
+                dir_count INTEGER,
+                profile TEXT,
+                disk_size INTEGER,
+                link_target TEXT,
+                last_accessed TEXT,
                 UNIQUE(folder_type, folder_name)
             )",
             [],
         )?;
 
+        // 旧版本数据库缺少指纹列，补齐
+        self.ensure_column("folder_scans", "newest_mtime", "TEXT")?;
+        self.ensure_column("folder_scans", "file_count", "INTEGER")?;
+        self.ensure_column("folder_scans", "dir_count", "INTEGER")?;
+        // 旧版本数据库缺少配置文件列
+        self.ensure_column("folder_scans", "profile", "TEXT")?;
+        // 旧版本数据库缺少磁盘占用与链接目标列
+        self.ensure_column("folder_scans", "disk_size", "INTEGER")?;
+        self.ensure_column("folder_scans", "link_target", "TEXT")?;
+        // 旧版本的 last_modified 只是扫描时间，清除指纹使下次扫描重新统计
+        if self.ensure_column("folder_scans", "last_accessed", "TEXT")? {
+            self.conn.execute("UPDATE folder_scans SET newest_mtime = NULL", [])?;
+        }
+
+        // 文件夹详情，与 folder_scans 使用相同的 (folder_type, folder_name) 标识
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS folder_details (
+                folder_type TEXT NOT NULL,
+                folder_name TEXT NOT NULL,
+                oldest_mtime TEXT,
+                newest_mtime TEXT,
+                computed_at TEXT NOT NULL,
+                PRIMARY KEY(folder_type, folder_name)
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS folder_largest_files (
+                folder_type TEXT NOT NULL,
+                folder_name TEXT NOT NULL,
+                rank INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                size INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS folder_extensions (
+                folder_type TEXT NOT NULL,
+                folder_name TEXT NOT NULL,
+                label TEXT NOT NULL,
+                bytes INTEGER NOT NULL,
+                files INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // 扫描快照：每次完整扫描保存一份各文件夹的大小，用于对比两次扫描
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS scan_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                folder_type TEXT NOT NULL,
+                profile TEXT,
+                scanned_at TEXT NOT NULL,
+                total_size INTEGER NOT NULL,
+                folder_count INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS scan_snapshot_folders (
+                session_id INTEGER NOT NULL,
+                folder_name TEXT NOT NULL,
+                folder_size INTEGER NOT NULL,
+                disk_size INTEGER,
+                PRIMARY KEY(session_id, folder_name)
+            )",
+            [],
+        )?;
+
+        // 安装追踪：安装前的基线与安装后生成的足迹清单
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS install_baselines (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS install_baseline_entries (
+                baseline_id INTEGER NOT NULL,
+                root_path TEXT NOT NULL,
+                path TEXT NOT NULL,
+                is_dir INTEGER NOT NULL,
+                size INTEGER NOT NULL,
+                mtime_ms INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS install_footprints (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS install_footprint_items (
+                footprint_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                size INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        // 新增目录在记录足迹时的内容，删除时只删除其中的条目
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS install_footprint_contents (
+                footprint_id INTEGER NOT NULL,
+                item_path TEXT NOT NULL,
+                path TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_baseline_entries ON install_baseline_entries(baseline_id)",
+            [],
+        )?;
+
+        // 隔离区中的文件夹与界面设置
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS quarantine_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                original_path TEXT NOT NULL,
+                stored_path TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                quarantined_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // 每次删除的结果与其中失败的路径
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS delete_reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                deleted_at TEXT NOT NULL,
+                bytes_freed INTEGER NOT NULL,
+                files_removed INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS delete_report_failures (
+                report_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                reason TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         // 创建索引提高查询性能
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_folder_type_name 
@@ -55,10 +353,29 @@ impl Database {
         Ok(())
     }
 
+    /// 表中不存在该列时添加，返回是否新添加了该列
+    fn ensure_column(&self, table: &str, column: &str, definition: &str) -> SqliteResult<bool> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        if !columns.iter().any(|c| c == column) {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+            logger::log_info(&format!("数据库表 {} 已添加列 {}", table, column));
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
     /// 获取指定文件夹类型的所有记录
     pub fn get_folders_by_type(&self, folder_type: &str) -> SqliteResult<Vec<FolderRecord>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, folder_type, folder_name, folder_size, last_modified, created_at, updated_at 
+            "SELECT id, folder_type, folder_name, folder_size, last_modified, created_at, updated_at,
+                    newest_mtime, file_count, dir_count, profile, disk_size, link_target, last_accessed
              FROM folder_scans WHERE folder_type = ?1 ORDER BY folder_name",
         )?;
 
@@ -77,6 +394,26 @@ impl Database {
                 updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
                     .unwrap()
                     .with_timezone(&Utc),
+                fingerprint: match (
+                    row.get::<_, Option<String>>(7)?,
+                    row.get::<_, Option<i64>>(8)?,
+                    row.get::<_, Option<i64>>(9)?,
+                ) {
+                    (Some(newest_mtime), Some(file_count), Some(dir_count)) => {
+                        DateTime::parse_from_rfc3339(&newest_mtime)
+                            .ok()
+                            .map(|newest_mtime| FolderFingerprint {
+                                newest_mtime: newest_mtime.with_timezone(&Utc),
+                                file_count: file_count as u64,
+                                dir_count: dir_count as u64,
+                            })
+                    }
+                    _ => None,
+                },
+                profile: row.get(10)?,
+                disk_size: row.get::<_, Option<i64>>(11)?.map(|size| size as u64),
+                link_target: row.get(12)?,
+                last_accessed: row.get::<_, Option<String>>(13)?.as_deref().and_then(parse_time),
             })
         })?;
 
@@ -88,23 +425,33 @@ impl Database {
     }
 
     /// 插入或更新文件夹记录
-    #[allow(dead_code)]
     pub fn upsert_folder(&self, record: &FolderRecord) -> SqliteResult<()> {
         let now = Utc::now().to_rfc3339();
         
+        let (newest_mtime, file_count, dir_count) = fingerprint_columns(&record.fingerprint);
+
         self.conn.execute(
             "INSERT OR REPLACE INTO folder_scans 
-             (folder_type, folder_name, folder_size, last_modified, created_at, updated_at)
+             (folder_type, folder_name, folder_size, last_modified, created_at, updated_at,
+              newest_mtime, file_count, dir_count, profile, disk_size, link_target, last_accessed)
              VALUES (?1, ?2, ?3, ?4, 
                      COALESCE((SELECT created_at FROM folder_scans 
-                              WHERE folder_type = ?1 AND folder_name = ?2), ?5), ?6)",
+                              WHERE folder_type = ?1 AND folder_name = ?2), ?5), ?6,
+                     ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 record.folder_type,
                 record.folder_name,
                 record.folder_size as i64,
                 record.last_modified.to_rfc3339(),
                 now,  // created_at (only used if record doesn't exist)
-                now   // updated_at (always updated)
+                now,  // updated_at (always updated)
+                newest_mtime,
+                file_count,
+                dir_count,
+                record.profile,
+                record.disk_size.map(|size| size as i64),
+                record.link_target,
+                record.last_accessed.map(|t| t.to_rfc3339())
             ],
         )?;
         Ok(())
@@ -116,19 +463,29 @@ impl Database {
         
         for record in records {
             let now = Utc::now().to_rfc3339();
+            let (newest_mtime, file_count, dir_count) = fingerprint_columns(&record.fingerprint);
             tx.execute(
                 "INSERT OR REPLACE INTO folder_scans 
-                 (folder_type, folder_name, folder_size, last_modified, created_at, updated_at)
+                 (folder_type, folder_name, folder_size, last_modified, created_at, updated_at,
+                  newest_mtime, file_count, dir_count, profile, disk_size, link_target, last_accessed)
                  VALUES (?1, ?2, ?3, ?4, 
                          COALESCE((SELECT created_at FROM folder_scans 
-                                  WHERE folder_type = ?1 AND folder_name = ?2), ?5), ?6)",
+                                  WHERE folder_type = ?1 AND folder_name = ?2), ?5), ?6,
+                         ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
                 params![
                     record.folder_type,
                     record.folder_name,
                     record.folder_size as i64,
                     record.last_modified.to_rfc3339(),
                     now,
-                    now
+                    now,
+                    newest_mtime,
+                    file_count,
+                    dir_count,
+                    record.profile,
+                    record.disk_size.map(|size| size as i64),
+                    record.link_target,
+                    record.last_accessed.map(|t| t.to_rfc3339())
                 ],
             )?;
         }
@@ -138,6 +495,15 @@ impl Database {
         Ok(())
     }
 
+    /// 删除单个文件夹的记录
+    pub fn remove_folder(&self, folder_type: &str, folder_name: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "DELETE FROM folder_scans WHERE folder_type = ?1 AND folder_name = ?2",
+            params![folder_type, folder_name],
+        )?;
+        Ok(())
+    }
+
     /// 删除指定文件夹类型中不存在的文件夹记录
     pub fn remove_missing_folders(&self, folder_type: &str, existing_folders: &[String]) -> SqliteResult<()> {
         if existing_folders.is_empty() {
@@ -163,6 +529,451 @@ impl Database {
         Ok(())
     }
 
+    /// 保存文件夹详情，覆盖之前的结果
+    pub fn save_folder_details(
+        &self,
+        folder_type: &str,
+        folder_name: &str,
+        details: &FolderDetails,
+    ) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        delete_details(&tx, folder_type, folder_name)?;
+
+        tx.execute(
+            "INSERT INTO folder_details (folder_type, folder_name, oldest_mtime, newest_mtime, computed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                folder_type,
+                folder_name,
+                details.oldest_mtime.map(|t| t.to_rfc3339()),
+                details.newest_mtime.map(|t| t.to_rfc3339()),
+                details.computed_at.to_rfc3339()
+            ],
+        )?;
+        for (rank, (path, size)) in details.largest_files.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO folder_largest_files (folder_type, folder_name, rank, path, size)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![folder_type, folder_name, rank as i64, path, *size as i64],
+            )?;
+        }
+        for ext in &details.extensions {
+            tx.execute(
+                "INSERT INTO folder_extensions (folder_type, folder_name, label, bytes, files)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![folder_type, folder_name, ext.label, ext.bytes as i64, ext.files as i64],
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    /// 读取文件夹详情，没有计算过时返回 `None`
+    pub fn get_folder_details(&self, folder_type: &str, folder_name: &str) -> SqliteResult<Option<FolderDetails>> {
+        let header = self
+            .conn
+            .query_row(
+                "SELECT oldest_mtime, newest_mtime, computed_at FROM folder_details
+                 WHERE folder_type = ?1 AND folder_name = ?2",
+                [folder_type, folder_name],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((oldest_mtime, newest_mtime, computed_at)) = header else {
+            return Ok(None);
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT path, size FROM folder_largest_files
+             WHERE folder_type = ?1 AND folder_name = ?2 ORDER BY rank",
+        )?;
+        let largest_files = stmt
+            .query_map([folder_type, folder_name], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT label, bytes, files FROM folder_extensions
+             WHERE folder_type = ?1 AND folder_name = ?2 ORDER BY bytes DESC",
+        )?;
+        let extensions = stmt
+            .query_map([folder_type, folder_name], |row| {
+                Ok(ExtensionStat {
+                    label: row.get(0)?,
+                    bytes: row.get::<_, i64>(1)? as u64,
+                    files: row.get::<_, i64>(2)? as u64,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(Some(FolderDetails {
+            largest_files,
+            extensions,
+            oldest_mtime: oldest_mtime.as_deref().and_then(parse_time),
+            newest_mtime: newest_mtime.as_deref().and_then(parse_time),
+            computed_at: parse_time(&computed_at).unwrap_or_else(Utc::now),
+        }))
+    }
+
+    /// 把一次扫描的结果保存为快照，返回快照编号
+    pub fn save_snapshot(
+        &self,
+        folder_type: &str,
+        profile: Option<&str>,
+        records: &[FolderRecord],
+    ) -> SqliteResult<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let total_size: u64 = records.iter().map(|r| r.folder_size).sum();
+        tx.execute(
+            "INSERT INTO scan_sessions (folder_type, profile, scanned_at, total_size, folder_count)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                folder_type,
+                profile,
+                Utc::now().to_rfc3339(),
+                total_size as i64,
+                records.len() as i64
+            ],
+        )?;
+        let session_id = tx.last_insert_rowid();
+        for record in records {
+            tx.execute(
+                "INSERT OR REPLACE INTO scan_snapshot_folders (session_id, folder_name, folder_size, disk_size)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    session_id,
+                    record.folder_name,
+                    record.folder_size as i64,
+                    record.disk_size.map(|size| size as i64)
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(session_id)
+    }
+
+    /// 指定类型的所有快照，最新的在前
+    pub fn list_snapshots(&self, folder_type: &str) -> SqliteResult<Vec<ScanSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, folder_type, profile, scanned_at, total_size, folder_count
+             FROM scan_sessions WHERE folder_type = ?1 ORDER BY id DESC",
+        )?;
+        let sessions = stmt
+            .query_map([folder_type], |row| {
+                Ok(ScanSession {
+                    id: row.get(0)?,
+                    folder_type: row.get(1)?,
+                    profile: row.get(2)?,
+                    scanned_at: parse_time(&row.get::<_, String>(3)?).unwrap_or_else(Utc::now),
+                    total_size: row.get::<_, i64>(4)? as u64,
+                    folder_count: row.get::<_, i64>(5)? as u64,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(sessions)
+    }
+
+    /// 快照中各文件夹的大小
+    pub fn get_snapshot_folders(&self, session_id: i64) -> SqliteResult<HashMap<String, u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT folder_name, folder_size FROM scan_snapshot_folders WHERE session_id = ?1")?;
+        let folders = stmt
+            .query_map([session_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<SqliteResult<HashMap<_, _>>>()?;
+        Ok(folders)
+    }
+
+    /// 删除一个快照
+    pub fn delete_snapshot(&self, session_id: i64) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM scan_snapshot_folders WHERE session_id = ?1", [session_id])?;
+        tx.execute("DELETE FROM scan_sessions WHERE id = ?1", [session_id])?;
+        tx.commit()
+    }
+
+    /// 保存安装基线，返回基线编号
+    pub fn save_baseline(&self, name: &str, entries: &BaselineEntries) -> SqliteResult<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO install_baselines (name, created_at) VALUES (?1, ?2)",
+            params![name, Utc::now().to_rfc3339()],
+        )?;
+        let baseline_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO install_baseline_entries (baseline_id, root_path, path, is_dir, size, mtime_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for (root_path, files) in entries {
+                for (path, state) in files {
+                    stmt.execute(params![
+                        baseline_id,
+                        root_path,
+                        path,
+                        state.is_dir,
+                        state.size as i64,
+                        state.mtime_ms
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(baseline_id)
+    }
+
+    /// 所有安装基线，最新的在前
+    pub fn list_baselines(&self) -> SqliteResult<Vec<InstallRecord>> {
+        self.list_install_records(
+            "SELECT b.id, b.name, b.created_at,
+                    (SELECT COUNT(*) FROM install_baseline_entries e WHERE e.baseline_id = b.id)
+             FROM install_baselines b ORDER BY b.id DESC",
+        )
+    }
+
+    /// 读取基线的全部条目
+    pub fn load_baseline(&self, baseline_id: i64) -> SqliteResult<BaselineEntries> {
+        let mut stmt = self.conn.prepare(
+            "SELECT root_path, path, is_dir, size, mtime_ms FROM install_baseline_entries
+             WHERE baseline_id = ?1",
+        )?;
+        let mut rows = stmt.query([baseline_id])?;
+        let mut entries = BaselineEntries::new();
+        while let Some(row) = rows.next()? {
+            entries.entry(row.get(0)?).or_default().insert(
+                row.get(1)?,
+                FileState {
+                    is_dir: row.get(2)?,
+                    size: row.get::<_, i64>(3)? as u64,
+                    mtime_ms: row.get(4)?,
+                },
+            );
+        }
+        Ok(entries)
+    }
+
+    pub fn delete_baseline(&self, baseline_id: i64) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM install_baseline_entries WHERE baseline_id = ?1", [baseline_id])?;
+        tx.execute("DELETE FROM install_baselines WHERE id = ?1", [baseline_id])?;
+        tx.commit()
+    }
+
+    /// 保存足迹清单，返回清单编号
+    pub fn save_footprint(&self, name: &str, items: &[FootprintItem]) -> SqliteResult<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO install_footprints (name, created_at) VALUES (?1, ?2)",
+            params![name, Utc::now().to_rfc3339()],
+        )?;
+        let footprint_id = tx.last_insert_rowid();
+        for item in items {
+            tx.execute(
+                "INSERT INTO install_footprint_items (footprint_id, path, kind, size) VALUES (?1, ?2, ?3, ?4)",
+                params![footprint_id, item.path, item.kind.as_str(), item.size as i64],
+            )?;
+            for path in &item.contents {
+                tx.execute(
+                    "INSERT INTO install_footprint_contents (footprint_id, item_path, path) VALUES (?1, ?2, ?3)",
+                    params![footprint_id, item.path, path],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(footprint_id)
+    }
+
+    /// 所有足迹清单，最新的在前
+    pub fn list_footprints(&self) -> SqliteResult<Vec<InstallRecord>> {
+        self.list_install_records(
+            "SELECT f.id, f.name, f.created_at,
+                    (SELECT COUNT(*) FROM install_footprint_items i WHERE i.footprint_id = f.id)
+             FROM install_footprints f ORDER BY f.id DESC",
+        )
+    }
+
+    /// 读取足迹清单中的条目，按路径排序
+    pub fn load_footprint(&self, footprint_id: i64) -> SqliteResult<Vec<FootprintItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, kind, size FROM install_footprint_items WHERE footprint_id = ?1 ORDER BY path",
+        )?;
+        let mut contents_stmt = self.conn.prepare(
+            "SELECT path FROM install_footprint_contents WHERE footprint_id = ?1 AND item_path = ?2 ORDER BY path",
+        )?;
+        let mut items = stmt
+            .query_map([footprint_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?
+            .into_iter()
+            .filter_map(|(path, kind, size)| {
+                Some(FootprintItem {
+                    path,
+                    kind: FootprintKind::from_name(&kind)?,
+                    size: size as u64,
+                    contents: Vec::new(),
+                })
+            })
+            .collect::<Vec<_>>();
+        for item in items.iter_mut().filter(|item| item.kind == FootprintKind::AddedDir) {
+            item.contents = contents_stmt
+                .query_map(params![footprint_id, item.path], |row| row.get(0))?
+                .collect::<SqliteResult<Vec<_>>>()?;
+        }
+        Ok(items)
+    }
+
+    pub fn delete_footprint(&self, footprint_id: i64) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM install_footprint_contents WHERE footprint_id = ?1", [footprint_id])?;
+        tx.execute("DELETE FROM install_footprint_items WHERE footprint_id = ?1", [footprint_id])?;
+        tx.execute("DELETE FROM install_footprints WHERE id = ?1", [footprint_id])?;
+        tx.commit()
+    }
+
+    fn list_install_records(&self, query: &str) -> SqliteResult<Vec<InstallRecord>> {
+        let mut stmt = self.conn.prepare(query)?;
+        let records = stmt
+            .query_map([], |row| {
+                Ok(InstallRecord {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    created_at: parse_time(&row.get::<_, String>(2)?).unwrap_or_else(Utc::now),
+                    item_count: row.get::<_, i64>(3)? as u64,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    /// 记录移入隔离区的文件夹，返回条目编号
+    pub fn add_quarantine_entry(
+        &self,
+        original_path: &str,
+        stored_path: &str,
+        profile: &str,
+        size: u64,
+    ) -> SqliteResult<i64> {
+        self.conn.execute(
+            "INSERT INTO quarantine_entries (original_path, stored_path, profile, size, quarantined_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![original_path, stored_path, profile, size as i64, Utc::now().to_rfc3339()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 隔离区中的所有条目，最早的在前
+    pub fn list_quarantine_entries(&self) -> SqliteResult<Vec<QuarantineEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, original_path, stored_path, profile, size, quarantined_at
+             FROM quarantine_entries ORDER BY id",
+        )?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(QuarantineEntry {
+                    id: row.get(0)?,
+                    original_path: row.get(1)?,
+                    stored_path: row.get(2)?,
+                    profile: row.get(3)?,
+                    size: row.get::<_, i64>(4)? as u64,
+                    quarantined_at: parse_time(&row.get::<_, String>(5)?).unwrap_or_else(Utc::now),
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    pub fn remove_quarantine_entry(&self, entry_id: i64) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM quarantine_entries WHERE id = ?1", [entry_id])?;
+        Ok(())
+    }
+
+    /// 保存删除报告，返回报告编号
+    pub fn save_delete_report(&self, report: &DeleteReportRecord) -> SqliteResult<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO delete_reports (path, profile, operation, deleted_at, bytes_freed, files_removed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                report.path,
+                report.profile,
+                report.operation,
+                report.deleted_at.to_rfc3339(),
+                report.bytes_freed as i64,
+                report.files_removed as i64
+            ],
+        )?;
+        let report_id = tx.last_insert_rowid();
+        for (path, reason) in &report.failures {
+            tx.execute(
+                "INSERT INTO delete_report_failures (report_id, path, reason) VALUES (?1, ?2, ?3)",
+                params![report_id, path, reason],
+            )?;
+        }
+        tx.commit()?;
+        Ok(report_id)
+    }
+
+    /// 最近的删除报告，最新的在前
+    pub fn list_delete_reports(&self, limit: usize) -> SqliteResult<Vec<DeleteReportRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, profile, operation, deleted_at, bytes_freed, files_removed
+             FROM delete_reports ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    DeleteReportRecord {
+                        path: row.get(1)?,
+                        profile: row.get(2)?,
+                        operation: row.get(3)?,
+                        deleted_at: parse_time(&row.get::<_, String>(4)?).unwrap_or_else(Utc::now),
+                        bytes_freed: row.get::<_, i64>(5)? as u64,
+                        files_removed: row.get::<_, i64>(6)? as u64,
+                        failures: Vec::new(),
+                    },
+                ))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut failures_stmt = self
+            .conn
+            .prepare("SELECT path, reason FROM delete_report_failures WHERE report_id = ?1 ORDER BY rowid")?;
+        let mut reports = Vec::with_capacity(rows.len());
+        for (report_id, mut report) in rows {
+            report.failures = failures_stmt
+                .query_map([report_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+
+    /// 读取界面设置，未设置时返回 None
+    pub fn get_setting(&self, key: &str) -> SqliteResult<Option<String>> {
+        self.conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
     /// 检查数据库中是否有指定类型的数据
     pub fn has_data_for_type(&self, folder_type: &str) -> SqliteResult<bool> {
         let count: i64 = self.conn.query_row(
@@ -191,6 +1002,35 @@ impl Database {
     }
 }
 
+// 删除某个文件夹的详情记录
+fn delete_details(conn: &Connection, folder_type: &str, folder_name: &str) -> SqliteResult<()> {
+    for table in ["folder_details", "folder_largest_files", "folder_extensions"] {
+        conn.execute(
+            &format!("DELETE FROM {} WHERE folder_type = ?1 AND folder_name = ?2", table),
+            [folder_type, folder_name],
+        )?;
+    }
+    Ok(())
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// 将指纹拆分为数据库列的值
+fn fingerprint_columns(fingerprint: &Option<FolderFingerprint>) -> (Option<String>, Option<i64>, Option<i64>) {
+    match fingerprint {
+        Some(fp) => (
+            Some(fp.newest_mtime.to_rfc3339()),
+            Some(fp.file_count as i64),
+            Some(fp.dir_count as i64),
+        ),
+        None => (None, None, None),
+    }
+}
+
 /// 获取默认数据库路径
 pub fn get_default_db_path() -> String {
     "cleanappdata.db".to_string()
@@ -230,8 +1070,13 @@ mod tests {
                 folder_name: "TestApp".to_string(),
                 folder_size: 1024,
                 last_modified: Utc::now(),
+                last_accessed: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
+                fingerprint: None,
+                profile: None,
+                disk_size: None,
+                link_target: None,
             };
             
             // 插入记录
@@ -272,8 +1117,13 @@ mod tests {
                     folder_name: "App1".to_string(),
                     folder_size: 1024,
                     last_modified: Utc::now(),
+                    last_accessed: None,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
+                    fingerprint: None,
+                    profile: None,
+                    disk_size: None,
+                    link_target: None,
                 },
                 FolderRecord {
                     id: None,
@@ -281,8 +1131,13 @@ mod tests {
                     folder_name: "App2".to_string(),
                     folder_size: 2048,
                     last_modified: Utc::now(),
+                    last_accessed: None,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
+                    fingerprint: None,
+                    profile: None,
+                    disk_size: None,
+                    link_target: None,
                 },
             ];
             
@@ -299,8 +1154,13 @@ mod tests {
                 folder_name: "App1".to_string(),
                 folder_size: 4096, // 更新大小
                 last_modified: Utc::now(),
+                last_accessed: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
+                fingerprint: None,
+                profile: None,
+                disk_size: None,
+                link_target: None,
             };
             
             db.batch_upsert_folders(&[updated_record]).unwrap();
@@ -324,4 +1184,174 @@ mod tests {
         // 清理测试数据库
         fs::remove_file(test_db_path).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_schema_migration_and_roundtrip() {
+        let test_db_path = "test_fingerprint_db.db";
+
+        // 清理之前的测试数据库
+        if database_exists(test_db_path) {
+            fs::remove_file(test_db_path).unwrap();
+        }
+
+        {
+            // 模拟旧版本数据库：没有指纹列
+            let conn = Connection::open(test_db_path).unwrap();
+            conn.execute(
+                "CREATE TABLE folder_scans (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    folder_type TEXT NOT NULL,
+                    folder_name TEXT NOT NULL,
+                    folder_size INTEGER NOT NULL,
+                    last_modified TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    UNIQUE(folder_type, folder_name)
+                )",
+                [],
+            )
+            .unwrap();
+            let now = Utc::now().to_rfc3339();
+            conn.execute(
+                "INSERT INTO folder_scans
+                 (folder_type, folder_name, folder_size, last_modified, created_at, updated_at)
+                 VALUES ('Roaming', 'OldApp', 512, ?1, ?1, ?1)",
+                [&now],
+            )
+            .unwrap();
+        }
+
+        {
+            let db = Database::new(test_db_path).unwrap();
+
+            // 旧记录可以读取，但没有指纹
+            let folders = db.get_folders_by_type("Roaming").unwrap();
+            assert_eq!(folders.len(), 1);
+            assert!(folders[0].fingerprint.is_none());
+            assert!(folders[0].profile.is_none());
+
+            let fingerprint = FolderFingerprint {
+                newest_mtime: Utc::now(),
+                file_count: 12,
+                dir_count: 3,
+            };
+            let record = FolderRecord {
+                id: None,
+                folder_type: "Roaming".to_string(),
+                folder_name: "OldApp".to_string(),
+                folder_size: 1024,
+                last_modified: Utc::now(),
+                last_accessed: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                fingerprint: Some(fingerprint),
+                profile: Some("alice".to_string()),
+                disk_size: Some(4096),
+                link_target: Some("/mnt/data/OldApp".to_string()),
+            };
+            db.batch_upsert_folders(&[record]).unwrap();
+
+            let folders = db.get_folders_by_type("Roaming").unwrap();
+            assert_eq!(folders[0].fingerprint, Some(fingerprint));
+            assert_eq!(folders[0].profile.as_deref(), Some("alice"));
+            assert_eq!(folders[0].disk_size, Some(4096));
+            assert_eq!(folders[0].link_target.as_deref(), Some("/mnt/data/OldApp"));
+        }
+
+        // 清理测试数据库
+        fs::remove_file(test_db_path).unwrap();
+    }
+
+    #[test]
+    fn test_folder_details_roundtrip() {
+        let test_db_path = "test_details_db.db";
+
+        // 清理之前的测试数据库
+        if database_exists(test_db_path) {
+            fs::remove_file(test_db_path).unwrap();
+        }
+
+        {
+            let db = Database::new(test_db_path).unwrap();
+            assert_eq!(db.get_folder_details("Local", "Google").unwrap(), None);
+
+            let mut details = FolderDetails {
+                largest_files: vec![("Chrome/big.bin".to_string(), 4096), ("a.log".to_string(), 10)],
+                extensions: vec![ExtensionStat {
+                    label: "日志".to_string(),
+                    bytes: 10,
+                    files: 1,
+                }],
+                oldest_mtime: Some(Utc::now() - chrono::Duration::days(400)),
+                newest_mtime: None,
+                computed_at: Utc::now(),
+            };
+            db.save_folder_details("Local", "Google", &details).unwrap();
+            let loaded = db.get_folder_details("Local", "Google").unwrap().unwrap();
+            assert_eq!(loaded.largest_files, details.largest_files);
+            assert_eq!(loaded.extensions, details.extensions);
+            assert_eq!(loaded.newest_mtime, None);
+
+            // 重新计算后覆盖旧结果
+            details.largest_files.truncate(1);
+            db.save_folder_details("Local", "Google", &details).unwrap();
+            let loaded = db.get_folder_details("Local", "Google").unwrap().unwrap();
+            assert_eq!(loaded.largest_files.len(), 1);
+        }
+
+        // 清理测试数据库
+        fs::remove_file(test_db_path).unwrap();
+    }
+
+    #[test]
+    fn test_scan_snapshots() {
+        let test_db_path = "test_snapshot_db.db";
+
+        // 清理之前的测试数据库
+        if database_exists(test_db_path) {
+            fs::remove_file(test_db_path).unwrap();
+        }
+
+        {
+            let db = Database::new(test_db_path).unwrap();
+            let record = |name: &str, size: u64| FolderRecord {
+                id: None,
+                folder_type: "Local".to_string(),
+                folder_name: name.to_string(),
+                folder_size: size,
+                last_modified: Utc::now(),
+                last_accessed: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                fingerprint: None,
+                profile: None,
+                disk_size: Some(size),
+                link_target: None,
+            };
+
+            // 每次扫描保存独立的快照，不会互相覆盖
+            let first = db
+                .save_snapshot("Local", Some("alice"), &[record("App1", 100), record("App2", 200)])
+                .unwrap();
+            let second = db.save_snapshot("Local", Some("alice"), &[record("App1", 5000)]).unwrap();
+            db.save_snapshot("Roaming", None, &[record("Other", 1)]).unwrap();
+
+            let sessions = db.list_snapshots("Local").unwrap();
+            assert_eq!(sessions.iter().map(|s| s.id).collect::<Vec<_>>(), vec![second, first]);
+            assert_eq!(sessions[1].total_size, 300);
+            assert_eq!(sessions[1].folder_count, 2);
+            assert_eq!(sessions[0].profile.as_deref(), Some("alice"));
+
+            let folders = db.get_snapshot_folders(first).unwrap();
+            assert_eq!(folders.get("App2"), Some(&200));
+            assert_eq!(db.get_snapshot_folders(second).unwrap().get("App1"), Some(&5000));
+
+            db.delete_snapshot(first).unwrap();
+            assert_eq!(db.list_snapshots("Local").unwrap().len(), 1);
+            assert!(db.get_snapshot_folders(first).unwrap().is_empty());
+        }
+
+        // 清理测试数据库
+        fs::remove_file(test_db_path).unwrap();
+    }
+}
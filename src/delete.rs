@@ -1,58 +1,600 @@
-use crate::logger;
+use crate::database::{get_default_db_path, Database, DeleteReportRecord};
+use crate::dry_run::{self, PlannedAction};
+use crate::logger::{self, LogContext};
+use crate::quarantine;
+use crate::size;
 use crate::stats::Stats;
 use crate::stats_logger::StatsLogger; // 引入 StatsLogger 模块
+use crate::trash;
+use crate::utils;
+use chrono::Utc;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use walkdir::WalkDir;
 
-pub fn delete_folder(
-    folder_path: &PathBuf,
-    stats: &mut Stats,
-    stats_logger: &StatsLogger,
-) -> Result<(), String> {
+/// 删除方式，默认移到回收站
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeleteMode {
+    #[default]
+    Trash,
+    /// 移到工具管理的隔离区，可在隔离区标签页还原
+    Quarantine,
+    /// 无法恢复，界面上需单独确认
+    Permanent,
+}
+
+impl DeleteMode {
+    pub const ALL: [DeleteMode; 3] = [DeleteMode::Trash, DeleteMode::Quarantine, DeleteMode::Permanent];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DeleteMode::Trash => "移到回收站",
+            DeleteMode::Quarantine => "移到隔离区",
+            DeleteMode::Permanent => "彻底删除",
+        }
+    }
+
+    /// 按钮上的文字，批量操作时加上前缀
+    pub fn action_label(self) -> &'static str {
+        match self {
+            DeleteMode::Trash => "删除",
+            DeleteMode::Quarantine => "隔离",
+            DeleteMode::Permanent => "彻底删除",
+        }
+    }
+
+    /// 确认框中的提示
+    pub fn confirm_message(self, target: &str) -> String {
+        match self {
+            DeleteMode::Trash => format!("确定要将{}移到回收站吗？", target),
+            DeleteMode::Quarantine => format!("确定要将{}移到隔离区吗？到期前可以还原", target),
+            DeleteMode::Permanent => format!("确定要彻底删除{}吗？此操作无法撤销！", target),
+        }
+    }
+}
+
+/// 一次删除的结果；彻底删除时遇到无法删除的文件会继续处理其余文件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeleteReport {
+    pub path: PathBuf,
+    pub mode: DeleteMode,
+    pub bytes_freed: u64,
+    pub files_removed: u64,
+    /// 无法删除的路径与原因
+    pub failures: Vec<(PathBuf, String)>,
+    /// 删除中途被取消，文件夹只删除了一部分
+    pub cancelled: bool,
+    /// 演练模式下只记录了计划，没有改动磁盘
+    pub planned: bool,
+    /// 清除只读属性后重试才删除的条目数
+    pub readonly_cleared: u64,
+}
+
+impl DeleteReport {
+    fn new(path: &Path, mode: DeleteMode) -> Self {
+        DeleteReport {
+            path: path.to_path_buf(),
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// 文件夹是否已完全删除
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty() && !self.cancelled
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "释放 {}，删除 {} 个文件",
+            utils::format_size(self.bytes_freed),
+            self.files_removed
+        );
+        if !self.failures.is_empty() {
+            summary.push_str(&format!("，{} 项失败", self.failures.len()));
+        }
+        if self.cancelled {
+            summary.push_str("，已取消");
+        }
+        summary
+    }
+
+    /// 转为数据库中保存的记录
+    pub fn to_record(&self, profile: &str) -> DeleteReportRecord {
+        let operation = if self.cancelled {
+            format!("{}（已取消）", self.mode.label())
+        } else {
+            self.mode.label().to_string()
+        };
+        DeleteReportRecord {
+            path: self.path.to_string_lossy().to_string(),
+            profile: profile.to_string(),
+            operation,
+            deleted_at: Utc::now(),
+            bytes_freed: self.bytes_freed,
+            files_removed: self.files_removed,
+            failures: self
+                .failures
+                .iter()
+                .map(|(path, reason)| (path.to_string_lossy().to_string(), reason.clone()))
+                .collect(),
+        }
+    }
+
+    // 记录失败的路径；其上级目录随之无法删除，不再重复记录
+    fn fail(&mut self, path: &Path, reason: String, root: &Path, blocked: &mut HashSet<PathBuf>) {
+        for ancestor in path.ancestors().take_while(|ancestor| ancestor.starts_with(root)) {
+            blocked.insert(ancestor.to_path_buf());
+        }
+        self.failures.push((path.to_path_buf(), reason));
+    }
+}
+
+/// 逐个删除目录树中的文件，失败时继续删除其余部分
+///
+/// 先删除目录中的内容再删除目录本身；不跟随符号链接，路径本身是链接时只删除链接。
+/// 每删除一个文件后回调一次；回调返回 false 时停止并标记为已取消
+pub fn remove_tree_with(path: &Path, mut on_file: impl FnMut(&DeleteReport) -> bool) -> DeleteReport {
+    let mut report = DeleteReport::new(path, DeleteMode::Permanent);
+    let mut blocked = HashSet::new();
+    for entry in WalkDir::new(path).follow_root_links(false).contents_first(true) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let failed = e.path().unwrap_or(path).to_path_buf();
+                report.fail(&failed, e.to_string(), path, &mut blocked);
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+        if entry.file_type().is_dir() {
+            if blocked.contains(entry_path) {
+                continue;
+            }
+            match remove_entry(entry_path, true, path) {
+                Ok(retried) => report.readonly_cleared += retried as u64,
+                Err(e) => report.fail(entry_path, e.to_string(), path, &mut blocked),
+            }
+        } else {
+            let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            match remove_entry(entry_path, false, path) {
+                Ok(retried) => {
+                    report.files_removed += 1;
+                    report.bytes_freed += size;
+                    report.readonly_cleared += retried as u64;
+                }
+                Err(e) => report.fail(entry_path, e.to_string(), path, &mut blocked),
+            }
+            if !on_file(&report) {
+                report.cancelled = true;
+                break;
+            }
+        }
+    }
+    report
+}
+
+// 删除单个文件或空目录，权限不足时清除只读属性后重试一次，返回是否经过重试
+fn remove_entry(path: &Path, is_dir: bool, root: &Path) -> io::Result<bool> {
+    let remove = || {
+        if is_dir {
+            fs::remove_dir(path)
+        } else {
+            // Windows 上指向目录的符号链接需要按目录删除
+            fs::remove_file(path).or_else(|e| if cfg!(windows) { fs::remove_dir(path) } else { Err(e) })
+        }
+    };
+    match remove() {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            // Unix 上删除文件还需要上级目录可写，上级目录不在删除范围内时不修改
+            let mut cleared = clear_readonly(path);
+            if path != root {
+                if let Some(parent) = path.parent() {
+                    cleared |= clear_readonly(parent);
+                }
+            }
+            if cleared {
+                remove().map(|()| true)
+            } else {
+                Err(e)
+            }
+        }
+        result => result.map(|()| false),
+    }
+}
+
+// 清除只读属性，返回是否做了修改；符号链接不处理
+fn clear_readonly(path: &Path) -> bool {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    let mut permissions = metadata.permissions();
+    if metadata.file_type().is_symlink() || !permissions.readonly() {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions).is_ok()
+}
+
+/// 把删除结果计入统计；演练与没有删除任何文件的失败不计入
+pub fn record_stats(report: &DeleteReport, stats: &mut Stats, stats_logger: &StatsLogger) {
+    if report.planned || !(report.is_complete() || report.files_removed > 0) {
+        return;
+    }
+    stats.update_stats(report.bytes_freed); // 更新统计数据
+    stats_logger.log_stats(stats.cleaned_folders_count, stats.total_cleaned_size); // 记录统计数据到文件
+}
+
+/// 删除文件夹或单个文件，不更新统计，可在后台线程中调用
+///
+/// 移到回收站或隔离区失败时返回错误；彻底删除时部分文件失败仍返回报告，
+/// `on_file` 返回 false 时中途停止。报告保存到 `db`，隔离区记录也写入 `db`，
+/// 批量删除时由调用方打开一次连接后复用。
+/// `dry_run` 由调用方在用户确认时读取，`true` 时只记录到演练计划
+pub fn delete_path(
+    db: &Database,
+    folder_path: &Path,
+    profile: &str,
+    mode: DeleteMode,
+    dry_run: bool,
+    on_file: impl FnMut(&DeleteReport) -> bool,
+) -> Result<DeleteReport, String> {
     let folder_path_str = folder_path.to_string_lossy();
-    println!("尝试删除文件夹: {}", folder_path_str);
-    logger::log_info(&format!("尝试删除文件夹: {}", folder_path_str));
+    // 日志标明所属的用户配置文件
+    let ctx = LogContext::new("删除")
+        .with_target_type(profile)
+        .with_target_name(folder_path_str.to_string());
+    logger::log_structured_info(&ctx, &format!("尝试{}", mode.label()));
 
     if !folder_path.exists() {
         let error_msg = format!("文件夹不存在: {}", folder_path_str);
-        println!("{}", error_msg);
-        logger::log_error(&error_msg);
+        logger::log_structured_error(&ctx, &error_msg);
+        return Err(error_msg);
+    }
+
+    if dry_run {
+        dry_run::record([PlannedAction::removal(mode.label(), folder_path)]);
+        logger::log_structured_info(&ctx, "演练模式，已记录到演练计划");
+        return Ok(DeleteReport {
+            planned: true,
+            ..DeleteReport::new(folder_path, mode)
+        });
+    }
+
+    // 重复文件等单个文件也经由这里删除
+    if !folder_path.is_dir() && !folder_path.is_file() {
+        let error_msg = format!("路径既不是目录也不是文件: {}", folder_path_str);
+        logger::log_structured_error(&ctx, &error_msg);
         return Err(error_msg);
     }
 
-    if folder_path.is_dir() {
-        let folder_size = calculate_folder_size(folder_path); // 计算文件夹大小
-        fs::remove_dir_all(folder_path).map_err(|e| {
-            let error_msg = format!("删除失败: {} - 错误: {}", folder_path_str, e);
-            println!("{}", error_msg);
-            logger::log_error(&error_msg);
-            error_msg
-        })?;
-        stats.update_stats(folder_size); // 更新统计数据
-        stats_logger.log_stats(stats.cleaned_folders_count, stats.total_cleaned_size); // 记录统计数据到文件
-        Ok(())
-    } else {
-        let error_msg = format!("路径不是目录: {}", folder_path_str);
-        println!("{}", error_msg);
-        logger::log_error(&error_msg);
-        Err(error_msg)
-    }
-}
-
-// 计算文件夹大小的函数
-fn calculate_folder_size(folder: &PathBuf) -> u64 {
-    let mut size = 0;
-    if let Ok(entries) = fs::read_dir(folder) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                size += calculate_folder_size(&path);
-            } else if path.is_file() {
-                if let Ok(metadata) = entry.metadata() {
-                    size += metadata.len();
+    let report = match mode {
+        DeleteMode::Permanent => remove_tree_with(folder_path, on_file),
+        DeleteMode::Trash | DeleteMode::Quarantine => {
+            // 整个文件夹一起移走；文件夹是符号链接时只移动链接本身，不计目标大小
+            let (files, bytes) = size::count_files(folder_path);
+            let result = if mode == DeleteMode::Trash {
+                trash::move_to_trash(folder_path)
+                    .map(|trashed| format!("已移到回收站: {}", trashed.display()))
+                    .map_err(|e| e.to_string())
+            } else {
+                let quarantine_dir = quarantine::get_default_quarantine_dir();
+                quarantine::quarantine(db, &quarantine_dir, folder_path, profile, bytes)
+                    .map(|_| "已移到隔离区".to_string())
+            };
+            let message = result.map_err(|e| {
+                let error_msg = format!("{}失败: {} - 错误: {}", mode.label(), folder_path_str, e);
+                logger::log_structured_error(&ctx, &error_msg);
+                error_msg
+            })?;
+            logger::log_structured_info(&ctx, &message);
+            DeleteReport {
+                files_removed: files,
+                bytes_freed: bytes,
+                ..DeleteReport::new(folder_path, mode)
+            }
+        }
+    };
+
+    for (path, reason) in &report.failures {
+        logger::log_structured_error(&ctx, &format!("无法删除 {}: {}", path.display(), reason));
+    }
+    logger::log_structured_info(&ctx, &report.summary());
+    if let Err(e) = db.save_delete_report(&report.to_record(profile)) {
+        logger::log_structured_error(&ctx, &format!("保存删除报告失败: {}", e));
+    }
+    Ok(report)
+}
+
+/// 交给后台删除的一个文件夹
+#[derive(Debug, Clone)]
+pub struct DeleteJob {
+    pub folder: String, // 主页列表中的文件夹名
+    pub path: PathBuf,
+    pub profile: String,
+    pub size: u64, // 扫描得到的大小，用于估算进度
+}
+
+/// 处理完的文件夹名及其删除结果
+pub type FolderResult = (String, Result<DeleteReport, String>);
+
+/// 后台删除线程发回的消息
+enum DeleteMessage {
+    /// 当前文件夹已删除的文件数与字节数
+    Progress(u64, u64),
+    /// 一个文件夹处理完毕
+    Finished(String, Result<DeleteReport, String>),
+}
+
+/// 在后台线程中依次删除文件夹，可在两个文件之间取消
+pub struct DeleteWorker {
+    pub mode: DeleteMode,
+    pub total_folders: usize,
+    pub total_bytes: u64,
+    pub finished_folders: usize,
+    // 已完成的文件夹与当前文件夹的进度之和
+    finished_files: u64,
+    finished_bytes: u64,
+    current: (u64, u64),
+    cancel: Arc<AtomicBool>,
+    rx: Receiver<DeleteMessage>,
+}
+
+impl DeleteWorker {
+    /// `dry_run` 在用户确认时读取，删除过程中切换演练开关不影响本次任务。
+    /// 整个任务共用一个数据库连接
+    pub fn start(jobs: Vec<DeleteJob>, mode: DeleteMode, dry_run: bool) -> Self {
+        let db = Database::new(&get_default_db_path()).map_err(|e| format!("无法打开数据库: {}", e));
+        DeleteWorker::start_with(jobs, mode, move |job, on_file| {
+            let db = db.as_ref().map_err(Clone::clone)?;
+            delete_path(db, &job.path, &job.profile, mode, dry_run, on_file)
+        })
+    }
+
+    // 由 `delete` 删除每个文件夹
+    fn start_with<F>(jobs: Vec<DeleteJob>, mode: DeleteMode, mut delete: F) -> Self
+    where
+        F: FnMut(&DeleteJob, &mut dyn FnMut(&DeleteReport) -> bool) -> Result<DeleteReport, String> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker = DeleteWorker {
+            mode,
+            total_folders: jobs.len(),
+            total_bytes: jobs.iter().map(|job| job.size).sum(),
+            finished_folders: 0,
+            finished_files: 0,
+            finished_bytes: 0,
+            current: (0, 0),
+            cancel: cancel.clone(),
+            rx,
+        };
+        thread::spawn(move || {
+            for job in jobs {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let result = delete(&job, &mut |report| {
+                    let _ = tx.send(DeleteMessage::Progress(report.files_removed, report.bytes_freed));
+                    !cancel.load(Ordering::Relaxed)
+                });
+                if tx.send(DeleteMessage::Finished(job.folder, result)).is_err() {
+                    break;
                 }
             }
+        });
+        worker
+    }
+
+    /// 请求取消，当前文件删除后停止
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelling(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// 已删除的文件数与字节数
+    pub fn progress(&self) -> (u64, u64) {
+        (self.finished_files + self.current.0, self.finished_bytes + self.current.1)
+    }
+
+    /// 按字节估算的完成比例
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return self.finished_folders as f32 / self.total_folders.max(1) as f32;
         }
+        (self.progress().1 as f32 / self.total_bytes as f32).min(1.0)
+    }
+
+    /// 取出已处理完的文件夹，并返回后台线程是否已结束
+    pub fn poll(&mut self) -> (Vec<FolderResult>, bool) {
+        let mut finished = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(DeleteMessage::Progress(files, bytes)) => self.current = (files, bytes),
+                Ok(DeleteMessage::Finished(folder, result)) => {
+                    if let Ok(report) = &result {
+                        self.finished_files += report.files_removed;
+                        self.finished_bytes += report.bytes_freed;
+                    }
+                    self.current = (0, 0);
+                    self.finished_folders += 1;
+                    finished.push((folder, result));
+                }
+                Err(TryRecvError::Empty) => return (finished, false),
+                Err(TryRecvError::Disconnected) => return (finished, true),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_tree_and_report() {
+        let temp_dir = std::env::temp_dir().join("test_remove_tree");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let folder = temp_dir.join("App");
+        fs::create_dir_all(folder.join("locked")).unwrap();
+        fs::write(folder.join("a.bin"), vec![0u8; 200]).unwrap();
+        fs::write(folder.join("locked/b.bin"), vec![0u8; 50]).unwrap();
+
+        // 只读的文件与目录清除只读属性后删除
+        for path in [folder.join("locked/b.bin"), folder.join("locked")] {
+            let mut permissions = fs::metadata(&path).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(&path, permissions).unwrap();
+        }
+        let report = remove_tree_with(&folder, |_| true);
+        assert_eq!(report.failures, Vec::new());
+        assert_eq!((report.files_removed, report.bytes_freed), (2, 250));
+        assert!(report.is_complete());
+        assert!(!folder.exists());
+        // root 不受只读权限限制，第一次就能删除，不会走到重试
+        #[cfg(unix)]
+        let privileged = unsafe { libc::geteuid() } == 0;
+        #[cfg(not(unix))]
+        let privileged = false;
+        if privileged {
+            assert_eq!(report.readonly_cleared, 0);
+        } else {
+            assert!(report.readonly_cleared > 0);
+        }
+
+        // 清除只读属性本身与权限无关
+        fs::create_dir_all(&folder).unwrap();
+        let readonly = folder.join("readonly.txt");
+        fs::write(&readonly, "x").unwrap();
+        let mut permissions = fs::metadata(&readonly).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&readonly, permissions).unwrap();
+        assert!(clear_readonly(&readonly));
+        assert!(!fs::metadata(&readonly).unwrap().permissions().readonly());
+        assert!(!clear_readonly(&readonly));
+        fs::remove_dir_all(&folder).unwrap();
+
+        // 报告连同失败的路径一起保存
+        let test_db_path = "test_delete_report.db";
+        let _ = fs::remove_file(test_db_path);
+        {
+            let db = Database::new(test_db_path).unwrap();
+            let partial = DeleteReport {
+                failures: vec![(folder.join("in_use.dll"), "文件被占用".to_string())],
+                ..report.clone()
+            };
+            db.save_delete_report(&report.to_record("tester")).unwrap();
+            db.save_delete_report(&partial.to_record("tester")).unwrap();
+            let saved = db.list_delete_reports(10).unwrap();
+            assert_eq!(saved.len(), 2);
+            assert_eq!((saved[0].bytes_freed, saved[0].operation.as_str()), (250, "彻底删除"));
+            assert_eq!(saved[0].failures[0].1, "文件被占用");
+            assert!(saved[1].failures.is_empty());
+            assert_eq!(db.list_delete_reports(1).unwrap().len(), 1);
+
+            // delete_path 把报告写入传入的数据库
+            fs::create_dir_all(&folder).unwrap();
+            fs::write(folder.join("c.bin"), vec![0u8; 30]).unwrap();
+            let report = delete_path(&db, &folder, "tester", DeleteMode::Permanent, false, |_| true).unwrap();
+            assert_eq!(report.bytes_freed, 30);
+            assert_eq!(db.list_delete_reports(10).unwrap().len(), 3);
+        }
+
+        // 回调返回 false 时在两个文件之间停止，其余文件保留
+        fs::create_dir_all(&folder).unwrap();
+        for name in ["1.bin", "2.bin", "3.bin"] {
+            fs::write(folder.join(name), vec![0u8; 10]).unwrap();
+        }
+        let mut progress = Vec::new();
+        let report = remove_tree_with(&folder, |report| {
+            progress.push((report.files_removed, report.bytes_freed));
+            report.files_removed < 2
+        });
+        assert_eq!(progress, vec![(1, 10), (2, 20)]);
+        assert!(report.cancelled && !report.is_complete());
+        assert!(report.summary().ends_with("已取消"));
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 1);
+        assert_eq!(report.to_record("tester").operation, "彻底删除（已取消）");
+
+        // 清理
+        fs::remove_file(test_db_path).unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_delete_worker_cancel() {
+        let temp_dir = std::env::temp_dir().join("test_delete_worker");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let jobs: Vec<DeleteJob> = ["A", "B", "C"]
+            .iter()
+            .map(|folder| {
+                let path = temp_dir.join(folder);
+                fs::create_dir_all(&path).unwrap();
+                fs::write(path.join("1.bin"), vec![0u8; 10]).unwrap();
+                fs::write(path.join("2.bin"), vec![0u8; 10]).unwrap();
+                DeleteJob {
+                    folder: folder.to_string(),
+                    path,
+                    profile: "tester".to_string(),
+                    size: 20,
+                }
+            })
+            .collect();
+
+        // 每个文件夹等到测试放行后才开始删除，便于在两个文件夹之间取消
+        let (gate_tx, gate_rx) = mpsc::channel::<()>();
+        let mut worker = DeleteWorker::start_with(jobs, DeleteMode::Permanent, move |job, on_file| {
+            gate_rx.recv().map_err(|e| e.to_string())?;
+            Ok(remove_tree_with(&job.path, on_file))
+        });
+        let poll_until = |worker: &mut DeleteWorker, results: &mut Vec<FolderResult>, count: usize| loop {
+            let (finished, done) = worker.poll();
+            results.extend(finished);
+            if done || results.len() >= count {
+                return done;
+            }
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+
+        let mut results = Vec::new();
+        gate_tx.send(()).unwrap();
+        assert!(!poll_until(&mut worker, &mut results, 1));
+        assert_eq!(worker.progress(), (2, 20));
+
+        // 第二个文件夹删除一个文件后停止，第三个不再处理
+        worker.cancel();
+        gate_tx.send(()).unwrap();
+        assert!(poll_until(&mut worker, &mut results, usize::MAX));
+        let folders: Vec<&str> = results.iter().map(|(folder, _)| folder.as_str()).collect();
+        assert_eq!(folders, vec!["A", "B"]);
+        let reports: Vec<&DeleteReport> = results.iter().map(|(_, result)| result.as_ref().unwrap()).collect();
+        assert!(reports[0].is_complete());
+        assert!(reports[1].cancelled && reports[1].files_removed == 1);
+        assert_eq!(worker.total_folders - worker.finished_folders, 1);
+        assert!(!temp_dir.join("A").exists());
+        assert_eq!(fs::read_dir(temp_dir.join("B")).unwrap().count(), 1);
+        assert_eq!(fs::read_dir(temp_dir.join("C")).unwrap().count(), 2);
+
+        // 后台线程结束后继续返回已结束
+        assert_eq!(worker.poll().0.len(), 0);
+        assert!(worker.poll().1);
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
     }
-    size
 }
@@ -0,0 +1,144 @@
+use crate::database::{ExtensionStat, FolderDetails};
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// 详情中列出的最大文件数量
+pub const TOP_FILES: usize = 20;
+
+// 归入“媒体”的扩展名
+const MEDIA_EXTENSIONS: [&str; 16] = [
+    "jpg", "jpeg", "png", "gif", "webp", "bmp", "ico", "svg", "mp3", "wav", "ogg", "flac", "mp4",
+    "mkv", "webm", "avi",
+];
+
+/// 文件所属的分类：日志、临时文件、缓存、媒体，其余按扩展名
+pub fn file_category(relative: &Path) -> String {
+    let name = relative
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = relative
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    // 位于缓存目录中的文件统一归为缓存
+    let in_cache = relative
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_lowercase().contains("cache"))
+        .unwrap_or(false);
+
+    if name.ends_with(".log") || name.contains(".log.") || extension.as_deref() == Some("old") {
+        "日志".to_string()
+    } else if matches!(extension.as_deref(), Some("tmp" | "temp" | "bak")) {
+        "临时文件".to_string()
+    } else if in_cache || extension.as_deref() == Some("cache") {
+        "缓存".to_string()
+    } else {
+        match extension {
+            Some(ext) if MEDIA_EXTENSIONS.contains(&ext.as_str()) => "媒体".to_string(),
+            Some(ext) => format!(".{}", ext),
+            None => "(无扩展名)".to_string(),
+        }
+    }
+}
+
+/// 遍历文件夹，统计最大的 `top_n` 个文件、各分类的大小与修改时间范围
+///
+/// 不跟随符号链接，无法读取的条目直接跳过
+pub fn compute_details(folder: &Path, top_n: usize) -> FolderDetails {
+    let mut largest: BinaryHeap<Reverse<(u64, String)>> = BinaryHeap::new();
+    let mut categories: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut oldest_mtime: Option<DateTime<Utc>> = None;
+    let mut newest_mtime: Option<DateTime<Utc>> = None;
+
+    for entry in WalkDir::new(folder).into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let size = metadata.len();
+        let relative = entry.path().strip_prefix(folder).unwrap_or(entry.path());
+
+        let category = categories.entry(file_category(relative)).or_default();
+        category.0 += size;
+        category.1 += 1;
+
+        if let Ok(modified) = metadata.modified() {
+            let modified = DateTime::<Utc>::from(modified);
+            oldest_mtime = Some(oldest_mtime.map_or(modified, |t| t.min(modified)));
+            newest_mtime = Some(newest_mtime.map_or(modified, |t| t.max(modified)));
+        }
+
+        // 小顶堆只保留最大的 top_n 个
+        largest.push(Reverse((size, relative.to_string_lossy().replace('\\', "/"))));
+        if largest.len() > top_n {
+            largest.pop();
+        }
+    }
+
+    let mut largest_files: Vec<(String, u64)> = largest
+        .into_iter()
+        .map(|Reverse((size, path))| (path, size))
+        .collect();
+    largest_files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut extensions: Vec<ExtensionStat> = categories
+        .into_iter()
+        .map(|(label, (bytes, files))| ExtensionStat { label, bytes, files })
+        .collect();
+    extensions.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.label.cmp(&b.label)));
+
+    FolderDetails {
+        largest_files,
+        extensions,
+        oldest_mtime,
+        newest_mtime,
+        computed_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_compute_details() {
+        let temp_dir = std::env::temp_dir().join("test_compute_details");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("Code Cache")).unwrap();
+        fs::write(temp_dir.join("app.log"), vec![0u8; 300]).unwrap();
+        fs::write(temp_dir.join("app.log.1"), vec![0u8; 200]).unwrap();
+        fs::write(temp_dir.join("Code Cache").join("index"), vec![0u8; 1000]).unwrap();
+        fs::write(temp_dir.join("logo.PNG"), vec![0u8; 50]).unwrap();
+        fs::write(temp_dir.join("core.dll"), vec![0u8; 700]).unwrap();
+
+        let details = compute_details(&temp_dir, 3);
+        assert_eq!(
+            details.largest_files,
+            vec![
+                ("Code Cache/index".to_string(), 1000),
+                ("core.dll".to_string(), 700),
+                ("app.log".to_string(), 300),
+            ]
+        );
+        let labels: Vec<(&str, u64, u64)> = details
+            .extensions
+            .iter()
+            .map(|ext| (ext.label.as_str(), ext.bytes, ext.files))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![("缓存", 1000, 1), (".dll", 700, 1), ("日志", 500, 2), ("媒体", 50, 1)]
+        );
+        assert!(details.oldest_mtime <= details.newest_mtime);
+        assert!(details.newest_mtime.is_some());
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! 演练模式：删除、批量删除、规则清理与移动只记录将要执行的操作，不改动磁盘
+//!
+//! 开关与记录的计划是全局的，由顶部菜单切换，在“演练计划”窗口中查看并导出为 JSON
+
+use crate::size;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PLAN: Mutex<Vec<PlannedAction>> = Mutex::new(Vec::new());
+
+/// 计划中一项操作的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionKind {
+    /// 删除，释放空间
+    Remove,
+    /// 复制到目标位置后删除原文件夹
+    Move,
+    /// 在原位置创建指向目标的符号链接
+    Link,
+}
+
+/// 演练时记录的一项操作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedAction {
+    pub kind: ActionKind,
+    /// 界面上的操作名称，如“移到回收站”“规则清理”
+    pub operation: String,
+    pub path: String,
+    /// 移动的目标目录或链接指向的位置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+impl PlannedAction {
+    /// 删除某个文件或文件夹，统计其中的文件数与大小；不跟随符号链接
+    pub fn removal(operation: &str, path: &Path) -> Self {
+        let (files, bytes) = size::count_files(path);
+        PlannedAction {
+            kind: ActionKind::Remove,
+            operation: operation.to_string(),
+            path: path.to_string_lossy().to_string(),
+            target: None,
+            files,
+            bytes,
+        }
+    }
+
+    /// 把文件或文件夹移到目标位置，如从隔离区还原
+    pub fn transfer(operation: &str, source: &Path, target: &Path) -> Self {
+        let (files, bytes) = size::count_files(source);
+        PlannedAction {
+            kind: ActionKind::Move,
+            operation: operation.to_string(),
+            path: source.to_string_lossy().to_string(),
+            target: Some(target.to_string_lossy().to_string()),
+            files,
+            bytes,
+        }
+    }
+
+    /// 把文件夹移到目标目录，并在原位置创建链接
+    pub fn relocation(source: &Path, target: &Path) -> [Self; 2] {
+        let moved = PlannedAction::transfer("移动", source, target);
+        let link = PlannedAction {
+            kind: ActionKind::Link,
+            operation: "创建链接".to_string(),
+            files: 0,
+            bytes: 0,
+            ..moved.clone()
+        };
+        [moved, link]
+    }
+}
+
+/// 计划的合计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PlanSummary {
+    pub files_removed: u64,
+    pub bytes_reclaimed: u64,
+    pub files_moved: u64,
+    pub bytes_moved: u64,
+    pub links_created: u64,
+}
+
+pub fn summarize(actions: &[PlannedAction]) -> PlanSummary {
+    let mut summary = PlanSummary::default();
+    for action in actions {
+        match action.kind {
+            ActionKind::Remove => {
+                summary.files_removed += action.files;
+                summary.bytes_reclaimed += action.bytes;
+            }
+            ActionKind::Move => {
+                summary.files_moved += action.files;
+                summary.bytes_moved += action.bytes;
+            }
+            ActionKind::Link => summary.links_created += 1,
+        }
+    }
+    summary
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// 把操作追加到当前计划
+pub fn record(actions: impl IntoIterator<Item = PlannedAction>) {
+    if let Ok(mut plan) = PLAN.lock() {
+        plan.extend(actions);
+    }
+}
+
+/// 当前计划的副本
+pub fn actions() -> Vec<PlannedAction> {
+    PLAN.lock().map(|plan| plan.clone()).unwrap_or_default()
+}
+
+pub fn clear() {
+    if let Ok(mut plan) = PLAN.lock() {
+        plan.clear();
+    }
+}
+
+/// 计划的 JSON 表示，包含合计与每一项操作
+pub fn to_json(actions: &[PlannedAction]) -> Result<String, String> {
+    #[derive(Serialize)]
+    struct Export<'a> {
+        summary: PlanSummary,
+        actions: &'a [PlannedAction],
+    }
+    let export = Export {
+        summary: summarize(actions),
+        actions,
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| format!("序列化演练计划失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_plan_summary_and_json() {
+        let temp_dir = std::env::temp_dir().join("test_dry_run_plan");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let folder = temp_dir.join("App");
+        fs::create_dir_all(folder.join("cache")).unwrap();
+        fs::write(folder.join("cache/a.bin"), vec![0u8; 300]).unwrap();
+        fs::write(folder.join("b.log"), vec![0u8; 20]).unwrap();
+
+        let mut actions = vec![PlannedAction::removal("彻底删除", &folder)];
+        actions.extend(PlannedAction::relocation(&folder, &temp_dir.join("D/App")));
+        assert_eq!((actions[0].files, actions[0].bytes), (2, 320));
+        assert_eq!(
+            summarize(&actions),
+            PlanSummary {
+                files_removed: 2,
+                bytes_reclaimed: 320,
+                files_moved: 2,
+                bytes_moved: 320,
+                links_created: 1,
+            }
+        );
+        // 演练只统计，不改动磁盘
+        assert!(folder.join("cache/a.bin").exists());
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&actions).unwrap()).unwrap();
+        assert_eq!(json["summary"]["bytes_reclaimed"], 320);
+        assert_eq!(json["actions"][0]["kind"], "remove");
+        assert!(json["actions"][0].get("target").is_none());
+        assert_eq!(json["actions"][2]["kind"], "link");
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,352 @@
+use crate::database::Database;
+use crate::delete::{self, DeleteMode};
+use crate::dry_run::{self, ActionKind, PlannedAction};
+use crate::logger::{self, LogContext};
+use crate::{roots, utils};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use walkdir::WalkDir;
+
+/// 部分哈希读取的字节数
+const PARTIAL_BYTES: usize = 4096;
+
+/// 一组内容相同的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// 单个文件的大小
+    pub size: u64,
+    /// 完整内容的 SHA-256
+    pub hash: String,
+    /// 内容相同的文件，已按路径排序
+    pub files: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// 只保留一份时可以释放的空间
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.files.len().saturating_sub(1)) as u64
+    }
+}
+
+/// 查找过程中发送给界面的消息
+pub enum DuplicateMessage {
+    /// 当前阶段的说明
+    Progress(String),
+    Finished(Vec<DuplicateGroup>),
+}
+
+// 同一 inode 的硬链接本来就不占用额外空间，只保留一个路径
+#[cfg(unix)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// 文件开头 PARTIAL_BYTES 字节的哈希，用于快速排除大小相同但内容不同的文件
+fn partial_hash(path: &Path) -> io::Result<String> {
+    let mut buffer = vec![0u8; PARTIAL_BYTES];
+    let mut file = fs::File::open(path)?;
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(format!("{:x}", Sha256::digest(&buffer[..filled])))
+}
+
+// 按 key 分组，只保留至少有两个文件的组
+fn regroup<K, F>(files: Vec<PathBuf>, mut key: F) -> Vec<Vec<PathBuf>>
+where
+    K: std::hash::Hash + Eq,
+    F: FnMut(&Path) -> Option<K>,
+{
+    let mut groups: HashMap<K, Vec<PathBuf>> = HashMap::new();
+    for file in files {
+        if let Some(key) = key(&file) {
+            groups.entry(key).or_default().push(file);
+        }
+    }
+    groups.into_values().filter(|files| files.len() > 1).collect()
+}
+
+/// 在多个目录中查找内容相同的文件
+///
+/// 先按大小分组，再比较开头部分的哈希，最后比较完整哈希；
+/// 不跟随符号链接，小于 `min_size` 的文件不参与比较。
+/// 结果按可释放空间从大到小排序
+pub fn find_duplicates(
+    folders: &[PathBuf],
+    min_size: u64,
+    progress: Option<&Sender<DuplicateMessage>>,
+) -> Vec<DuplicateGroup> {
+    let report = |message: String| {
+        if let Some(tx) = progress {
+            let _ = tx.send(DuplicateMessage::Progress(message));
+        }
+    };
+
+    // 按大小分组，目录互相包含或同一文件有多个硬链接时只计一次
+    let mut seen_paths = HashSet::new();
+    let mut seen_ids = HashSet::new();
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut file_count = 0usize;
+    for folder in folders {
+        for entry in WalkDir::new(folder).into_iter().filter_map(Result::ok) {
+            if !entry.file_type().is_file() || !seen_paths.insert(entry.path().to_path_buf()) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.len() < min_size.max(1) {
+                continue;
+            }
+            if let Some(id) = file_id(&metadata) {
+                if !seen_ids.insert(id) {
+                    continue;
+                }
+            }
+            by_size.entry(metadata.len()).or_default().push(entry.into_path());
+            file_count += 1;
+            if file_count.is_multiple_of(1000) {
+                report(format!("已列出 {} 个文件", file_count));
+            }
+        }
+    }
+
+    let candidates: Vec<(u64, Vec<PathBuf>)> = by_size
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect();
+    report(format!(
+        "{} 个文件中有 {} 组大小相同，正在比较内容",
+        file_count,
+        candidates.len()
+    ));
+
+    let mut groups = Vec::new();
+    for (index, (size, files)) in candidates.into_iter().enumerate() {
+        if index.is_multiple_of(100) {
+            report(format!("正在比较第 {} 组", index + 1));
+        }
+        for partial in regroup(files, |path| partial_hash(path).ok()) {
+            // 不超过部分哈希长度的文件已经比较过完整内容
+            let full_groups = if size as usize <= PARTIAL_BYTES {
+                let hash = partial_hash(&partial[0]).unwrap_or_default();
+                vec![(hash, partial)]
+            } else {
+                let mut hashes = HashMap::new();
+                let grouped = regroup(partial, |path| {
+                    let hash = utils::hash_file(path).ok()?;
+                    hashes.insert(path.to_path_buf(), hash.clone());
+                    Some(hash)
+                });
+                grouped
+                    .into_iter()
+                    .map(|files| (hashes[&files[0]].clone(), files))
+                    .collect()
+            };
+            for (hash, mut files) in full_groups {
+                files.sort();
+                groups.push(DuplicateGroup { size, hash, files });
+            }
+        }
+    }
+
+    groups.sort_by(|a, b| b.wasted().cmp(&a.wasted()).then_with(|| a.files.cmp(&b.files)));
+    let wasted: u64 = groups.iter().map(DuplicateGroup::wasted).sum();
+    logger::log_info(&format!(
+        "找到 {} 组重复文件，可释放 {}",
+        groups.len(),
+        utils::format_size(wasted)
+    ));
+    groups
+}
+
+/// 处理重复文件的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeAction {
+    /// 按删除方式处理保留文件以外的副本
+    Delete(DeleteMode),
+    /// 把其余副本替换为指向保留文件的硬链接
+    HardLink,
+}
+
+impl DedupeAction {
+    pub const ALL: [DedupeAction; 4] = [
+        DedupeAction::Delete(DeleteMode::Trash),
+        DedupeAction::Delete(DeleteMode::Quarantine),
+        DedupeAction::Delete(DeleteMode::Permanent),
+        DedupeAction::HardLink,
+    ];
+
+    pub fn label(self) -> String {
+        match self {
+            DedupeAction::Delete(mode) => format!("{}其余副本", mode.action_label()),
+            DedupeAction::HardLink => "替换为硬链接".to_string(),
+        }
+    }
+}
+
+/// 释放的字节数与失败的文件及原因
+pub type DedupeResult = (u64, Vec<(PathBuf, String)>);
+
+/// 对一组重复文件执行操作，`keep` 为保留的文件序号
+///
+/// 操作前重新计算哈希，内容已变化的文件跳过；删除副本与其他删除一样
+/// 经由 `delete::delete_path`，默认移到回收站，删除报告保存到 `db`。
+/// 需要逐个读取文件，应在后台线程中调用
+pub fn dedupe(
+    db: &Database,
+    group: &DuplicateGroup,
+    keep: usize,
+    action: DedupeAction,
+    dry_run: bool,
+) -> DedupeResult {
+    let mut freed = 0;
+    let mut failures = Vec::new();
+    let Some(kept) = group.files.get(keep) else {
+        return (0, failures);
+    };
+    let ctx = LogContext::new("重复文件").with_target_name(kept.to_string_lossy().to_string());
+
+    for (index, file) in group.files.iter().enumerate() {
+        if index == keep {
+            continue;
+        }
+        let result = match utils::hash_file(file) {
+            Ok(hash) if hash == group.hash => match action {
+                DedupeAction::Delete(mode) => delete_copy(db, file, mode, dry_run),
+                DedupeAction::HardLink => replace_with_hard_link(kept, file, dry_run),
+            },
+            Ok(_) => Err("文件内容已变化".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(()) => {
+                // 演练只记录到演练计划，没有释放空间
+                if !dry_run {
+                    freed += group.size;
+                }
+                logger::log_structured_info(&ctx, &format!("{}: {}", action.label(), file.display()));
+            }
+            Err(e) => {
+                logger::log_structured_error(&ctx, &format!("处理 {} 失败: {}", file.display(), e));
+                failures.push((file.clone(), e));
+            }
+        }
+    }
+    (freed, failures)
+}
+
+// 删除一个副本，单个文件没有完全删除即视为失败
+fn delete_copy(db: &Database, file: &Path, mode: DeleteMode, dry_run: bool) -> Result<(), String> {
+    let report = delete::delete_path(db, file, &roots::current_user(), mode, dry_run, |_| true)?;
+    match report.failures.into_iter().next() {
+        Some((_, reason)) => Err(reason),
+        None => Ok(()),
+    }
+}
+
+// 先在同一目录创建临时硬链接，再原子地替换原文件，失败时原文件保持不变；
+// 演练时只记录到演练计划
+fn replace_with_hard_link(kept: &Path, file: &Path, dry_run: bool) -> Result<(), String> {
+    if dry_run {
+        dry_run::record([PlannedAction {
+            kind: ActionKind::Link,
+            target: Some(kept.to_string_lossy().to_string()),
+            ..PlannedAction::removal(&DedupeAction::HardLink.label(), file)
+        }]);
+        return Ok(());
+    }
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let temp = file.with_file_name(format!(".{}.dedupe-tmp", name));
+    fs::hard_link(kept, &temp).map_err(|e| format!("无法创建硬链接（可能不在同一分区）: {}", e))?;
+    fs::rename(&temp, file).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("无法替换文件: {}", e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_dedupe() {
+        let temp_dir = std::env::temp_dir().join("test_find_duplicates");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let roaming = temp_dir.join("Roaming/App/runtime");
+        let local = temp_dir.join("Local/Other/runtime");
+        fs::create_dir_all(&roaming).unwrap();
+        fs::create_dir_all(&local).unwrap();
+
+        // 大于部分哈希长度、开头相同但结尾不同的文件不算重复
+        let mut big = vec![7u8; 10_000];
+        fs::write(roaming.join("core.bin"), &big).unwrap();
+        fs::write(local.join("core.bin"), &big).unwrap();
+        big[9_999] = 8;
+        fs::write(local.join("core-patched.bin"), &big).unwrap();
+        // 小文件
+        fs::write(roaming.join("a.txt"), "same").unwrap();
+        fs::write(local.join("b.txt"), "same").unwrap();
+        fs::write(local.join("c.txt"), "diff").unwrap();
+
+        let folders = vec![temp_dir.join("Roaming"), temp_dir.join("Local"), temp_dir.join("Local/Other")];
+        let groups = find_duplicates(&folders, 0, None);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].size, 10_000);
+        assert_eq!(groups[0].wasted(), 10_000);
+        assert_eq!(groups[0].files, vec![local.join("core.bin"), roaming.join("core.bin")]);
+        assert_eq!(groups[1].files, vec![local.join("b.txt"), roaming.join("a.txt")]);
+        assert!(find_duplicates(&folders, 100, None).len() == 1);
+
+        let test_db_path = "test_dedupe.db";
+        let _ = fs::remove_file(test_db_path);
+        let db = Database::new(test_db_path).unwrap();
+
+        // 替换为硬链接后不再视为重复
+        let (freed, failures) = dedupe(&db, &groups[0], 1, DedupeAction::HardLink, false);
+        assert_eq!((freed, failures.len()), (10_000, 0));
+        assert_eq!(fs::read(local.join("core.bin")).unwrap().len(), 10_000);
+        #[cfg(unix)]
+        assert_eq!(find_duplicates(&folders, 100, None).len(), 0);
+
+        // 删除时内容已变化的文件被跳过
+        fs::write(roaming.join("a.txt"), "changed").unwrap();
+        let (freed, failures) = dedupe(&db, &groups[1], 0, DedupeAction::Delete(DeleteMode::Permanent), false);
+        assert_eq!(freed, 0);
+        assert_eq!(failures.len(), 1);
+        assert!(roaming.join("a.txt").exists());
+
+        // 演练时不释放空间，副本保留并记录到演练计划
+        fs::write(roaming.join("a.txt"), "same").unwrap();
+        let (freed, failures) = dedupe(&db, &groups[1], 0, DedupeAction::Delete(DeleteMode::Permanent), true);
+        assert_eq!((freed, failures.len()), (0, 0));
+        assert!(roaming.join("a.txt").exists());
+        let planned = roaming.join("a.txt").to_string_lossy().to_string();
+        assert!(dry_run::actions().iter().any(|action| action.path == planned));
+
+        // 删除的副本写入传入数据库的删除报告
+        let (freed, failures) = dedupe(&db, &groups[1], 0, DedupeAction::Delete(DeleteMode::Permanent), false);
+        assert_eq!((freed, failures.len()), (4, 0));
+        assert!(!roaming.join("a.txt").exists());
+        assert_eq!(db.list_delete_reports(10).unwrap().len(), 1);
+
+        // 清理
+        drop(db);
+        fs::remove_file(test_db_path).unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use crate::database::{BaselineEntries, Database, FileState, FootprintItem, FootprintKind};
+use crate::delete::{self, DeleteMode, DeleteReport};
+use crate::logger::{self, LogContext};
+use crate::roots;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// 记录目录下所有文件与子目录的状态，键为以 `/` 分隔的相对路径
+///
+/// 不跟随符号链接，无法读取的条目直接跳过
+pub fn capture(root: &Path) -> HashMap<String, FileState> {
+    WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let relative = entry.path().strip_prefix(root).ok()?;
+            let mtime_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_millis() as i64)
+                .unwrap_or(0);
+            Some((
+                relative.to_string_lossy().replace('\\', "/"),
+                FileState {
+                    is_dir: metadata.is_dir(),
+                    size: if metadata.is_dir() { 0 } else { metadata.len() },
+                    mtime_ms,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// 记录多个根目录，作为安装前的基线
+pub fn capture_roots(roots: &[String]) -> BaselineEntries {
+    roots
+        .iter()
+        .map(|root| (root.clone(), capture(Path::new(root))))
+        .collect()
+}
+
+/// 对比基线与当前状态，得到新增与修改的条目
+///
+/// 新增目录只记录最上层的一个，其中的文件计入该目录的大小，并记下其中已有的内容；
+/// 目录的修改时间随内容变化，只对比文件
+pub fn compute_footprint(
+    root: &Path,
+    baseline: &HashMap<String, FileState>,
+    current: &HashMap<String, FileState>,
+) -> Vec<FootprintItem> {
+    // 最上层的新增目录及其大小与内容
+    let mut added_dirs: HashMap<&str, (u64, Vec<String>)> = current
+        .iter()
+        .filter(|(path, state)| {
+            state.is_dir && !baseline.contains_key(*path) && top_added_ancestor(path, baseline).is_none()
+        })
+        .map(|(path, _)| (path.as_str(), (0, Vec::new())))
+        .collect();
+
+    let mut items = Vec::new();
+    for (path, state) in current {
+        match baseline.get(path) {
+            None => match top_added_ancestor(path, baseline) {
+                Some(dir) => {
+                    let (size, contents) = added_dirs.entry(dir).or_default();
+                    *size += state.size;
+                    contents.push(path[dir.len() + 1..].to_string());
+                }
+                None if state.is_dir => {}
+                None => items.push(footprint_item(root, path, FootprintKind::AddedFile, state.size)),
+            },
+            Some(old) if !state.is_dir && (old.size != state.size || old.mtime_ms != state.mtime_ms) => {
+                items.push(footprint_item(root, path, FootprintKind::Modified, state.size))
+            }
+            Some(_) => {}
+        }
+    }
+    items.extend(added_dirs.into_iter().map(|(dir, (size, mut contents))| {
+        contents.sort();
+        FootprintItem {
+            contents,
+            ..footprint_item(root, dir, FootprintKind::AddedDir, size)
+        }
+    }));
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    items
+}
+
+fn footprint_item(root: &Path, relative: &str, kind: FootprintKind, size: u64) -> FootprintItem {
+    FootprintItem {
+        path: root.join(relative).to_string_lossy().to_string(),
+        kind,
+        size,
+        contents: Vec::new(),
+    }
+}
+
+// 最上层的新增上级目录
+fn top_added_ancestor<'a>(path: &'a str, baseline: &HashMap<String, FileState>) -> Option<&'a str> {
+    path.char_indices()
+        .filter(|(_, c)| *c == '/')
+        .map(|(index, _)| &path[..index])
+        .find(|ancestor| !baseline.contains_key(*ancestor))
+}
+
+/// 删除足迹中新增的目录与文件，与其他删除一样经由 `delete::delete_path`；
+/// 修改过的文件原本就存在，保持不变
+///
+/// 需要逐个检查文件，应在后台线程中调用；`dry_run` 在用户确认时读取，
+/// 删除报告保存到 `db`。返回释放的字节数与失败的路径及原因
+pub fn remove_footprint(
+    db: &Database,
+    name: &str,
+    items: &[FootprintItem],
+    mode: DeleteMode,
+    dry_run: bool,
+) -> (u64, Vec<(String, String)>) {
+    let profile = roots::current_user();
+    remove_footprint_with(name, items, |path| delete::delete_path(db, path, &profile, mode, dry_run, |_| true))
+}
+
+/// 同 `remove_footprint`，由 `remove` 删除每个路径
+///
+/// 新增目录只删除记录足迹时已有的内容，之后才出现的文件连同其上级目录保留；
+/// 已不存在的条目视为已删除
+pub fn remove_footprint_with(
+    name: &str,
+    items: &[FootprintItem],
+    mut remove: impl FnMut(&Path) -> Result<DeleteReport, String>,
+) -> (u64, Vec<(String, String)>) {
+    let ctx = LogContext::new("足迹").with_target_name(name.to_string());
+    let mut freed = 0;
+    let mut failures = Vec::new();
+
+    for item in items {
+        let path = Path::new(&item.path);
+        let (targets, leftover_dirs) = match item.kind {
+            FootprintKind::Modified => continue,
+            _ if fs::symlink_metadata(path).is_err() => continue,
+            FootprintKind::AddedFile => (vec![path.to_path_buf()], Vec::new()),
+            FootprintKind::AddedDir => covered_entries(path, &item.contents),
+        };
+
+        let mut planned = false;
+        for target in targets {
+            match remove(&target) {
+                Ok(report) => {
+                    planned |= report.planned;
+                    freed += report.bytes_freed;
+                    failures.extend(
+                        report
+                            .failures
+                            .into_iter()
+                            .map(|(failed, reason)| (failed.to_string_lossy().to_string(), reason)),
+                    );
+                }
+                Err(e) => {
+                    logger::log_structured_error(&ctx, &format!("删除 {} 失败: {}", target.display(), e));
+                    failures.push((target.to_string_lossy().to_string(), e));
+                }
+            }
+        }
+        // 删除文件后留下的空目录，演练时不改动
+        if !planned {
+            for dir in leftover_dirs {
+                let _ = fs::remove_dir(dir);
+            }
+        }
+        logger::log_structured_info(&ctx, &format!("已处理 {}", item.path));
+    }
+    (freed, failures)
+}
+
+// 新增目录中仍由足迹记录的条目：内容未增加时整个目录一起删除，
+// 否则逐个删除记录过的文件，并返回由深到浅排列、之后尝试移除的记录过的子目录
+fn covered_entries(dir: &Path, contents: &[String]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let recorded: HashSet<&str> = contents.iter().map(String::as_str).collect();
+    let current = capture(dir);
+    if current.keys().all(|path| recorded.contains(path.as_str())) {
+        return (vec![dir.to_path_buf()], Vec::new());
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for (path, state) in current.iter().filter(|(path, _)| recorded.contains(path.as_str())) {
+        if state.is_dir {
+            dirs.push(path.as_str());
+        } else {
+            files.push(dir.join(path));
+        }
+    }
+    dirs.sort_by_key(|path| std::cmp::Reverse(path.matches('/').count()));
+    (files, dirs.into_iter().map(|path| dir.join(path)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_footprint_and_removal() {
+        let temp_dir = std::env::temp_dir().join("test_install_footprint");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("Existing")).unwrap();
+        fs::write(temp_dir.join("Existing/settings.ini"), "a=1").unwrap();
+        fs::write(temp_dir.join("Existing/keep.txt"), "keep").unwrap();
+
+        let baseline = capture(&temp_dir);
+
+        // 模拟安装：新建带子目录的文件夹、在已有目录中新增文件并修改一个文件
+        fs::create_dir_all(temp_dir.join("NewApp/cache")).unwrap();
+        fs::write(temp_dir.join("NewApp/cache/blob"), vec![0u8; 1000]).unwrap();
+        fs::write(temp_dir.join("NewApp/config.json"), vec![0u8; 24]).unwrap();
+        fs::write(temp_dir.join("Existing/plugin.dll"), vec![0u8; 500]).unwrap();
+        fs::write(temp_dir.join("Existing/settings.ini"), "a=1\nb=2").unwrap();
+
+        let items = compute_footprint(&temp_dir, &baseline, &capture(&temp_dir));
+        let summary: Vec<(String, FootprintKind, u64)> = items
+            .iter()
+            .map(|item| {
+                let relative = Path::new(&item.path).strip_prefix(&temp_dir).unwrap();
+                (relative.to_string_lossy().replace('\\', "/"), item.kind, item.size)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Existing/plugin.dll".to_string(), FootprintKind::AddedFile, 500),
+                ("Existing/settings.ini".to_string(), FootprintKind::Modified, 7),
+                ("NewApp".to_string(), FootprintKind::AddedDir, 1024),
+            ]
+        );
+
+        let new_app = items.iter().find(|item| item.kind == FootprintKind::AddedDir).unwrap();
+        assert_eq!(new_app.contents, vec!["cache", "cache/blob", "config.json"]);
+
+        // 记录足迹后新增的文件不属于足迹，删除时保留
+        fs::create_dir_all(temp_dir.join("NewApp/saves")).unwrap();
+        fs::write(temp_dir.join("NewApp/saves/game.sav"), "progress").unwrap();
+
+        let remove = |path: &Path| Ok(delete::remove_tree_with(path, |_| true));
+        let (freed, failures) = remove_footprint_with("test", &items, remove);
+        assert_eq!(freed, 1524);
+        assert!(failures.is_empty());
+        assert!(!temp_dir.join("NewApp/cache").exists());
+        assert!(!temp_dir.join("NewApp/config.json").exists());
+        assert!(temp_dir.join("NewApp/saves/game.sav").exists());
+        assert!(!temp_dir.join("Existing/plugin.dll").exists());
+        assert!(temp_dir.join("Existing/settings.ini").exists());
+        assert!(temp_dir.join("Existing/keep.txt").exists());
+
+        // 再次执行时已删除的条目直接跳过
+        assert_eq!(remove_footprint_with("test", &items, remove), (0, Vec::new()));
+
+        // 内容未增加的新增目录整个删除
+        fs::remove_dir_all(temp_dir.join("NewApp/saves")).unwrap();
+        assert_eq!(remove_footprint_with("test", &items, remove), (0, Vec::new()));
+        assert!(!temp_dir.join("NewApp").exists());
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
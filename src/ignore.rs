@@ -8,7 +8,7 @@ const IGNORE_FILE: &str = "adcignore.txt";
 pub fn load_ignored_folders() -> HashSet<String> {
     let mut ignored = HashSet::new();
     if let Ok(file) = fs::File::open(IGNORE_FILE) {
-        for line in io::BufReader::new(file).lines().flatten() {
+        for line in io::BufReader::new(file).lines().map_while(Result::ok) {
             ignored.insert(line);
         }
     }
@@ -1,18 +1,36 @@
 // mod about; // 关于界面
 pub mod ai_config; // 使用 pub 使其可以被其他模块访问
+mod apps; // 跨根目录按应用汇总文件夹
+mod cleanerml; // 导入 BleachBit CleanerML 清理规则
 mod confirmation; // 确认删除模块
 mod database; // 数据库模块
 mod delete; // 引入删除模块
+mod details; // 文件夹详情：最大文件与类型分布
+mod dry_run; // 演练模式：只记录将要执行的操作
+mod duplicates; // 跨根目录查找重复文件
+mod footprint; // 安装前后的文件足迹
 mod ignore; // 引入忽略模块
 mod logger; // 引入日志模块
 mod move_module; // 移动文件夹，使用 mklink 指令
 mod open; // 调用资源管理器打开文件夹
+mod orphan; // 判断文件夹的所属程序是否仍已安装
+mod quarantine; // 隔离区：可还原、到期自动清除
+mod roots; // 扫描根目录（AppData 与 XDG 目录）
+mod rules; // 按规则清理应用文件夹中的缓存
 mod scanner; // 引入扫盘模块
+mod search; // 跨根目录模糊搜索应用的残留文件夹
+mod snapshot; // 扫描快照的对比
+mod size; // 文件夹大小统计（不跟随链接，硬链接只计一次）
 mod stats; // 引入统计模块
 mod stats_logger; // 引入统计日志模块
 pub mod tabs;
+mod trash; // 按 freedesktop 规范移到回收站
+mod tree; // 可逐级展开的子文件夹树
+mod treemap; // 磁盘占用矩形树图
 mod ui; // 引入 ui 模块
 mod utils; // 文件夹大小计算模块
+mod watcher; // 监视文件变化，增量更新文件夹大小
+mod wine; // 查找 Wine 与 Proton 前缀
 mod yaml_loader; // 文件描述 // 添加tabs模块，使其可以被其他模块访问
 
 use ui::AppDataCleaner;
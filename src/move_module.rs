@@ -1,5 +1,6 @@
-use crate::logger;
-use dirs_next as dirs;
+use crate::dry_run::{self, PlannedAction};
+use crate::logger::{self, LogContext};
+use crate::utils;
 use eframe::egui;
 use native_dialog::FileDialog;
 use sha2::{Digest, Sha256};
@@ -12,7 +13,9 @@ use walkdir::WalkDir;
 
 pub struct MoveModule {
     pub show_window: bool,
-    pub folder_name: String,                         // 源文件夹名（相对路径）
+    pub source_root: PathBuf,                        // 源文件夹所在的根目录
+    pub profile: String,                             // 源文件夹所属的用户配置文件
+    pub folder_name: String,                         // 源文件夹名（相对路径，子文件夹以 / 分隔）
     pub selected_path: Option<PathBuf>,              // 目标路径
     pub progress: f32,                               // 复制进度
     pub status_message: Option<String>,              // 操作状态
@@ -32,6 +35,8 @@ impl Default for MoveModule {
     fn default() -> Self {
         Self {
             show_window: false,
+            source_root: PathBuf::new(),
+            profile: String::new(),
             folder_name: String::new(),
             selected_path: None,
             progress: 0.0,
@@ -41,6 +46,13 @@ impl Default for MoveModule {
     }
 }
 
+// 移动日志的上下文，标明配置文件与文件夹
+fn move_log_context(profile: &str, folder_name: &str) -> LogContext {
+    LogContext::new("移动")
+        .with_target_type(profile)
+        .with_target_name(folder_name)
+}
+
 impl MoveModule {
     pub fn show_move_window(&mut self, ctx: &egui::Context) {
         let receiver = self.receiver.take();
@@ -69,13 +81,19 @@ impl MoveModule {
                         self.status_message = Some(msg);
                         //self.receiver = None; // 完成后清除接收器
                         ctx.request_repaint();
-                        logger::log_info("文件夹移动操作成功完成");
+                        logger::log_structured_info(
+                            &move_log_context(&self.profile, &self.folder_name),
+                            "文件夹移动操作成功完成",
+                        );
                     }
                     ProgressMessage::Error(err) => {
                         self.status_message = Some(err.clone());
                         //self.receiver = None; // 错误后清除接收器
                         ctx.request_repaint();
-                        logger::log_error(&err);
+                        logger::log_structured_error(
+                            &move_log_context(&self.profile, &self.folder_name),
+                            &err,
+                        );
                     }
                 }
             }
@@ -144,16 +162,13 @@ impl MoveModule {
     }
 
     fn start_move_folder(&mut self, target_path: PathBuf) {
-        // 获取系统 AppData 路径
-        let appdata_path = dirs::data_dir()
-            .or_else(|| dirs::config_dir()) // 备用获取 Roaming 路径
-            .unwrap_or_else(|| PathBuf::from("%appdata%"));
-
-        let source_path = appdata_path.join(&self.folder_name);
+        // 源文件夹位于当前选中的根目录下
+        let source_path = self.source_root.join(&self.folder_name);
+        let ctx = move_log_context(&self.profile, &self.folder_name);
 
         // 调试日志打印完整路径
         println!("完整源文件夹路径: {}", source_path.display());
-        logger::log_info(&format!(
+        logger::log_structured_info(&ctx, &format!(
             "开始移动文件夹: {} -> {}",
             source_path.display(),
             target_path.display()
@@ -163,7 +178,28 @@ impl MoveModule {
         if !source_path.exists() {
             self.status_message = Some(format!("源文件夹不存在: {}", source_path.display()));
             println!("源文件夹不存在: {}", source_path.display());
-            logger::log_error(&format!("源文件夹不存在: {}", source_path.display()));
+            logger::log_structured_error(&ctx, &format!("源文件夹不存在: {}", source_path.display()));
+            return;
+        }
+
+        // 子文件夹只取最后一级名称放到目标目录下
+        let folder_name = Path::new(&self.folder_name)
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_else(|| self.folder_name.clone().into());
+        let target_folder_path = target_path.join(&folder_name);
+
+        // 演练模式只记录复制与创建链接，不启动后台线程
+        if dry_run::is_enabled() {
+            let actions = PlannedAction::relocation(&source_path, &target_folder_path);
+            self.status_message = Some(format!(
+                "演练：将复制 {} 个文件（{}）到 {}，并在原位置创建链接",
+                actions[0].files,
+                utils::format_size(actions[0].bytes),
+                target_folder_path.display()
+            ));
+            dry_run::record(actions);
+            logger::log_structured_info(&ctx, "演练模式，已记录到演练计划");
             return;
         }
 
@@ -172,9 +208,6 @@ impl MoveModule {
         self.progress = 0.0;
         self.status_message = Some("开始移动文件夹...".to_string());
 
-        let folder_name = self.folder_name.clone();
-        let target_folder_path = target_path.join(&folder_name);
-
         // 启动后台线程执行移动逻辑
         thread::spawn(move || {
             println!(
@@ -200,7 +233,7 @@ impl MoveModule {
 
             match verify_directory_hashes(&source_path, &target_folder_path, &tx) {
                 Ok(true) => {
-                    logger::log_info("哈希校验通过，所有文件完全一致");
+                    logger::log_structured_info(&ctx, "哈希校验通过，所有文件完全一致");
                     let _ = tx.send(ProgressMessage::Progress(
                         0.9,
                         "哈希校验通过，开始删除源目录...".to_string(),
@@ -238,7 +271,7 @@ impl MoveModule {
                     target_folder_path.display()
                 );
                 println!("即将执行命令: {}", mklink_cmd);
-                logger::log_info(&format!("即将执行命令: {}", mklink_cmd));
+                logger::log_structured_info(&ctx, &format!("即将执行命令: {}", mklink_cmd));
 
                 let output = std::process::Command::new("cmd")
                     .args([
@@ -257,7 +290,7 @@ impl MoveModule {
                             source_path.display(),
                             target_folder_path.display()
                         );
-                        logger::log_info(&success_msg);
+                        logger::log_structured_info(&ctx, &success_msg);
                         let _ = tx.send(ProgressMessage::Success(success_msg));
                     }
                     Ok(output) => {
@@ -288,7 +321,7 @@ impl MoveModule {
                             source_path.display(),
                             target_folder_path.display()
                         );
-                        logger::log_info(&success_msg);
+                        logger::log_structured_info(&ctx, &success_msg);
                         let _ = tx.send(ProgressMessage::Success(success_msg));
                     }
                 }
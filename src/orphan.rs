@@ -0,0 +1,298 @@
+use crate::logger;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// 文件夹的所属程序是否仍然安装
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Ownership {
+    /// 找到了同名的已安装程序
+    Installed,
+    /// 没有任何已安装程序与之对应，很可能是卸载后的残留
+    NotFound,
+    /// 无法判断（非 Linux 根目录、通用目录名或没有可用的程序列表）
+    #[default]
+    Unknown,
+}
+
+impl Ownership {
+    pub fn label(self) -> &'static str {
+        match self {
+            Ownership::Installed => "所属程序已安装",
+            Ownership::NotFound => "未找到所属程序",
+            Ownership::Unknown => "未知",
+        }
+    }
+}
+
+// 拆分名称时忽略的片段，它们几乎出现在所有程序名中
+const STOP_WORDS: [&str; 12] = [
+    "org", "com", "net", "app", "apps", "desktop", "bin", "lib", "common", "data", "the", "linux",
+];
+
+// 由桌面环境或多个程序共用的目录，无法归属到单个程序
+const GENERIC_FOLDERS: [&str; 24] = [
+    "autostart",
+    "applications",
+    "desktop-directories",
+    "environment.d",
+    "fontconfig",
+    "fonts",
+    "icons",
+    "menus",
+    "mime",
+    "systemd",
+    "user-dirs.dirs",
+    "user-dirs.locale",
+    "trash",
+    "keyrings",
+    "sounds",
+    "themes",
+    "backgrounds",
+    "thumbnails",
+    "recently-used.xbel",
+    "gtk-2.0",
+    "gtk-3.0",
+    "gtk-4.0",
+    "dconf",
+    "pulse",
+];
+
+// 只保留小写字母与数字，使 "Sublime Text" 与 "sublime-text" 等价
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// 名称中有意义的片段，如 org.mozilla.firefox 中的 mozilla 与 firefox
+fn tokens(name: &str) -> impl Iterator<Item = String> + '_ {
+    name.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|token| token.chars().count() >= 3 && !STOP_WORDS.contains(&token.as_str()))
+}
+
+/// 已安装程序的名称集合，用于判断文件夹是否还有所属程序
+#[derive(Debug, Clone, Default)]
+pub struct InstalledApps {
+    names: HashSet<String>,
+}
+
+impl InstalledApps {
+    /// 从本机收集已安装程序：.desktop 文件、PATH 中的可执行文件、
+    /// Flatpak 应用以及 dpkg/rpm 数据库中的软件包
+    #[cfg(target_os = "linux")]
+    pub fn collect() -> Self {
+        use dirs_next as dirs;
+        use std::env;
+        use std::path::PathBuf;
+
+        let mut apps = Self::default();
+        let home = dirs::home_dir();
+        let data_home = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+        // .desktop 文件
+        let data_dirs = env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        let mut application_dirs: Vec<PathBuf> = env::split_paths(&data_dirs).collect();
+        application_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+        if let Some(data_home) = &data_home {
+            application_dirs.push(data_home.clone());
+            application_dirs.push(data_home.join("flatpak/exports/share"));
+        }
+        for dir in application_dirs {
+            apps.add_desktop_dir(&dir.join("applications"));
+        }
+
+        // PATH 中的可执行文件
+        if let Some(path) = env::var_os("PATH") {
+            for dir in env::split_paths(&path) {
+                apps.add_bin_dir(&dir);
+            }
+        }
+
+        // Flatpak 应用 ID
+        apps.add_flatpak_dir(Path::new("/var/lib/flatpak/app"));
+        if let Some(data_home) = &data_home {
+            apps.add_flatpak_dir(&data_home.join("flatpak/app"));
+        }
+
+        // 软件包数据库
+        apps.add_dpkg_status(Path::new("/var/lib/dpkg/status"));
+        apps.add_rpmdb(Path::new("/var/lib/rpm/rpmdb.sqlite"));
+        apps.add_rpmdb(Path::new("/usr/lib/sysimage/rpm/rpmdb.sqlite"));
+
+        logger::log_info(&format!("已收集 {} 个已安装程序名称", apps.names.len()));
+        apps
+    }
+
+    /// 其他平台暂不支持，所有文件夹都标记为未知
+    #[cfg(not(target_os = "linux"))]
+    pub fn collect() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, name: &str) {
+        let normalized = normalize(name);
+        if !normalized.is_empty() {
+            self.names.insert(normalized);
+        }
+        self.names.extend(tokens(name));
+    }
+
+    /// 读取目录下的 .desktop 文件：文件名、Name、Exec 与 StartupWMClass
+    pub fn add_desktop_dir(&mut self, dir: &Path) {
+        for entry in WalkDir::new(dir).max_depth(2).into_iter().filter_map(Result::ok) {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("desktop") {
+                continue;
+            }
+            if let Some(stem) = path.file_stem() {
+                self.add(&stem.to_string_lossy());
+            }
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            for line in content.lines() {
+                if let Some(name) = line.strip_prefix("Name=") {
+                    self.add(name);
+                } else if let Some(class) = line.strip_prefix("StartupWMClass=") {
+                    self.add(class);
+                } else if let Some(exec) = line.strip_prefix("Exec=") {
+                    // 只取命令本身的文件名，忽略参数
+                    if let Some(command) = exec.split_whitespace().next() {
+                        let command = command.trim_matches('"');
+                        self.add(command.rsplit('/').next().unwrap_or(command));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 读取目录中的可执行文件名
+    pub fn add_bin_dir(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            self.add(&entry.file_name().to_string_lossy());
+        }
+    }
+
+    /// 读取 Flatpak 安装目录下的应用 ID，如 org.mozilla.firefox
+    pub fn add_flatpak_dir(&mut self, dir: &Path) {
+        self.add_bin_dir(dir);
+    }
+
+    /// 解析 dpkg 的 status 文件，只计入处于已安装状态的软件包
+    pub fn add_dpkg_status(&mut self, path: &Path) {
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+        for paragraph in content.split("\n\n") {
+            let mut package = None;
+            let mut installed = false;
+            for line in paragraph.lines() {
+                if let Some(name) = line.strip_prefix("Package: ") {
+                    package = Some(name.trim());
+                } else if let Some(status) = line.strip_prefix("Status: ") {
+                    installed = status.trim().ends_with(" installed");
+                }
+            }
+            if let (Some(package), true) = (package, installed) {
+                self.add(package);
+            }
+        }
+    }
+
+    /// 读取 rpm 的 SQLite 数据库中的软件包名索引
+    pub fn add_rpmdb(&mut self, path: &Path) {
+        if !path.is_file() {
+            return;
+        }
+        let names = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| {
+                let mut stmt = conn.prepare("SELECT key FROM Name")?;
+                let names = stmt
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(names)
+            });
+        match names {
+            Ok(names) => names.iter().for_each(|name| self.add(name)),
+            Err(e) => logger::log_error(&format!("读取 rpm 数据库 {} 失败: {}", path.display(), e)),
+        }
+    }
+
+    /// 判断文件夹是否还有所属程序
+    pub fn classify(&self, folder_name: &str) -> Ownership {
+        let normalized = normalize(folder_name);
+        if self.names.is_empty()
+            || normalized.is_empty()
+            || GENERIC_FOLDERS.iter().any(|generic| normalize(generic) == normalized)
+        {
+            return Ownership::Unknown;
+        }
+        if self.names.contains(&normalized) || tokens(folder_name).any(|token| self.names.contains(&token)) {
+            Ownership::Installed
+        } else {
+            Ownership::NotFound
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_against_collected_apps() {
+        let temp_dir = std::env::temp_dir().join("test_orphan_classify");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let applications = temp_dir.join("applications");
+        let bin = temp_dir.join("bin");
+        let flatpak = temp_dir.join("flatpak");
+        fs::create_dir_all(&applications).unwrap();
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(flatpak.join("org.mozilla.firefox")).unwrap();
+        fs::write(
+            applications.join("sublime_text.desktop"),
+            "[Desktop Entry]\nName=Sublime Text\nName[zh_CN]=文本编辑器\nExec=/opt/sublime_text/sublime_text %F\n",
+        )
+        .unwrap();
+        fs::write(bin.join("code"), "").unwrap();
+        fs::write(
+            temp_dir.join("status"),
+            "Package: vlc\nStatus: install ok installed\n\nPackage: gimp\nStatus: deinstall ok config-files\n",
+        )
+        .unwrap();
+
+        let mut apps = InstalledApps::default();
+        assert_eq!(apps.classify("Code"), Ownership::Unknown);
+
+        apps.add_desktop_dir(&applications);
+        apps.add_bin_dir(&bin);
+        apps.add_flatpak_dir(&flatpak);
+        apps.add_dpkg_status(&temp_dir.join("status"));
+        apps.add_rpmdb(&temp_dir.join("missing.sqlite"));
+
+        assert_eq!(apps.classify("Code"), Ownership::Installed);
+        assert_eq!(apps.classify("sublime-text"), Ownership::Installed);
+        assert_eq!(apps.classify("mozilla"), Ownership::Installed);
+        assert_eq!(apps.classify("vlc"), Ownership::Installed);
+        // 只剩配置文件的软件包视为已卸载
+        assert_eq!(apps.classify("GIMP"), Ownership::NotFound);
+        assert_eq!(apps.classify("Old Editor"), Ownership::NotFound);
+        assert_eq!(apps.classify("fontconfig"), Ownership::Unknown);
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,318 @@
+//! 隔离区：文件夹先移到工具管理的目录中，程序出问题时可以还原
+//!
+//! 每个条目独占隔离目录下的一个子目录，数据库记录原路径、大小与隔离时间。
+//! 超过设定天数的条目在程序启动时彻底删除。演练模式下还原与清除只记录到演练计划
+
+use crate::database::{get_default_db_path, Database, QuarantineEntry};
+use crate::dry_run::{self, PlannedAction};
+use crate::logger::{self, LogContext};
+use crate::utils;
+use chrono::{DateTime, Duration, Local, Utc};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 隔离目录，与数据库位于同一目录
+pub const QUARANTINE_DIR: &str = "quarantine";
+/// 自动清除天数在 settings 表中的键
+pub const EXPIRY_SETTING: &str = "quarantine_expiry_days";
+pub const DEFAULT_EXPIRY_DAYS: u32 = 30;
+
+pub fn get_default_quarantine_dir() -> PathBuf {
+    PathBuf::from(QUARANTINE_DIR)
+}
+
+/// 隔离条目保留的天数，0 表示不自动清除
+pub fn expiry_days(db: &Database) -> u32 {
+    db.get_setting(EXPIRY_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_EXPIRY_DAYS)
+}
+
+pub fn set_expiry_days(db: &Database, days: u32) -> Result<(), String> {
+    db.set_setting(EXPIRY_SETTING, &days.to_string())
+        .map_err(|e| format!("保存隔离天数失败: {}", e))
+}
+
+/// 条目是否已超过保留天数
+pub fn is_expired(entry: &QuarantineEntry, days: u32, now: DateTime<Utc>) -> bool {
+    days > 0 && now - entry.quarantined_at >= Duration::days(days as i64)
+}
+
+/// 把文件夹移到隔离区并记录到数据库，返回条目编号
+pub fn quarantine(
+    db: &Database,
+    quarantine_dir: &Path,
+    path: &Path,
+    profile: &str,
+    size: u64,
+) -> Result<i64, String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("路径没有文件名: {}", path.display()))?;
+    fs::create_dir_all(quarantine_dir).map_err(|e| format!("无法创建隔离目录: {}", e))?;
+    let quarantine_dir = quarantine_dir
+        .canonicalize()
+        .map_err(|e| format!("无法定位隔离目录: {}", e))?;
+
+    // 以创建目录的方式占用名称，同一秒内隔离多个文件夹时依次编号
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let mut container = None;
+    for attempt in 1..=1000 {
+        let candidate = quarantine_dir.join(format!("{}-{}", stamp, attempt));
+        match fs::create_dir(&candidate) {
+            Ok(()) => {
+                container = Some(candidate);
+                break;
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("无法创建隔离目录: {}", e)),
+        }
+    }
+    let container = container.ok_or_else(|| "无法创建隔离目录: 名称已用尽".to_string())?;
+    let stored = container.join(name);
+
+    if let Err(e) = move_path(path, &stored) {
+        let _ = fs::remove_dir(&container);
+        return Err(format!("移到隔离区失败: {}", e));
+    }
+    let original = path.to_string_lossy();
+    match db.add_quarantine_entry(&original, &stored.to_string_lossy(), profile, size) {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            // 没有记录就无法还原，移回原处
+            let _ = move_path(&stored, path);
+            let _ = fs::remove_dir(&container);
+            Err(format!("记录隔离条目失败: {}", e))
+        }
+    }
+}
+
+/// 把条目移回原位置；原位置已有同名文件夹时不覆盖
+pub fn restore(db: &Database, entry: &QuarantineEntry) -> Result<(), String> {
+    let original = Path::new(&entry.original_path);
+    let stored = Path::new(&entry.stored_path);
+    if fs::symlink_metadata(original).is_ok() {
+        return Err(format!("原位置已存在: {}", entry.original_path));
+    }
+    if fs::symlink_metadata(stored).is_err() {
+        return Err(format!("隔离区中的文件夹已不存在: {}", entry.stored_path));
+    }
+    if dry_run::is_enabled() {
+        dry_run::record([PlannedAction::transfer("还原", stored, original)]);
+        return Ok(());
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建上级目录: {}", e))?;
+    }
+    move_path(stored, original).map_err(|e| format!("还原失败: {}", e))?;
+    remove_container(stored);
+    db.remove_quarantine_entry(entry.id)
+        .map_err(|e| format!("更新数据库失败: {}", e))
+}
+
+/// 彻底删除条目；隔离区中的文件夹已被手动删除时只移除记录
+pub fn purge(db: &Database, entry: &QuarantineEntry) -> Result<(), String> {
+    let stored = Path::new(&entry.stored_path);
+    if dry_run::is_enabled() {
+        if fs::symlink_metadata(stored).is_ok() {
+            dry_run::record([PlannedAction::removal("彻底删除", stored)]);
+        }
+        return Ok(());
+    }
+    let result = match fs::symlink_metadata(stored) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(stored),
+        Ok(_) => fs::remove_file(stored),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    };
+    result.map_err(|e| format!("删除 {} 失败: {}", entry.stored_path, e))?;
+    remove_container(stored);
+    db.remove_quarantine_entry(entry.id)
+        .map_err(|e| format!("更新数据库失败: {}", e))
+}
+
+/// 清除所有过期条目，返回清除的数量、释放的字节数与失败原因
+pub fn purge_expired(db: &Database, days: u32, now: DateTime<Utc>) -> (usize, u64, Vec<String>) {
+    let entries = match db.list_quarantine_entries() {
+        Ok(entries) => entries,
+        Err(e) => return (0, 0, vec![format!("读取隔离区失败: {}", e)]),
+    };
+    let mut purged = 0;
+    let mut freed = 0;
+    let mut errors = Vec::new();
+    for entry in entries.iter().filter(|entry| is_expired(entry, days, now)) {
+        let ctx = LogContext::new("隔离区")
+            .with_target_type(entry.profile.clone())
+            .with_target_name(entry.original_path.clone());
+        match purge(db, entry) {
+            Ok(()) => {
+                purged += 1;
+                freed += entry.size;
+                logger::log_structured_info(&ctx, "已过期，彻底删除");
+            }
+            Err(e) => {
+                logger::log_structured_error(&ctx, &e);
+                errors.push(e);
+            }
+        }
+    }
+    (purged, freed, errors)
+}
+
+/// 程序启动时在后台清除默认数据库中的过期条目，演练模式下不清除
+pub fn auto_purge() {
+    if dry_run::is_enabled() {
+        logger::log_info("演练模式，跳过隔离区自动清除");
+        return;
+    }
+    let db = match Database::new(&get_default_db_path()) {
+        Ok(db) => db,
+        Err(e) => {
+            logger::log_error(&format!("无法打开数据库，跳过隔离区清理: {}", e));
+            return;
+        }
+    };
+    let (purged, freed, errors) = purge_expired(&db, expiry_days(&db), Utc::now());
+    if purged > 0 || !errors.is_empty() {
+        logger::log_info(&format!(
+            "隔离区自动清除 {} 项，释放 {}，{} 项失败",
+            purged,
+            utils::format_size(freed),
+            errors.len()
+        ));
+    }
+}
+
+// 条目的上级目录由隔离区独占，为空时一并删除
+fn remove_container(stored: &Path) {
+    if let Some(container) = stored.parent() {
+        let _ = fs::remove_dir(container);
+    }
+}
+
+// 同一分区内直接重命名，跨分区时先复制再删除原文件夹
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_tree(from, to) {
+                let _ = fs::remove_dir_all(to);
+                return Err(e);
+            }
+            if fs::symlink_metadata(from)?.is_dir() {
+                fs::remove_dir_all(from)
+            } else {
+                fs::remove_file(from)
+            }
+        }
+        result => result,
+    }
+}
+
+/// 复制整个目录树，符号链接复制链接本身
+pub fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in WalkDir::new(from).follow_root_links(false) {
+        let entry = entry.map_err(io::Error::other)?;
+        let relative = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = if relative.as_os_str().is_empty() {
+            to.to_path_buf()
+        } else {
+            to.join(relative)
+        };
+        let file_type = entry.file_type();
+        if file_type.is_symlink() {
+            copy_link(entry.path(), &target)?;
+        } else if file_type.is_dir() {
+            fs::create_dir(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_link(link: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(link)?, target)
+}
+
+#[cfg(windows)]
+fn copy_link(link: &Path, target: &Path) -> io::Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let destination = fs::read_link(link)?;
+    if fs::metadata(link).map(|metadata| metadata.is_dir()).unwrap_or(false) {
+        symlink_dir(destination, target)
+    } else {
+        symlink_file(destination, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_restore_and_expiry() {
+        let test_db_path = "test_quarantine.db";
+        let _ = fs::remove_file(test_db_path);
+        let temp_dir = std::env::temp_dir().join("test_quarantine");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let quarantine_dir = temp_dir.join("quarantine");
+        let folder = temp_dir.join("Roaming/OldApp");
+        fs::create_dir_all(folder.join("cache")).unwrap();
+        fs::write(folder.join("cache/data.bin"), vec![0u8; 100]).unwrap();
+
+        {
+            let db = Database::new(test_db_path).unwrap();
+            assert_eq!(expiry_days(&db), DEFAULT_EXPIRY_DAYS);
+            set_expiry_days(&db, 7).unwrap();
+            assert_eq!(expiry_days(&db), 7);
+
+            // 隔离后原位置消失，还原后回到原处
+            quarantine(&db, &quarantine_dir, &folder, "tester", 100).unwrap();
+            assert!(!folder.exists());
+            let entries = db.list_quarantine_entries().unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!((entries[0].size, entries[0].profile.as_str()), (100, "tester"));
+            assert!(Path::new(&entries[0].stored_path).join("cache/data.bin").exists());
+            restore(&db, &entries[0]).unwrap();
+            assert!(folder.join("cache/data.bin").exists());
+            assert!(db.list_quarantine_entries().unwrap().is_empty());
+            assert_eq!(fs::read_dir(&quarantine_dir).unwrap().count(), 0);
+
+            // 原位置已有同名文件夹时不还原
+            quarantine(&db, &quarantine_dir, &folder, "tester", 100).unwrap();
+            fs::create_dir_all(&folder).unwrap();
+            let entry = db.list_quarantine_entries().unwrap().remove(0);
+            assert!(restore(&db, &entry).is_err());
+
+            // 未到期的条目保留，到期后彻底删除
+            assert_eq!(purge_expired(&db, 7, Utc::now()).0, 0);
+            let later = Utc::now() + Duration::days(8);
+            assert!(!is_expired(&entry, 0, later));
+            let (purged, freed, errors) = purge_expired(&db, 7, later);
+            assert_eq!((purged, freed, errors.len()), (1, 100, 0));
+            assert!(!Path::new(&entry.stored_path).exists());
+            assert!(db.list_quarantine_entries().unwrap().is_empty());
+        }
+
+        // 复制目录树时保留符号链接
+        let copy = temp_dir.join("copy");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("cache/data.bin", folder.join("link")).unwrap();
+        fs::create_dir_all(folder.join("cache")).unwrap();
+        fs::write(folder.join("cache/data.bin"), "data").unwrap();
+        copy_tree(&folder, &copy).unwrap();
+        assert_eq!(fs::read_to_string(copy.join("cache/data.bin")).unwrap(), "data");
+        #[cfg(unix)]
+        assert!(fs::symlink_metadata(copy.join("link")).unwrap().file_type().is_symlink());
+
+        // 清理
+        fs::remove_file(test_db_path).unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
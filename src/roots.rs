@@ -0,0 +1,270 @@
+use dirs_next as dirs;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 根目录类别，决定描述文件中使用的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RootKind {
+    Roaming,
+    Local,
+    LocalLow,
+    XdgConfig, // ~/.config
+    XdgCache,  // ~/.cache
+    XdgData,   // ~/.local/share
+    XdgState,  // ~/.local/state
+}
+
+impl RootKind {
+    /// Windows AppData 下的根目录
+    pub const WINDOWS: [RootKind; 3] = [RootKind::Roaming, RootKind::Local, RootKind::LocalLow];
+
+    /// Linux XDG 基础目录
+    pub const XDG: [RootKind; 4] = [
+        RootKind::XdgConfig,
+        RootKind::XdgCache,
+        RootKind::XdgData,
+        RootKind::XdgState,
+    ];
+
+    /// 类别名，用作描述文件的键和本机根目录的数据库标识
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RootKind::Roaming => "Roaming",
+            RootKind::Local => "Local",
+            RootKind::LocalLow => "LocalLow",
+            RootKind::XdgConfig => "XdgConfig",
+            RootKind::XdgCache => "XdgCache",
+            RootKind::XdgData => "XdgData",
+            RootKind::XdgState => "XdgState",
+        }
+    }
+
+    /// 根据类别名解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::WINDOWS
+            .into_iter()
+            .chain(Self::XDG)
+            .find(|kind| kind.as_str() == name)
+    }
+
+    /// 界面上显示的名称
+    pub fn label(self) -> &'static str {
+        match self {
+            RootKind::Roaming => "Roaming",
+            RootKind::Local => "Local",
+            RootKind::LocalLow => "LocalLow",
+            RootKind::XdgConfig => "~/.config",
+            RootKind::XdgCache => "~/.cache",
+            RootKind::XdgData => "~/.local/share",
+            RootKind::XdgState => "~/.local/state",
+        }
+    }
+
+    /// 是否为 XDG 目录
+    pub fn is_xdg(self) -> bool {
+        Self::XDG.contains(&self)
+    }
+
+    /// 解析当前用户的该类根目录
+    fn resolve_native(self) -> Option<PathBuf> {
+        match self {
+            RootKind::Roaming => dirs::data_dir(), // Roaming 目录（跨设备同步的配置）
+            RootKind::Local => dirs::cache_dir(),  // Local 目录（本机应用数据）
+            RootKind::LocalLow => {
+                // 通过 APPDATA 环境变量推导路径
+                env::var("APPDATA").ok().and_then(|apdata| {
+                    let appdata_path = PathBuf::from(apdata);
+                    // 获取上级目录（即 AppData 文件夹）
+                    appdata_path
+                        .parent()
+                        .map(|appdata_dir| appdata_dir.join("LocalLow"))
+                })
+            }
+            RootKind::XdgConfig => xdg_dir("XDG_CONFIG_HOME", ".config"),
+            RootKind::XdgCache => xdg_dir("XDG_CACHE_HOME", ".cache"),
+            RootKind::XdgData => xdg_dir("XDG_DATA_HOME", ".local/share"),
+            RootKind::XdgState => xdg_dir("XDG_STATE_HOME", ".local/state"),
+        }
+    }
+}
+
+// 按 XDG 规范解析目录：环境变量为绝对路径时优先，否则使用家目录下的默认位置
+fn xdg_dir(env_key: &str, default_relative: &str) -> Option<PathBuf> {
+    env::var_os(env_key)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(default_relative)))
+}
+
+/// 可供扫描的根目录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanRoot {
+    /// 数据库中的 folder_type，本机根目录直接使用类别名以兼容旧数据库
+    pub id: String,
+    pub kind: RootKind,
+    pub path: PathBuf,
+    /// 界面上显示的名称
+    pub label: String,
+    /// 所属的用户配置文件，用于标记扫描结果、日志和数据库记录
+    pub profile: String,
+}
+
+impl ScanRoot {
+    /// 当前用户的根目录
+    pub fn native(kind: RootKind) -> Option<Self> {
+        kind.resolve_native().map(|path| Self {
+            id: kind.as_str().to_string(),
+            kind,
+            path,
+            label: kind.label().to_string(),
+            profile: current_user(),
+        })
+    }
+}
+
+/// 当前登录的用户名
+pub fn current_user() -> String {
+    env::var("USERNAME")
+        .or_else(|_| env::var("USER"))
+        .unwrap_or_else(|_| "当前用户".to_string())
+}
+
+/// 某个 AppData 目录下的 Roaming、Local、LocalLow 根目录
+///
+/// 用于 Wine 前缀等非本机用户的 AppData，`id_prefix` 保证数据库标识唯一，
+/// 类别不变，因此描述与忽略规则照常生效。`label` 为界面上显示在类别前的名称，
+/// `profile` 只是用户名，写入数据库与日志
+pub fn appdata_roots(appdata_dir: &Path, id_prefix: &str, label: &str, profile: &str) -> Vec<ScanRoot> {
+    RootKind::WINDOWS
+        .iter()
+        .map(|kind| ScanRoot {
+            id: format!("{}:{}", id_prefix, kind.as_str()),
+            kind: *kind,
+            path: appdata_dir.join(kind.as_str()),
+            label: format!("{} {}", label, kind.label()),
+            profile: profile.to_string(),
+        })
+        .collect()
+}
+
+// 系统自带的模板或公共配置文件，不作为可选用户
+const SYSTEM_PROFILES: [&str; 4] = ["Public", "Default", "Default User", "All Users"];
+
+/// 列出 Users 目录下拥有 AppData 的用户配置文件
+pub fn list_profiles(users_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(users_dir) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().join("AppData").is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !SYSTEM_PROFILES.contains(&name.as_str()))
+        .collect();
+    profiles.sort();
+    profiles
+}
+
+/// 挂载的 Windows 分区或磁盘镜像中某个用户的 AppData 根目录
+pub fn profile_roots(users_dir: &Path, profile: &str) -> Vec<ScanRoot> {
+    appdata_roots(
+        &users_dir.join(profile).join("AppData"),
+        &format!("profile:{}:{}", users_dir.display(), profile),
+        &format!("{} ({})", profile, users_dir.display()),
+        profile,
+    )
+}
+
+/// 与某个根目录属于同一用户的全部根目录，用于跨根目录汇总
+///
+/// Wine 前缀与离线配置文件的标识形如 `前缀:类别`，据此还原同一 AppData 下的其他根目录
+pub fn sibling_roots(root: &ScanRoot) -> Vec<ScanRoot> {
+    match (root.id.rsplit_once(':'), root.path.parent()) {
+        (Some((id_prefix, _)), Some(appdata_dir)) => {
+            let label = root.label.strip_suffix(root.kind.label()).unwrap_or(&root.label).trim_end();
+            appdata_roots(appdata_dir, id_prefix, label, &root.profile)
+        }
+        _ => native_roots(),
+    }
+}
+
+/// 当前平台上可选择的本机根目录
+pub fn native_roots() -> Vec<ScanRoot> {
+    let kinds: &[RootKind] = if cfg!(target_os = "linux") {
+        &RootKind::XDG
+    } else {
+        &RootKind::WINDOWS
+    };
+    kinds.iter().filter_map(|kind| ScanRoot::native(*kind)).collect()
+}
+
+/// 默认选中的根目录
+pub fn default_root() -> ScanRoot {
+    native_roots().into_iter().next().unwrap_or_else(|| ScanRoot {
+        id: RootKind::Roaming.as_str().to_string(),
+        kind: RootKind::Roaming,
+        path: PathBuf::new(),
+        label: RootKind::Roaming.label().to_string(),
+        profile: current_user(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_kind_name_roundtrip() {
+        for kind in RootKind::WINDOWS.into_iter().chain(RootKind::XDG) {
+            assert_eq!(RootKind::from_name(kind.as_str()), Some(kind));
+        }
+        assert_eq!(RootKind::from_name("Unknown"), None);
+        assert!(RootKind::XdgState.is_xdg());
+        assert!(!RootKind::LocalLow.is_xdg());
+    }
+
+    #[test]
+    fn test_offline_profile_roots() {
+        let users_dir = std::env::temp_dir().join("test_offline_profiles").join("Users");
+        let _ = fs::remove_dir_all(&users_dir);
+        for profile in ["alice", "bob", "Default", "Public"] {
+            fs::create_dir_all(users_dir.join(profile).join("AppData/Local")).unwrap();
+        }
+        // 没有 AppData 的目录不是用户配置文件
+        fs::create_dir_all(users_dir.join("share")).unwrap();
+
+        assert_eq!(list_profiles(&users_dir), vec!["alice".to_string(), "bob".to_string()]);
+
+        let roots = profile_roots(&users_dir, "alice");
+        assert_eq!(roots.len(), 3);
+        assert_eq!(roots[1].kind, RootKind::Local);
+        assert_eq!(roots[1].path, users_dir.join("alice/AppData/Local"));
+        for root in &roots {
+            assert_eq!(root.profile, "alice");
+            assert!(root.label.starts_with("alice ("));
+        }
+        // 不同配置文件的数据库标识互不相同
+        assert_ne!(roots[1].id, profile_roots(&users_dir, "bob")[1].id);
+        // 从任一根目录都能还原同一配置文件的全部根目录
+        assert_eq!(sibling_roots(&roots[2]), roots);
+
+        // 清理
+        fs::remove_dir_all(users_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_xdg_dir_env_override() {
+        // 使用测试专用的环境变量名，避免影响其他测试
+        let key = "CLEANAPPDATA_TEST_XDG_HOME";
+
+        env::set_var(key, "/tmp/xdg-config");
+        assert_eq!(xdg_dir(key, ".config"), Some(PathBuf::from("/tmp/xdg-config")));
+
+        // 相对路径按规范忽略，回退到家目录下的默认位置
+        env::set_var(key, "relative/config");
+        assert_eq!(xdg_dir(key, ".config"), dirs::home_dir().map(|home| home.join(".config")));
+
+        env::remove_var(key);
+    }
+}
@@ -0,0 +1,379 @@
+//! 按规则清理应用文件夹中的缓存、日志等可再生内容
+//!
+//! 规则文件与 folders_description.yaml 放在同一目录，格式如下：
+//!
+//! ```yaml
+//! rules:
+//!   - app: Code                 # 根目录下的应用文件夹名，只能是一层
+//!     roots: [Roaming, XdgConfig] # 可选，只在这些类别的根目录中生效
+//!     safety: safe              # safe、caution 或 risky
+//!     files_only: false         # 可选，为 true 时只删除匹配的文件，目录保留并继续向下查找
+//!     description: VS Code 缓存
+//!     paths:
+//!       - "Code Cache/**"       # 末尾的 ** 只清理目录中的内容，保留目录本身
+//!       - GPUCache              # 匹配的目录或文件整个删除
+//!       - "logs/*.log"
+//! ```
+//!
+//! 应用文件夹名与模式中不能出现 `.`、`..` 或绝对路径，匹配到的条目必须位于应用文件夹内
+
+use crate::delete::DeleteJob;
+use crate::logger;
+use crate::roots::ScanRoot;
+use crate::size;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 规则文件名，与 folders_description.yaml 位于同一目录
+pub const RULES_FILE: &str = "cleaning_rules.yaml";
+
+/// 清理的安全程度，界面只执行不超过所选等级的规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafetyLevel {
+    /// 纯缓存，删除后程序会自动重建
+    Safe,
+    /// 日志、崩溃转储等，删除后会丢失排查问题的记录
+    Caution,
+    /// 可能包含用户数据或登录状态
+    Risky,
+}
+
+impl SafetyLevel {
+    pub const ALL: [SafetyLevel; 3] = [SafetyLevel::Safe, SafetyLevel::Caution, SafetyLevel::Risky];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SafetyLevel::Safe => "安全",
+            SafetyLevel::Caution => "谨慎",
+            SafetyLevel::Risky => "有风险",
+        }
+    }
+}
+
+/// 一条清理规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleaningRule {
+    pub app: String,
+    /// 根目录类别名，为空时适用于所有根目录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<String>,
+    pub safety: SafetyLevel,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub paths: Vec<String>,
+    /// 只删除文件，保留目录
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub files_only: bool,
+}
+
+impl CleaningRule {
+    /// 检查应用文件夹名与模式，不合法的规则不会匹配任何条目
+    pub fn validate(&self) -> Result<(), String> {
+        let mut components = Path::new(&self.app).components();
+        if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+            return Err(format!("应用文件夹名必须是一层文件夹: {}", self.app));
+        }
+        match self.paths.iter().find(|pattern| !is_relative_pattern(pattern)) {
+            Some(pattern) => Err(format!("模式不能包含 . 、.. 或绝对路径: {}", pattern)),
+            None => Ok(()),
+        }
+    }
+}
+
+// 模式只能是应用文件夹内以 / 分隔的相对路径
+fn is_relative_pattern(pattern: &str) -> bool {
+    !pattern.is_empty()
+        && !pattern.starts_with('/')
+        && !pattern.contains('\\')
+        && !Path::new(pattern).has_root()
+        && pattern.split('/').all(|segment| !segment.contains(':') && segment != "." && segment != "..")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RuleFile {
+    pub rules: Vec<CleaningRule>,
+}
+
+impl RuleFile {
+    pub fn load_from_yaml(file_path: &str) -> Result<Self, String> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err("规则文件未找到".to_string());
+        }
+        let content = fs::read_to_string(path).map_err(|e| format!("读取规则文件失败: {}", e))?;
+        serde_yaml::from_str(&content).map_err(|e| format!("解析规则文件失败: {}", e))
+    }
+
+    pub fn save_to_yaml(&self, file_path: &str) -> Result<(), String> {
+        let content = serde_yaml::to_string(self).map_err(|e| format!("序列化规则失败: {}", e))?;
+        fs::write(file_path, content).map_err(|e| format!("写入规则文件失败: {}", e))
+    }
+}
+
+/// 规则匹配到的一个待清理条目
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanTarget {
+    pub app: String,
+    pub safety: SafetyLevel,
+    pub root_label: String,
+    pub profile: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// 判断以 `/` 分隔的相对路径是否匹配规则中的模式
+///
+/// 每段中 `*` 匹配任意个字符、`?` 匹配一个字符；`**` 匹配任意层目录，
+/// 位于末尾时至少匹配一层，即只匹配目录中的内容
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|segment| !segment.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", [])) => !path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path_rest)| match_segment(segment, name) && match_segments(rest, path_rest)),
+    }
+}
+
+// 单段通配符匹配，Windows 上文件名不区分大小写
+fn match_segment(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) = if cfg!(windows) {
+        (pattern.to_lowercase().chars().collect(), name.to_lowercase().chars().collect())
+    } else {
+        (pattern.chars().collect(), name.chars().collect())
+    };
+
+    // 回溯到最近一个 * 继续尝试
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 规则在某个根目录中匹配到的条目
+///
+/// 匹配的目录不再向下查找；不跟随符号链接，链接本身作为文件处理。
+/// 不合法的规则、指向根目录之外的应用文件夹都不匹配任何条目
+pub fn plan_rule(rule: &CleaningRule, root: &ScanRoot) -> Vec<CleanTarget> {
+    if !rule.roots.is_empty() && !rule.roots.iter().any(|kind| kind == root.kind.as_str()) {
+        return Vec::new();
+    }
+    if let Err(e) = rule.validate() {
+        logger::log_error(&format!("跳过规则: {}", e));
+        return Vec::new();
+    }
+    let app_dir = root.path.join(&rule.app);
+    if !app_dir.is_dir() {
+        return Vec::new();
+    }
+    // 应用文件夹是指向别处的链接时，其中的内容不属于该根目录
+    let Ok(app_real) = app_dir.canonicalize() else {
+        return Vec::new();
+    };
+    if !root.path.canonicalize().is_ok_and(|root_real| app_real.starts_with(root_real)) {
+        logger::log_error(&format!("跳过规则: {} 不在 {} 中", app_dir.display(), root.path.display()));
+        return Vec::new();
+    }
+
+    let mut targets = Vec::new();
+    let mut entries = WalkDir::new(&app_dir).min_depth(1).into_iter();
+    while let Some(entry) = entries.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let relative = entry
+            .path()
+            .strip_prefix(&app_dir)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        if !rule.paths.iter().any(|pattern| glob_match(pattern, &relative)) {
+            continue;
+        }
+        // 条目本身可以是链接，但其所在目录必须仍在应用文件夹内
+        let parent_real = entry.path().parent().and_then(|parent| parent.canonicalize().ok());
+        if !parent_real.is_some_and(|parent| parent.starts_with(&app_real)) {
+            continue;
+        }
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && rule.files_only {
+            continue;
+        }
+        let size = if is_dir {
+            entries.skip_current_dir();
+            size::measure(entry.path(), &mut Vec::new()).apparent
+        } else {
+            entry.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+        };
+        targets.push(CleanTarget {
+            app: rule.app.clone(),
+            safety: rule.safety,
+            root_label: root.label.clone(),
+            profile: root.profile.clone(),
+            path: entry.into_path(),
+            is_dir,
+            size,
+        });
+    }
+    targets
+}
+
+/// 所有不超过 `max_safety` 的规则在各根目录中匹配到的条目
+pub fn plan(rules: &[CleaningRule], roots: &[ScanRoot], max_safety: SafetyLevel) -> Vec<CleanTarget> {
+    rules
+        .iter()
+        .filter(|rule| rule.safety <= max_safety)
+        .flat_map(|rule| roots.iter().flat_map(move |root| plan_rule(rule, root)))
+        .collect()
+}
+
+/// 匹配到的条目对应的删除任务，交给 `DeleteWorker` 经由 `delete::delete_path` 删除；
+/// 任务名为条目的完整路径
+pub fn delete_jobs(targets: &[CleanTarget]) -> Vec<DeleteJob> {
+    targets
+        .iter()
+        .map(|target| DeleteJob {
+            folder: target.path.to_string_lossy().to_string(),
+            path: target.path.clone(),
+            profile: target.profile.clone(),
+            size: target.size,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::delete::{self, DeleteMode};
+    use crate::roots;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("GPUCache", "GPUCache"));
+        assert!(!glob_match("GPUCache", "GPUCache/data_0"));
+        assert!(glob_match("logs/*.log", "logs/main.log"));
+        assert!(!glob_match("logs/*.log", "logs/old/main.log"));
+        assert!(glob_match("Code Cache/**", "Code Cache/js/index"));
+        assert!(!glob_match("Code Cache/**", "Code Cache"));
+        assert!(glob_match("**/Cache", "Default/Service Worker/Cache"));
+        assert!(glob_match("crash?.dmp", "crash1.dmp"));
+        assert!(glob_match("*a*b", "xxaxxb"));
+        assert!(!glob_match("*a*b", "xxaxxbc"));
+    }
+
+    #[test]
+    fn test_plan_and_apply_rules() {
+        let temp_dir = std::env::temp_dir().join("test_cleaning_rules");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let roots = roots::appdata_roots(&temp_dir, "test", "tester", "tester");
+        let app = temp_dir.join("Roaming/Code");
+        fs::create_dir_all(app.join("Code Cache/js")).unwrap();
+        fs::write(app.join("Code Cache/js/index"), vec![0u8; 300]).unwrap();
+        fs::create_dir_all(app.join("GPUCache")).unwrap();
+        fs::write(app.join("GPUCache/data_0"), vec![0u8; 200]).unwrap();
+        fs::create_dir_all(app.join("logs")).unwrap();
+        fs::write(app.join("logs/main.log"), vec![0u8; 50]).unwrap();
+        fs::write(app.join("settings.json"), "{}").unwrap();
+
+        let file: RuleFile = serde_yaml::from_str(
+            r#"
+rules:
+  - app: Code
+    roots: [Roaming]
+    safety: safe
+    paths: ["Code Cache/**", GPUCache]
+  - app: Code
+    safety: caution
+    paths: ["logs/*.log"]
+"#,
+        )
+        .unwrap();
+
+        let targets = plan(&file.rules, &roots, SafetyLevel::Safe);
+        let total: u64 = targets.iter().map(|target| target.size).sum();
+        assert_eq!((targets.len(), total), (2, 500));
+        assert_eq!(plan(&file.rules, &roots, SafetyLevel::Risky).len(), 3);
+
+        // 应用文件夹名或模式越出应用文件夹的规则不匹配任何条目
+        let escaping: RuleFile = serde_yaml::from_str(
+            r#"
+rules:
+  - app: ../Roaming/Code
+    safety: safe
+    paths: [GPUCache]
+  - app: Code
+    safety: safe
+    paths: ["../Code/GPUCache", "./GPUCache", /tmp]
+"#,
+        )
+        .unwrap();
+        assert!(escaping.rules.iter().all(|rule| rule.validate().is_err()));
+        assert!(plan(&escaping.rules, &roots, SafetyLevel::Risky).is_empty());
+
+        // 只删除文件的规则不匹配其中的目录，继续在目录中查找文件
+        let files_only = CleaningRule {
+            files_only: true,
+            ..file.rules[0].clone()
+        };
+        let matched: Vec<PathBuf> = plan(&[files_only], &roots, SafetyLevel::Safe)
+            .into_iter()
+            .map(|target| target.path)
+            .collect();
+        assert_eq!(matched, vec![app.join("Code Cache/js/index")]);
+
+        let test_db_path = "test_rule_reports.db";
+        let _ = fs::remove_file(test_db_path);
+        let db = Database::new(test_db_path).unwrap();
+        let jobs = delete_jobs(&targets);
+        assert_eq!(jobs[0].folder, targets[0].path.to_string_lossy());
+        let freed: u64 = jobs
+            .iter()
+            .map(|job| delete::delete_path(&db, &job.path, &job.profile, DeleteMode::Permanent, false, |_| true))
+            .map(|result| result.unwrap().bytes_freed)
+            .sum();
+        assert_eq!(freed, 500);
+        assert_eq!(db.list_delete_reports(10).unwrap().len(), 2);
+        // 末尾为 ** 的规则保留目录本身
+        assert!(app.join("Code Cache").is_dir());
+        assert!(!app.join("Code Cache/js").exists());
+        assert!(!app.join("GPUCache").exists());
+        assert!(app.join("logs/main.log").exists());
+        assert!(app.join("settings.json").exists());
+
+        // 清理
+        drop(db);
+        fs::remove_file(test_db_path).unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
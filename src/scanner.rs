@@ -1,200 +1,783 @@
-use std::env;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
 use std::path::Path;
-use std::sync::mpsc::Sender;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
 use std::thread;
+use std::time::SystemTime;
 use std::{fs, path::PathBuf};
 
-use crate::database::{Database, FolderRecord, get_default_db_path, database_exists};
-use crate::logger;
-use chrono::Utc;
-use dirs_next as dirs; // 引入日志模块
+use crate::database::{Database, FolderFingerprint, FolderRecord, get_default_db_path, database_exists};
+use crate::logger::{self, LogContext};
+use crate::orphan::{InstalledApps, Ownership};
+use crate::roots::ScanRoot;
+use crate::size::{self, FolderSize};
+use chrono::{DateTime, Utc};
 
-pub fn scan_appdata(tx: Sender<(String, u64)>, folder_type: &str) {
-    println!("开始扫描 {} 类型的文件夹", folder_type);
+/// 扫描过程中发送给界面的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    /// 扫描开始，`from_cache` 表示是否先加载了数据库缓存
+    Started { from_cache: bool },
+    /// 新出现的文件夹（包括从缓存加载的记录）
+    FolderAdded { name: String, size: u64 },
+    /// 已显示的文件夹大小发生变化
+    FolderChanged { name: String, old_size: u64, new_size: u64 },
+    /// 缓存中的文件夹已不存在
+    FolderRemoved { name: String },
+    /// 文件夹的磁盘占用、链接与活动时间；装箱以免每个事件都按它的大小分配
+    FolderMeta { name: String, meta: Box<FolderMeta> },
+    /// 已完成计算的顶层文件夹数量
+    Progress { done: usize, total: usize },
+    /// 某个路径无法扫描
+    Error { path: PathBuf, kind: ScanErrorKind },
+    /// 扫描结束
+    Completed { stats: ScanStats },
+}
+
+/// 文件夹的附加信息，界面按文件夹名单独保存
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FolderMeta {
+    /// 实际占用的磁盘空间
+    pub disk_size: u64,
+    /// 文件夹本身是符号链接时的目标
+    pub link_target: Option<PathBuf>,
+    /// 文件夹内最近的修改时间
+    pub last_modified: Option<DateTime<Utc>>,
+    /// 文件夹内文件最近的访问时间
+    pub last_accessed: Option<DateTime<Utc>>,
+    /// 所属程序是否仍已安装，每次扫描时重新判断
+    pub ownership: Ownership,
+}
+
+impl FolderMeta {
+    /// 最近一次使用的时间：修改与访问中较晚的一个
+    pub fn last_used(&self) -> Option<DateTime<Utc>> {
+        self.last_modified.max(self.last_accessed)
+    }
+}
+
+impl From<&FolderRecord> for FolderMeta {
+    fn from(record: &FolderRecord) -> Self {
+        Self {
+            disk_size: record.disk_size.unwrap_or(record.folder_size),
+            link_target: record.link_target.as_ref().map(PathBuf::from),
+            // 旧版本记录的 last_modified 只是扫描时间，迁移时已清除指纹
+            last_modified: record.fingerprint.map(|_| record.last_modified),
+            last_accessed: record.last_accessed,
+            ownership: Ownership::Unknown,
+        }
+    }
+}
+
+/// 附带来源的扫描事件
+///
+/// 界面切换根目录后，旧扫描线程仍可能继续发送事件，按 `root_id` 丢弃即可
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanMessage {
+    /// 事件所属根目录的标识（即数据库中的 folder_type）
+    pub root_id: String,
+    /// 事件所属的用户配置文件
+    pub profile: String,
+    pub event: ScanEvent,
+}
+
+// 为每个事件附加根目录与配置文件标记
+struct EventSender {
+    tx: Sender<ScanMessage>,
+    root_id: String,
+    profile: String,
+}
+
+impl EventSender {
+    fn new(tx: Sender<ScanMessage>, root: &ScanRoot) -> Self {
+        Self {
+            tx,
+            root_id: root.id.clone(),
+            profile: root.profile.clone(),
+        }
+    }
+
+    fn send(&self, event: ScanEvent) -> Result<(), mpsc::SendError<ScanMessage>> {
+        self.tx.send(ScanMessage {
+            root_id: self.root_id.clone(),
+            profile: self.profile.clone(),
+            event,
+        })
+    }
+}
+
+/// 扫描错误类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanErrorKind {
+    /// 无法读取目录或文件元数据
+    Io(io::ErrorKind),
+    /// 扫描根目录无法解析
+    RootUnavailable,
+    /// 数据库等内部错误
+    Internal(String),
+}
+
+impl fmt::Display for ScanErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanErrorKind::Io(kind) => write!(f, "无法访问: {}", kind),
+            ScanErrorKind::RootUnavailable => write!(f, "无法定位扫描目录"),
+            ScanErrorKind::Internal(msg) => write!(f, "内部错误: {}", msg),
+        }
+    }
+}
+
+/// 一次扫描的汇总信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanStats {
+    pub total_folders: usize,
+    pub total_size: u64,
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+    pub errors: usize,
+    pub from_cache: usize, // 指纹未变化、直接复用缓存大小的文件夹数
+    pub recomputed: usize, // 重新计算大小的文件夹数
+}
+
+/// 单个顶层文件夹的扫描结果
+#[derive(Debug, Clone)]
+struct FolderScan {
+    name: String,
+    size: FolderSize,
+    link_target: Option<PathBuf>,
+    fingerprint: Option<FolderFingerprint>,
+    from_cache: bool, // 指纹未变化，大小取自缓存
+    /// 扫描过程中无法访问的路径
+    errors: Vec<(PathBuf, io::ErrorKind)>,
+}
+
+/// 扫描结果回调的返回类型
+type ScanCallbackResult = Result<(), Box<dyn std::error::Error>>;
+
+/// 扫描日志的上下文，标明配置文件与根目录
+fn scan_log_context(root: &ScanRoot) -> LogContext {
+    LogContext::new("扫描")
+        .with_target_type(root.profile.clone())
+        .with_target_name(root.label.clone())
+}
+
+pub fn scan_appdata(tx: Sender<ScanMessage>, root: &ScanRoot) {
+    println!("开始扫描 {} 类型的文件夹", root.label);
     // 记录日志
-    logger::log_info(&format!("开始扫描 {} 类型的文件夹", folder_type));
+    logger::log_structured_info(
+        &scan_log_context(root),
+        &format!("开始扫描: {}", root.path.display()),
+    );
+
+    let root = root.clone();
 
-    let folder_type = folder_type.to_string();
-    
     thread::spawn(move || {
-        if let Err(e) = scan_with_database(tx.clone(), &folder_type) {
-            logger::log_error(&format!("扫描过程中发生错误: {}", e));
+        let tx = EventSender::new(tx, &root);
+        if let Err(e) = scan_with_database(&tx, &root) {
+            logger::log_structured_error(&scan_log_context(&root), &format!("扫描过程中发生错误: {}", e));
+            let _ = tx.send(ScanEvent::Error {
+                path: PathBuf::from(get_default_db_path()),
+                kind: ScanErrorKind::Internal(e.to_string()),
+            });
             // 发送扫描完成信号
-            let _ = tx.send(("__SCAN_COMPLETE__".to_string(), 0));
+            let _ = tx.send(ScanEvent::Completed {
+                stats: ScanStats {
+                    errors: 1,
+                    ..Default::default()
+                },
+            });
         }
     });
 }
 
-fn scan_with_database(tx: Sender<(String, u64)>, folder_type: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn scan_with_database(tx: &EventSender, root: &ScanRoot) -> Result<(), Box<dyn std::error::Error>> {
+    let folder_type = root.id.as_str();
+    let ctx = scan_log_context(root);
     let db_path = get_default_db_path();
     let db_exists = database_exists(&db_path);
-    
+
     // 打开或创建数据库
     let db = Database::new(&db_path)?;
-    
+
     // 如果数据库存在且有该类型的数据，先从数据库加载
-    if db_exists && db.has_data_for_type(folder_type)? {
-        logger::log_info(&format!("从数据库加载 {} 类型的文件夹数据", folder_type));
-        
-        // 发送状态消息（使用特殊前缀识别）
-        tx.send(("__STATUS__从缓存加载数据...".to_string(), 0))?;
-        
-        let cached_records = db.get_folders_by_type(folder_type)?;
-        for record in &cached_records {
-            tx.send((record.folder_name.clone(), record.folder_size))?;
-        }
-        
-        logger::log_info(&format!("从数据库加载了 {} 个文件夹记录", cached_records.len()));
-        
-        // 发送状态消息
-        tx.send(("__STATUS__正在检查文件系统变化...".to_string(), 0))?;
-    }
-    
-    // 执行实际的文件系统扫描
-    let fs_scan_results = perform_filesystem_scan(folder_type)?;
-    
+    let existing_records = if db_exists && db.has_data_for_type(folder_type)? {
+        db.get_folders_by_type(folder_type)?
+    } else {
+        Vec::new()
+    };
+    tx.send(ScanEvent::Started {
+        from_cache: !existing_records.is_empty(),
+    })?;
+
+    if !existing_records.is_empty() {
+        logger::log_structured_info(&ctx, &format!("从数据库加载 {} 的文件夹数据", folder_type));
+        for record in &existing_records {
+            tx.send(ScanEvent::FolderAdded {
+                name: record.folder_name.clone(),
+                size: record.folder_size,
+            })?;
+            tx.send(ScanEvent::FolderMeta {
+                name: record.folder_name.clone(),
+                meta: Box::new(FolderMeta::from(record)),
+            })?;
+        }
+        logger::log_structured_info(&ctx, &format!("从数据库加载了 {} 个文件夹记录", existing_records.len()));
+    }
+
+    let mut stats = ScanStats::default();
+
+    // 列出根目录下的顶层文件夹
+    if root.path.as_os_str().is_empty() {
+        logger::log_structured_error(&ctx, &format!("无法定位 {} 类型的扫描目录", folder_type));
+        stats.errors += 1;
+        tx.send(ScanEvent::Error {
+            path: PathBuf::from(folder_type),
+            kind: ScanErrorKind::RootUnavailable,
+        })?;
+        tx.send(ScanEvent::Completed { stats })?;
+        return Ok(());
+    }
+    let folders = match list_top_level_folders(&root.path) {
+        Ok(folders) => folders,
+        Err(e) => {
+            logger::log_structured_error(&ctx, &format!("无法读取目录 {}: {}", root.path.display(), e));
+            stats.errors += 1;
+            tx.send(ScanEvent::Error {
+                path: root.path.clone(),
+                kind: ScanErrorKind::Io(e.kind()),
+            })?;
+            tx.send(ScanEvent::Completed { stats })?;
+            return Ok(());
+        }
+    };
+
+    // 已缓存的记录，用于在扫描过程中判断新增或变化
+    let existing_sizes: HashMap<String, u64> = existing_records
+        .iter()
+        .map(|r| (r.folder_name.clone(), r.folder_size))
+        .collect();
+    // 带指纹的缓存记录，指纹未变化的文件夹跳过大小计算
+    let cached_fingerprints: HashMap<String, (FolderFingerprint, FolderSize)> = existing_records
+        .iter()
+        .filter_map(|r| {
+            let size = FolderSize {
+                apparent: r.folder_size,
+                on_disk: r.disk_size?,
+                last_modified: Some(r.last_modified),
+                last_accessed: r.last_accessed,
+            };
+            r.fingerprint.map(|fp| (r.folder_name.clone(), (fp, size)))
+        })
+        .collect();
+
+    // 只有 XDG 目录中的文件夹能与本机安装的程序对应
+    let installed_apps = if root.kind.is_xdg() {
+        InstalledApps::collect()
+    } else {
+        InstalledApps::default()
+    };
+
+    // 执行实际的文件系统扫描，每完成一个文件夹立即把结果推送给界面
+    logger::log_structured_info(&ctx, &format!("开始计算 {} 个文件夹的大小", folders.len()));
+    let fs_scan_results = scan_folders_parallel(folders, &cached_fingerprints, |scan, done, total| {
+        if scan.from_cache {
+            stats.from_cache += 1;
+        } else {
+            stats.recomputed += 1;
+        }
+        for (path, kind) in &scan.errors {
+            stats.errors += 1;
+            tx.send(ScanEvent::Error {
+                path: path.clone(),
+                kind: ScanErrorKind::Io(*kind),
+            })?;
+        }
+        if let Some(event) = reconcile_folder(&existing_sizes, &scan.name, scan.size.apparent) {
+            match &event {
+                ScanEvent::FolderChanged { name, old_size, new_size } => {
+                    stats.changed += 1;
+                    logger::log_structured_info(&ctx, &format!(
+                        "检测到文件夹 '{}' 大小变化: {} -> {}",
+                        name, old_size, new_size
+                    ));
+                }
+                ScanEvent::FolderAdded { name, .. } => {
+                    stats.added += 1;
+                    if !existing_sizes.is_empty() {
+                        logger::log_structured_info(&ctx, &format!("发现新文件夹: {}", name));
+                    }
+                }
+                _ => {}
+            }
+            tx.send(event)?;
+        }
+        tx.send(ScanEvent::FolderMeta {
+            name: scan.name.clone(),
+            meta: Box::new(folder_meta(scan, &installed_apps)),
+        })?;
+        tx.send(ScanEvent::Progress { done, total })?;
+        Ok(())
+    })?;
+
+    // 检查是否有文件夹被删除
+    for existing in &existing_records {
+        if !fs_scan_results.iter().any(|scan| scan.name == existing.folder_name) {
+            stats.removed += 1;
+            logger::log_structured_info(&ctx, &format!("文件夹已被删除: {}", existing.folder_name));
+            tx.send(ScanEvent::FolderRemoved {
+                name: existing.folder_name.clone(),
+            })?;
+        }
+    }
+
+    stats.total_folders = fs_scan_results.len();
+    stats.total_size = fs_scan_results.iter().map(|scan| scan.size.apparent).sum();
+
     if !fs_scan_results.is_empty() {
         // 创建文件夹记录
         let folder_records: Vec<FolderRecord> = fs_scan_results
             .iter()
-            .map(|(name, size)| FolderRecord {
-                id: None,
-                folder_type: folder_type.to_string(),
-                folder_name: name.clone(),
-                folder_size: *size,
-                last_modified: Utc::now(),
-                created_at: Utc::now(),
-                updated_at: Utc::now(),
-            })
+            .map(|scan| folder_record(root, scan))
             .collect();
-        
-        // 如果数据库中已有数据，比较并更新；否则直接插入
-        if db_exists && db.has_data_for_type(folder_type)? {
-            // 获取现有记录进行比较
-            let existing_records = db.get_folders_by_type(folder_type)?;
-            let mut changes_detected = false;
-            
-            // 检查是否有变化
-            for new_record in &folder_records {
-                if let Some(existing) = existing_records.iter().find(|r| r.folder_name == new_record.folder_name) {
-                    // 如果大小发生变化，发送更新的数据
-                    if existing.folder_size != new_record.folder_size {
-                        tx.send((new_record.folder_name.clone(), new_record.folder_size))?;
-                        changes_detected = true;
-                        logger::log_info(&format!(
-                            "检测到文件夹 '{}' 大小变化: {} -> {}", 
-                            new_record.folder_name,
-                            existing.folder_size,
-                            new_record.folder_size
-                        ));
-                    }
-                } else {
-                    // 新文件夹
-                    tx.send((new_record.folder_name.clone(), new_record.folder_size))?;
-                    changes_detected = true;
-                    logger::log_info(&format!("发现新文件夹: {}", new_record.folder_name));
-                }
-            }
-            
-            // 检查是否有文件夹被删除
-            let new_folder_names: Vec<String> = folder_records.iter().map(|r| r.folder_name.clone()).collect();
-            for existing in &existing_records {
-                if !new_folder_names.contains(&existing.folder_name) {
-                    changes_detected = true;
-                    logger::log_info(&format!("文件夹已被删除: {}", existing.folder_name));
-                }
-            }
-            
-            if changes_detected {
-                logger::log_info("检测到变化，更新数据库");
-            } else {
-                logger::log_info("未检测到变化，使用缓存数据");
-            }
+
+        if existing_records.is_empty() {
+            logger::log_structured_info(&ctx, "第一次扫描，创建数据库记录");
+        } else if stats.added + stats.changed + stats.removed > 0 {
+            logger::log_structured_info(&ctx, "检测到变化，更新数据库");
         } else {
-            // 第一次扫描，发送所有结果
-            logger::log_info("第一次扫描，创建数据库记录");
-            for (name, size) in &fs_scan_results {
-                tx.send((name.clone(), *size))?;
-            }
+            logger::log_structured_info(&ctx, "未检测到变化，使用缓存数据");
         }
-        
+
         // 更新数据库
         db.batch_upsert_folders(&folder_records)?;
-        
+
         // 清理不存在的文件夹记录
         let existing_folder_names: Vec<String> = folder_records.iter().map(|r| r.folder_name.clone()).collect();
         db.remove_missing_folders(folder_type, &existing_folder_names)?;
-        
-        logger::log_info(&format!("数据库更新完成，共处理 {} 个文件夹", folder_records.len()));
+
+        // 保留本次扫描的快照，用于与以往的扫描对比
+        db.save_snapshot(folder_type, Some(&root.profile), &folder_records)?;
+
+        logger::log_structured_info(&ctx, &format!(
+            "数据库更新完成，共处理 {} 个文件夹（缓存 {}，重新计算 {}）",
+            folder_records.len(),
+            stats.from_cache,
+            stats.recomputed
+        ));
     } else {
-        logger::log_info("未找到任何文件夹");
+        logger::log_structured_info(&ctx, "未找到任何文件夹");
     }
-    
+
     // 发送扫描完成标志
-    tx.send(("__SCAN_COMPLETE__".to_string(), 0))?;
-    logger::log_info(&format!("{} 类型文件夹扫描完成", folder_type));
-    
+    tx.send(ScanEvent::Completed { stats })?;
+    logger::log_structured_info(&ctx, "扫描完成");
+
     Ok(())
 }
 
-fn perform_filesystem_scan(folder_type: &str) -> Result<Vec<(String, u64)>, Box<dyn std::error::Error>> {
-    let mut results = Vec::new();
-    
-    // 根据 folder_type 确定要扫描的目录
-    let appdata_dir = match folder_type {
-        "Roaming" => dirs::data_dir(), // Roaming 目录（跨设备同步的配置）
-        "Local" => dirs::cache_dir(),  // Local 目录（本机应用数据）
-        "LocalLow" => {
-            // 通过 APPDATA 环境变量推导路径
-            env::var("APPDATA").ok().and_then(|apdata| {
-                let appdata_path = PathBuf::from(apdata);
-                // 获取上级目录（即 AppData 文件夹）
-                appdata_path
-                    .parent()
-                    .map(|appdata_dir| appdata_dir.join("LocalLow"))
-            })
+/// 重新计算指定的顶层文件夹，增量更新界面与数据库
+///
+/// 供文件监视使用：不发送开始与完成事件，已不存在的文件夹发送 `FolderRemoved`
+pub fn refresh_folders(
+    tx: Sender<ScanMessage>,
+    root: &ScanRoot,
+    names: &[String],
+    installed_apps: &InstalledApps,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tx = EventSender::new(tx, root);
+    let ctx = scan_log_context(root);
+    let db = Database::new(&get_default_db_path())?;
+    let existing_sizes: HashMap<String, u64> = db
+        .get_folders_by_type(&root.id)?
+        .into_iter()
+        .map(|r| (r.folder_name, r.folder_size))
+        .collect();
+
+    for name in names {
+        let path = root.path.join(name);
+        // 与 list_top_level_folders 一致，指向目录的符号链接也算文件夹
+        if !path.is_dir() {
+            if existing_sizes.contains_key(name) {
+                db.remove_folder(&root.id, name)?;
+                logger::log_structured_info(&ctx, &format!("文件夹已被删除: {}", name));
+                tx.send(ScanEvent::FolderRemoved { name: name.clone() })?;
+            }
+            continue;
         }
-        // 未知类型返回 None
-        _ => None,
-    };
 
-    // 如果找到有效的目录，开始扫描
-    if let Some(appdata_dir) = appdata_dir {
-        if let Ok(entries) = fs::read_dir(&appdata_dir) {
-            for entry in entries.flatten() {
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_dir() {
-                        let folder_name = entry.file_name().to_string_lossy().to_string();
-                        let size = calculate_folder_size(&entry.path());
-                        results.push((folder_name, size));
-                    }
-                }
+        let fingerprint = calculate_fingerprint(&path);
+        let scan = measure_folder(name, &path, fingerprint);
+        for (path, kind) in &scan.errors {
+            tx.send(ScanEvent::Error {
+                path: path.clone(),
+                kind: ScanErrorKind::Io(*kind),
+            })?;
+        }
+        if let Some(event) = reconcile_folder(&existing_sizes, name, scan.size.apparent) {
+            logger::log_structured_info(&ctx, &format!("文件夹 '{}' 已更新: {} 字节", name, scan.size.apparent));
+            tx.send(event)?;
+        }
+        tx.send(ScanEvent::FolderMeta {
+            name: name.clone(),
+            meta: Box::new(folder_meta(&scan, installed_apps)),
+        })?;
+        db.upsert_folder(&folder_record(root, &scan))?;
+    }
+    Ok(())
+}
+
+// 计算单个文件夹的大小
+fn measure_folder(name: &str, path: &Path, fingerprint: Option<FolderFingerprint>) -> FolderScan {
+    let mut errors = Vec::new();
+    let size = size::measure(path, &mut errors);
+    FolderScan {
+        name: name.to_string(),
+        size,
+        link_target: size::link_target(path),
+        fingerprint,
+        from_cache: false,
+        errors,
+    }
+}
+
+// 发送给界面的附加信息
+fn folder_meta(scan: &FolderScan, installed_apps: &InstalledApps) -> FolderMeta {
+    FolderMeta {
+        disk_size: scan.size.on_disk,
+        link_target: scan.link_target.clone(),
+        last_modified: scan.size.last_modified,
+        last_accessed: scan.size.last_accessed,
+        ownership: installed_apps.classify(&scan.name),
+    }
+}
+
+// 保存到数据库的记录
+fn folder_record(root: &ScanRoot, scan: &FolderScan) -> FolderRecord {
+    FolderRecord {
+        id: None,
+        folder_type: root.id.clone(),
+        folder_name: scan.name.clone(),
+        folder_size: scan.size.apparent,
+        // 无法读取目录时退回到指纹中的时间
+        last_modified: scan
+            .size
+            .last_modified
+            .or(scan.fingerprint.map(|fp| fp.newest_mtime))
+            .unwrap_or_else(Utc::now),
+        last_accessed: scan.size.last_accessed,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        fingerprint: scan.fingerprint,
+        profile: Some(root.profile.clone()),
+        disk_size: Some(scan.size.on_disk),
+        link_target: scan
+            .link_target
+            .as_ref()
+            .map(|target| target.to_string_lossy().to_string()),
+    }
+}
+
+/// 将新扫描的结果与缓存对比，得到需要发送给界面的事件
+///
+/// 大小未变化的文件夹界面上已有缓存数据，返回 `None`
+fn reconcile_folder(existing: &HashMap<String, u64>, name: &str, size: u64) -> Option<ScanEvent> {
+    match existing.get(name) {
+        Some(old_size) if *old_size == size => None,
+        Some(old_size) => Some(ScanEvent::FolderChanged {
+            name: name.to_string(),
+            old_size: *old_size,
+            new_size: size,
+        }),
+        None => Some(ScanEvent::FolderAdded {
+            name: name.to_string(),
+            size,
+        }),
+    }
+}
+
+// 列出根目录下的所有顶层文件夹，包括指向目录的符号链接（如已被移动的文件夹）
+pub fn list_top_level_folders(root: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut folders = Vec::new();
+    for entry in fs::read_dir(root)?.flatten() {
+        if let Ok(metadata) = entry.metadata() {
+            if metadata.is_dir() || (metadata.file_type().is_symlink() && entry.path().is_dir()) {
+                let folder_name = entry.file_name().to_string_lossy().to_string();
+                folders.push((folder_name, entry.path()));
             }
         }
     }
-    
-    Ok(results)
+    Ok(folders)
 }
 
-// 计算文件夹的总大小（递归）
-fn calculate_folder_size(folder: &Path) -> u64 {
-    let mut size = 0;
-
-    // 遍历文件夹中的所有条目
-    if let Ok(entries) = fs::read_dir(folder) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                // 递归计算子文件夹的大小
-                size += calculate_folder_size(&path);
-            } else if path.is_file() {
-                // 计算文件大小
-                if let Ok(metadata) = entry.metadata() {
-                    size += metadata.len();
+/// 使用工作线程池并行计算每个文件夹的大小
+///
+/// 指纹与 `cached` 中记录一致的文件夹直接复用缓存大小。
+/// 每个文件夹完成后立即调用 `on_folder(结果, 已完成数, 总数)`，返回全部结果
+fn scan_folders_parallel<F>(
+    folders: Vec<(String, PathBuf)>,
+    cached: &HashMap<String, (FolderFingerprint, FolderSize)>,
+    mut on_folder: F,
+) -> Result<Vec<FolderScan>, Box<dyn std::error::Error>>
+where
+    F: FnMut(&FolderScan, usize, usize) -> ScanCallbackResult,
+{
+    if folders.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let total = folders.len();
+    let worker_count = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(total);
+
+    let next_index = AtomicUsize::new(0);
+    let mut results = Vec::with_capacity(total);
+
+    thread::scope(|scope| -> ScanCallbackResult {
+        let (result_tx, result_rx) = mpsc::channel();
+
+        for _ in 0..worker_count {
+            let result_tx = result_tx.clone();
+            let folders = &folders;
+            let next_index = &next_index;
+            scope.spawn(move || loop {
+                // 每个工作线程依次领取下一个待计算的文件夹
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let Some((name, path)) = folders.get(index) else {
+                    break;
+                };
+                let fingerprint = calculate_fingerprint(path);
+                let scan = match (fingerprint, cached.get(name)) {
+                    // 大小取自缓存；目录的修改时间每次都重新读取，以免常用的程序被当作长期未使用
+                    (Some(fp), Some((cached_fp, cached_size))) if fp == *cached_fp => FolderScan {
+                        name: name.clone(),
+                        size: FolderSize {
+                            last_modified: cached_size.last_modified.max(Some(fp.newest_mtime)),
+                            ..*cached_size
+                        },
+                        link_target: size::link_target(path),
+                        fingerprint,
+                        from_cache: true,
+                        errors: Vec::new(),
+                    },
+                    _ => measure_folder(name, path, fingerprint),
+                };
+                if result_tx.send(scan).is_err() {
+                    break;
                 }
+            });
+        }
+        // 所有工作线程退出后接收循环自然结束
+        drop(result_tx);
+
+        for scan in result_rx {
+            if let Err(e) = on_folder(&scan, results.len() + 1, total) {
+                // 停止派发新任务，已在计算的文件夹完成后线程退出
+                next_index.store(total, Ordering::Relaxed);
+                return Err(e);
             }
+            results.push(scan);
         }
+        Ok(())
+    })?;
+
+    Ok(results)
+}
+
+/// 计算文件夹指纹：只读取目录的元数据，不逐个读取文件
+///
+/// 新增、删除或重命名条目都会更新所在目录的修改时间；
+/// 原地改写已有文件不会改变指纹，需要重新扫描时可删除数据库缓存。
+/// 任何目录无法读取或文件夹本身是符号链接时返回 `None`，该文件夹总是重新计算。
+fn calculate_fingerprint(folder: &Path) -> Option<FolderFingerprint> {
+    // 链接本身的大小计算开销很小，不必跟随到目标目录
+    if fs::symlink_metadata(folder).ok()?.file_type().is_symlink() {
+        return None;
     }
+    let mut newest_mtime = SystemTime::UNIX_EPOCH;
+    let mut file_count = 0;
+    let mut dir_count = 0;
+    let mut pending = vec![folder.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let modified = fs::metadata(&dir).and_then(|m| m.modified()).ok()?;
+        newest_mtime = newest_mtime.max(modified);
+
+        for entry in fs::read_dir(&dir).ok()? {
+            let entry = entry.ok()?;
+            // DirEntry::file_type 通常不需要额外的系统调用
+            if entry.file_type().ok()?.is_dir() {
+                dir_count += 1;
+                pending.push(entry.path());
+            } else {
+                file_count += 1;
+            }
+        }
+    }
+
+    Some(FolderFingerprint {
+        newest_mtime: DateTime::<Utc>::from(newest_mtime),
+        file_count,
+        dir_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_folders_parallel() {
+        // 创建临时目录和文件夹进行测试
+        let temp_dir = std::env::temp_dir().join("test_scan_folders_parallel");
+        let _ = fs::remove_dir_all(&temp_dir);
+        for i in 0..8 {
+            let folder = temp_dir.join(format!("app{}", i));
+            fs::create_dir_all(folder.join("sub")).unwrap();
+            fs::write(folder.join("a.bin"), vec![0u8; 100 * i]).unwrap();
+            fs::write(folder.join("sub").join("b.bin"), vec![0u8; 10]).unwrap();
+        }
+        // 根目录下的文件不计入结果
+        fs::write(temp_dir.join("root.txt"), "content").unwrap();
+
+        // 记录回调收到的进度
+        let mut progress = Vec::new();
+        let folders = list_top_level_folders(&temp_dir).unwrap();
+        let mut results = scan_folders_parallel(folders, &HashMap::new(), |_, done, total| {
+            progress.push((done, total));
+            Ok(())
+        })
+        .unwrap();
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(progress, (1..=8).map(|done| (done, 8)).collect::<Vec<_>>());
+        assert_eq!(results.len(), 8);
+        for (i, scan) in results.iter().enumerate() {
+            assert_eq!(scan.name, format!("app{}", i));
+            assert_eq!(scan.size.apparent, (100 * i + 10) as u64);
+            assert!(scan.errors.is_empty());
+            assert!(!scan.from_cache);
+        }
 
-    size
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fingerprint_skips_unchanged_folders() {
+        let temp_dir = std::env::temp_dir().join("test_fingerprint_skips_unchanged");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("Stable").join("sub")).unwrap();
+        fs::write(temp_dir.join("Stable").join("sub").join("a.bin"), vec![0u8; 64]).unwrap();
+        fs::create_dir_all(temp_dir.join("Growing")).unwrap();
+        fs::write(temp_dir.join("Growing").join("a.bin"), vec![0u8; 32]).unwrap();
+        fs::create_dir_all(temp_dir.join("Appending")).unwrap();
+        fs::write(temp_dir.join("Appending").join("app.log"), vec![0u8; 8]).unwrap();
+
+        let first = scan_folders_parallel(
+            list_top_level_folders(&temp_dir).unwrap(),
+            &HashMap::new(),
+            |_, _, _| Ok(()),
+        )
+        .unwrap();
+        let stable = first.iter().find(|scan| scan.name == "Stable").unwrap();
+        let fp = stable.fingerprint.unwrap();
+        assert_eq!((fp.file_count, fp.dir_count), (1, 1));
+
+        // 缓存大小故意与实际不同，用于确认没有重新计算
+        let cached_size = FolderSize {
+            apparent: 9999,
+            on_disk: 9999,
+            ..Default::default()
+        };
+        let cached: HashMap<String, (FolderFingerprint, FolderSize)> = first
+            .iter()
+            .map(|scan| (scan.name.clone(), (scan.fingerprint.unwrap(), cached_size)))
+            .collect();
+        fs::write(temp_dir.join("Growing").join("b.bin"), vec![0u8; 16]).unwrap();
+        // 原地追加不改变目录的修改时间与条目数，沿用缓存
+        let appending = temp_dir.join("Appending");
+        let dir_mtime = fs::metadata(&appending).unwrap().modified().unwrap();
+        let mut log = fs::OpenOptions::new().append(true).open(appending.join("app.log")).unwrap();
+        io::Write::write_all(&mut log, &[0u8; 100]).unwrap();
+        drop(log);
+        assert_eq!(fs::metadata(&appending).unwrap().modified().unwrap(), dir_mtime);
+
+        let second = scan_folders_parallel(
+            list_top_level_folders(&temp_dir).unwrap(),
+            &cached,
+            |_, _, _| Ok(()),
+        )
+        .unwrap();
+        let stable = second.iter().find(|scan| scan.name == "Stable").unwrap();
+        assert!(stable.from_cache);
+        assert_eq!(stable.size.apparent, 9999);
+        // 修改时间取自目录，访问时间沿用缓存
+        assert_eq!(stable.size.last_modified, Some(fp.newest_mtime));
+        assert_eq!(stable.size.last_accessed, None);
+        let growing = second.iter().find(|scan| scan.name == "Growing").unwrap();
+        assert!(!growing.from_cache);
+        assert_eq!(growing.size.apparent, 48);
+        let appended = second.iter().find(|scan| scan.name == "Appending").unwrap();
+        assert!(appended.from_cache);
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_folder_not_followed() {
+        let temp_dir = std::env::temp_dir().join("test_symlinked_folder_scan");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let root = temp_dir.join("root");
+        let moved = temp_dir.join("moved");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&moved).unwrap();
+        fs::write(moved.join("a.bin"), vec![0u8; 10_000]).unwrap();
+        // 模拟 move_module 移动后留下的链接
+        std::os::unix::fs::symlink(&moved, root.join("App")).unwrap();
+
+        let results = scan_folders_parallel(
+            list_top_level_folders(&root).unwrap(),
+            &HashMap::new(),
+            |_, _, _| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].link_target, Some(moved.clone()));
+        assert!(results[0].fingerprint.is_none());
+        assert!(results[0].size.apparent < 10_000);
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_reconcile_folder() {
+        let existing: HashMap<String, u64> =
+            [("App1".to_string(), 1024), ("App2".to_string(), 2048)].into_iter().collect();
+
+        // 大小未变化不产生事件
+        assert_eq!(reconcile_folder(&existing, "App1", 1024), None);
+        assert_eq!(
+            reconcile_folder(&existing, "App2", 4096),
+            Some(ScanEvent::FolderChanged {
+                name: "App2".to_string(),
+                old_size: 2048,
+                new_size: 4096,
+            })
+        );
+        assert_eq!(
+            reconcile_folder(&existing, "App3", 10),
+            Some(ScanEvent::FolderAdded {
+                name: "App3".to_string(),
+                size: 10,
+            })
+        );
+    }
 }
@@ -0,0 +1,212 @@
+use crate::logger;
+use crate::roots::ScanRoot;
+use crate::size::{self, FolderSize};
+use crate::yaml_loader::FolderDescriptions;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use walkdir::WalkDir;
+
+/// 完全相同时的得分
+const EXACT_SCORE: u32 = 1000;
+/// 描述中包含查询词时的得分
+const DESCRIPTION_SCORE: u32 = 300;
+
+/// 命中的依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSource {
+    Name,
+    Description,
+}
+
+impl MatchSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchSource::Name => "名称",
+            MatchSource::Description => "描述",
+        }
+    }
+}
+
+/// 一个匹配的文件夹
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub root: ScanRoot,
+    pub path: PathBuf,
+    /// 相对于根目录的路径，以 / 分隔
+    pub relative: String,
+    pub matched: MatchSource,
+    pub score: u32,
+    pub size: FolderSize,
+}
+
+/// 搜索过程中发送给界面的消息
+pub enum SearchMessage {
+    Progress(String),
+    Finished(Vec<SearchHit>),
+}
+
+// 只保留字母数字并转为小写，"Zoom Video" 与 "zoom_video" 相同
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 模糊匹配得分，越大越相关，不匹配时返回 `None`
+///
+/// 忽略大小写与分隔符。包含查询词时按出现位置打分，越靠前越高；
+/// 否则要求查询词的字符按顺序出现，中间跳过的字符越少得分越高。
+/// 少于 3 个字符的查询词只做包含匹配，避免大量无关结果
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let query = normalize(query);
+    let candidate = normalize(candidate);
+    if query.is_empty() {
+        return None;
+    }
+    if candidate == query {
+        return Some(EXACT_SCORE);
+    }
+    if let Some(position) = candidate.find(&query) {
+        return Some(800 - position.min(100) as u32);
+    }
+    let query_len = query.chars().count();
+    if query_len < 3 {
+        return None;
+    }
+
+    let mut gaps = 0;
+    let mut chars = candidate.chars();
+    for expected in query.chars() {
+        loop {
+            match chars.next() {
+                Some(c) if c == expected => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    (gaps <= query_len * 3).then(|| 500 - (gaps * 10).min(400) as u32)
+}
+
+/// 在多个根目录中查找名称与查询词相近的文件夹，最多深入 `max_depth` 层
+///
+/// 顶层文件夹还会匹配 folders_description.yaml 中的描述。
+/// 匹配的文件夹不再向下查找，避免结果互相包含；不跟随符号链接。
+/// 结果按得分从高到低、大小从大到小排序
+pub fn search(
+    roots: &[ScanRoot],
+    query: &str,
+    max_depth: usize,
+    descriptions: Option<&FolderDescriptions>,
+    progress: Option<&Sender<SearchMessage>>,
+) -> Vec<SearchHit> {
+    let description_query = query.trim().to_lowercase();
+    let mut hits = Vec::new();
+
+    for root in roots {
+        if let Some(tx) = progress {
+            let _ = tx.send(SearchMessage::Progress(format!("正在搜索 {}", root.label)));
+        }
+        let mut entries = WalkDir::new(&root.path).min_depth(1).max_depth(max_depth.max(1)).into_iter();
+        while let Some(entry) = entries.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy();
+            let matched = fuzzy_score(query, &name)
+                .map(|score| (MatchSource::Name, score))
+                .or_else(|| {
+                    let description = descriptions?.get_description(&name, root.kind)?;
+                    (entry.depth() == 1 && !description_query.is_empty())
+                        .then_some(description)
+                        .filter(|description| description.to_lowercase().contains(&description_query))
+                        .map(|_| (MatchSource::Description, DESCRIPTION_SCORE))
+                });
+            let Some((matched, score)) = matched else {
+                continue;
+            };
+
+            let relative = entry
+                .path()
+                .strip_prefix(&root.path)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .replace('\\', "/");
+            hits.push(SearchHit {
+                root: root.clone(),
+                size: size::measure(entry.path(), &mut Vec::new()),
+                path: entry.path().to_path_buf(),
+                relative,
+                matched,
+                score,
+            });
+            entries.skip_current_dir();
+        }
+    }
+
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.size.apparent.cmp(&a.size.apparent))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    logger::log_info(&format!("搜索 \"{}\" 找到 {} 个文件夹", query, hits.len()));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roots;
+    use std::fs;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("Zoom", "zoom"), Some(EXACT_SCORE));
+        assert!(fuzzy_score("zoom", "ZoomUs").unwrap() > fuzzy_score("zoom", "us.zoom.xos").unwrap());
+        assert!(fuzzy_score("vscode", "Visual Studio Code").is_some());
+        assert_eq!(fuzzy_score("zoom", "Microsoft"), None);
+        assert_eq!(fuzzy_score("zm", "zoom"), None);
+        assert_eq!(fuzzy_score("", "anything"), None);
+    }
+
+    #[test]
+    fn test_search_across_roots() {
+        let temp_dir = std::env::temp_dir().join("test_search_roots");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let roots = roots::appdata_roots(&temp_dir, "test", "tester", "tester");
+        fs::create_dir_all(temp_dir.join("Roaming/Zoom")).unwrap();
+        fs::write(temp_dir.join("Roaming/Zoom/data.bin"), vec![0u8; 100]).unwrap();
+        // 匹配的文件夹中不再查找
+        fs::create_dir_all(temp_dir.join("Local/zoomus/zoom")).unwrap();
+        fs::create_dir_all(temp_dir.join("Local/Vendor")).unwrap();
+        fs::create_dir_all(temp_dir.join("LocalLow/Plugins/ZoomPlugin")).unwrap();
+        fs::create_dir_all(temp_dir.join("LocalLow/a/b/c/zoom")).unwrap();
+
+        let descriptions: FolderDescriptions =
+            serde_yaml::from_str("Roaming: {}\nLocal:\n  Vendor: Zoom 会议缓存\nLocalLow: {}\n").unwrap();
+        let hits = search(&roots, "zoom", 3, Some(&descriptions), None);
+        let summary: Vec<(&str, MatchSource)> = hits
+            .iter()
+            .map(|hit| (hit.relative.as_str(), hit.matched))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Zoom", MatchSource::Name),
+                ("zoomus", MatchSource::Name),
+                ("Plugins/ZoomPlugin", MatchSource::Name),
+                ("Vendor", MatchSource::Description),
+            ]
+        );
+        assert_eq!(hits[0].size.apparent, 100);
+        assert_eq!(search(&roots, "zoom", 4, None, None).len(), 4);
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
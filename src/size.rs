@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 文件夹大小统计结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FolderSize {
+    /// 文件内容的总字节数
+    pub apparent: u64,
+    /// 实际占用的磁盘空间（已分配的块）
+    pub on_disk: u64,
+    /// 文件夹内最近的修改时间（包括目录本身）
+    pub last_modified: Option<DateTime<Utc>>,
+    /// 文件夹内文件最近的访问时间
+    pub last_accessed: Option<DateTime<Utc>>,
+}
+
+impl FolderSize {
+    fn add(&mut self, metadata: &Metadata) {
+        self.apparent += metadata.len();
+        self.on_disk += allocated_size(metadata);
+    }
+
+    // 目录的访问时间会被扫描本身刷新，只记录修改时间
+    fn touch(&mut self, metadata: &Metadata, include_access: bool) {
+        if let Ok(modified) = metadata.modified() {
+            self.last_modified = latest(self.last_modified, modified.into());
+        }
+        if include_access {
+            if let Ok(accessed) = metadata.accessed() {
+                self.last_accessed = latest(self.last_accessed, accessed.into());
+            }
+        }
+    }
+
+    /// 最近一次使用的时间：修改与访问中较晚的一个
+    pub fn last_used(&self) -> Option<DateTime<Utc>> {
+        self.last_modified.max(self.last_accessed)
+    }
+}
+
+fn latest(current: Option<DateTime<Utc>>, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    Some(current.map_or(time, |current| current.max(time)))
+}
+
+/// 统计路径的大小与最近活动时间，不跟随符号链接，硬链接的文件只计一次
+///
+/// 符号链接只计算链接本身，因此已被 `move_module` 替换为链接的文件夹
+/// 不会重复计算目标，也不会陷入链接循环。无法访问的路径记录到 `errors`
+pub fn measure(path: &Path, errors: &mut Vec<(PathBuf, io::ErrorKind)>) -> FolderSize {
+    let mut size = FolderSize::default();
+    let mut seen_inodes = HashSet::new();
+
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => size.touch(&metadata, false),
+        Ok(metadata) => {
+            // 文件或符号链接本身
+            size.add(&metadata);
+            size.touch(&metadata, true);
+            return size;
+        }
+        Err(e) => {
+            errors.push((path.to_path_buf(), e.kind()));
+            return size;
+        }
+    }
+
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                errors.push((dir, e.kind()));
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    errors.push((dir.clone(), e.kind()));
+                    continue;
+                }
+            };
+            // DirEntry::metadata 不跟随符号链接
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => {
+                    size.touch(&metadata, false);
+                    pending.push(entry.path());
+                }
+                Ok(metadata) => {
+                    size.touch(&metadata, true);
+                    if first_link(&metadata, &mut seen_inodes) {
+                        size.add(&metadata);
+                    }
+                }
+                Err(e) => errors.push((entry.path(), e.kind())),
+            }
+        }
+    }
+
+    size
+}
+
+/// 文件数与文件内容的总字节数，不跟随符号链接，链接本身计为一个文件
+pub fn count_files(path: &Path) -> (u64, u64) {
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in WalkDir::new(path).follow_root_links(false).into_iter().flatten() {
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        files += 1;
+        bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    }
+    (files, bytes)
+}
+
+/// 路径本身是符号链接时返回其目标
+pub fn link_target(path: &Path) -> Option<PathBuf> {
+    fs::read_link(path).ok()
+}
+
+// 已分配的磁盘空间，st_blocks 固定以 512 字节为单位
+#[cfg(unix)]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+// 标准库在其他平台上无法获取已分配大小，按内容大小计算
+#[cfg(not(unix))]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+// 同一 inode 第一次出现时返回 true
+#[cfg(unix)]
+fn first_link(metadata: &Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() <= 1 || seen.insert((metadata.dev(), metadata.ino()))
+}
+
+// 标准库在其他平台上无法稳定获取文件标识，每个链接都计入
+#[cfg(not(unix))]
+fn first_link(_metadata: &Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_plain_folder() {
+        let temp_dir = std::env::temp_dir().join("test_measure_plain_folder");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        fs::write(temp_dir.join("a.bin"), vec![0u8; 100]).unwrap();
+        fs::write(temp_dir.join("sub").join("b.bin"), vec![1u8; 5000]).unwrap();
+
+        let mut errors = Vec::new();
+        let size = measure(&temp_dir, &mut errors);
+        assert_eq!(size.apparent, 5100);
+        assert!(errors.is_empty());
+        // 刚写入的文件，最近活动时间就在眼前
+        let age = Utc::now() - size.last_used().unwrap();
+        assert!(age.num_minutes() < 5);
+        assert!(size.last_accessed.is_some());
+        assert_eq!(link_target(&temp_dir), None);
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_measure_links_counted_once() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = std::env::temp_dir().join("test_measure_links");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let folder = temp_dir.join("App");
+        let outside = temp_dir.join("Moved");
+        fs::create_dir_all(&folder).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(folder.join("data.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(outside.join("big.bin"), vec![0u8; 100_000]).unwrap();
+
+        // 同一文件的第二个硬链接不重复计算
+        fs::hard_link(folder.join("data.bin"), folder.join("data-link.bin")).unwrap();
+        // 指向外部目录的链接与指向自身的循环链接都不跟随
+        symlink(&outside, folder.join("moved")).unwrap();
+        symlink(&folder, folder.join("loop")).unwrap();
+
+        let mut errors = Vec::new();
+        let size = measure(&folder, &mut errors);
+        assert!(errors.is_empty());
+        let link_bytes = outside.as_os_str().len() + folder.as_os_str().len();
+        assert_eq!(size.apparent, (4096 + link_bytes) as u64);
+        assert!(size.on_disk >= 4096);
+
+        // 整个文件夹被替换为链接时只计算链接本身
+        let replaced = temp_dir.join("Replaced");
+        symlink(&outside, &replaced).unwrap();
+        assert_eq!(measure(&replaced, &mut errors).apparent, outside.as_os_str().len() as u64);
+        assert_eq!(link_target(&replaced), Some(outside.clone()));
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use crate::utils;
+use std::collections::HashMap;
+
+/// 文件夹在两次快照之间的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+}
+
+impl DiffKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffKind::Added => "新增",
+            DiffKind::Removed => "移除",
+            DiffKind::Grown => "增长",
+            DiffKind::Shrunk => "缩小",
+        }
+    }
+}
+
+/// 一个文件夹的变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderDiff {
+    pub name: String,
+    pub kind: DiffKind,
+    pub old_size: u64,
+    pub new_size: u64,
+}
+
+impl FolderDiff {
+    /// 大小变化，增长为正
+    pub fn delta(&self) -> i64 {
+        self.new_size as i64 - self.old_size as i64
+    }
+}
+
+/// 对比两次快照，大小未变的文件夹不列出，结果按变化量从大到小排序
+pub fn diff_folders(old: &HashMap<String, u64>, new: &HashMap<String, u64>) -> Vec<FolderDiff> {
+    let mut diffs: Vec<FolderDiff> = new
+        .iter()
+        .filter_map(|(name, new_size)| {
+            let (kind, old_size) = match old.get(name) {
+                None => (DiffKind::Added, 0),
+                Some(old_size) if new_size > old_size => (DiffKind::Grown, *old_size),
+                Some(old_size) if new_size < old_size => (DiffKind::Shrunk, *old_size),
+                Some(_) => return None,
+            };
+            Some(FolderDiff {
+                name: name.clone(),
+                kind,
+                old_size,
+                new_size: *new_size,
+            })
+        })
+        .chain(
+            old.iter()
+                .filter(|(name, _)| !new.contains_key(*name))
+                .map(|(name, old_size)| FolderDiff {
+                    name: name.clone(),
+                    kind: DiffKind::Removed,
+                    old_size: *old_size,
+                    new_size: 0,
+                }),
+        )
+        .collect();
+    diffs.sort_by(|a, b| {
+        b.delta()
+            .unsigned_abs()
+            .cmp(&a.delta().unsigned_abs())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    diffs
+}
+
+/// 带符号的大小，如 "+1.00 GB"
+pub fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, utils::format_size(delta.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_folders() {
+        let old: HashMap<String, u64> = [("Chrome", 1000), ("Slack", 500), ("Gone", 300), ("Same", 42)]
+            .into_iter()
+            .map(|(name, size)| (name.to_string(), size))
+            .collect();
+        let new: HashMap<String, u64> = [("Chrome", 6000), ("Slack", 100), ("Discord", 2000), ("Same", 42)]
+            .into_iter()
+            .map(|(name, size)| (name.to_string(), size))
+            .collect();
+
+        let diffs = diff_folders(&old, &new);
+        let summary: Vec<(&str, DiffKind, i64)> = diffs
+            .iter()
+            .map(|diff| (diff.name.as_str(), diff.kind, diff.delta()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Chrome", DiffKind::Grown, 5000),
+                ("Discord", DiffKind::Added, 2000),
+                ("Slack", DiffKind::Shrunk, -400),
+                ("Gone", DiffKind::Removed, -300),
+            ]
+        );
+        assert_eq!(format_delta(-2048), "-2.00 KB");
+    }
+}
@@ -14,7 +14,6 @@ impl StatsLogger {
     pub fn log_stats(&self, cleaned_folders_count: u64, total_cleaned_size: u64) -> Option<()> {
         let mut file = OpenOptions::new()
             .create(true)
-            .write(true)
             .append(true)
             .open(&self.file_path)
             .map_err(|err| {
@@ -5,9 +5,9 @@ use std::sync::{Arc, Mutex};
 // 定义标签类型枚举
 #[derive(PartialEq)]
 enum ConfigTab {
-    ApiSettings,
-    RetrySettings,
-    PromptSettings,
+    Api,
+    Retry,
+    Prompt,
 }
 
 pub struct AIConfigurationUI {
@@ -27,7 +27,7 @@ impl AIConfigurationUI {
             ai_config: ai_config.clone(),
             ai_handler,
             status: None,
-            current_tab: ConfigTab::ApiSettings,
+            current_tab: ConfigTab::Api,
             last_config: Some(ai_config),
             is_password_visible: false,
         }
@@ -93,22 +93,22 @@ impl AIConfigurationUI {
             // 顶部标签栏
             ui.horizontal(|ui| {
                 if ui
-                    .selectable_label(self.current_tab == ConfigTab::ApiSettings, "API设置")
+                    .selectable_label(self.current_tab == ConfigTab::Api, "API设置")
                     .clicked()
                 {
-                    self.current_tab = ConfigTab::ApiSettings;
+                    self.current_tab = ConfigTab::Api;
                 }
                 if ui
-                    .selectable_label(self.current_tab == ConfigTab::RetrySettings, "重试设置")
+                    .selectable_label(self.current_tab == ConfigTab::Retry, "重试设置")
                     .clicked()
                 {
-                    self.current_tab = ConfigTab::RetrySettings;
+                    self.current_tab = ConfigTab::Retry;
                 }
                 if ui
-                    .selectable_label(self.current_tab == ConfigTab::PromptSettings, "Prompt设置")
+                    .selectable_label(self.current_tab == ConfigTab::Prompt, "Prompt设置")
                     .clicked()
                 {
-                    self.current_tab = ConfigTab::PromptSettings;
+                    self.current_tab = ConfigTab::Prompt;
                 }
             });
 
@@ -116,9 +116,9 @@ impl AIConfigurationUI {
 
             // 中间部分：根据选中的标签显示对应的内容区域
             ui.group(|ui| match self.current_tab {
-                ConfigTab::ApiSettings => self.draw_basic_settings(ui),
-                ConfigTab::RetrySettings => self.draw_retry_settings(ui),
-                ConfigTab::PromptSettings => self.draw_prompt_settings(ui),
+                ConfigTab::Api => self.draw_basic_settings(ui),
+                ConfigTab::Retry => self.draw_retry_settings(ui),
+                ConfigTab::Prompt => self.draw_prompt_settings(ui),
             });
 
             ui.separator();
@@ -198,13 +198,9 @@ impl AIConfigurationUI {
                 std::thread::spawn(move || {
                     let rt = tokio::runtime::Runtime::new().unwrap();
                     let result = rt.block_on(async {
-                        if let Ok(handler) = handler.lock() {
-                            match handler.test_connection().await {
-                                Ok(message) => Ok(message),
-                                Err(e) => Err(format!("连接失败: {}", e)),
-                            }
-                        } else {
-                            Err("无法获取 AI 处理器锁".to_string())
+                        match AIHandler::test_connection(&handler).await {
+                            Ok(message) => Ok(message),
+                            Err(e) => Err(format!("连接失败: {}", e)),
                         }
                     });
 
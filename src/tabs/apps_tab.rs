@@ -0,0 +1,481 @@
+use crate::apps::{self, AppGroup, AppPart};
+use crate::database::{get_default_db_path, Database};
+use crate::delete::{DeleteJob, DeleteMode, DeleteReport, DeleteWorker};
+use crate::dry_run;
+use crate::logger::{self, LogContext};
+use crate::roots::{self, ScanRoot};
+use crate::scanner::{self, FolderMeta, ScanEvent, ScanMessage};
+use crate::tabs::clear_tab::{self, ClearTabState};
+use crate::{confirmation, ignore, open, utils};
+use eframe::egui::{self, Grid, ScrollArea};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+// 等待确认的整组删除，`checked` 为仍勾选的文件夹路径
+struct PendingDelete {
+    key: String,
+    mode: DeleteMode,
+    checked: HashSet<PathBuf>,
+    // 彻底删除已通过第一次确认，等待再次确认
+    confirmed_once: bool,
+}
+
+// 应用一行上点击的操作
+enum GroupAction {
+    Toggle,
+    Delete(DeleteMode),
+    Ignore,
+}
+
+/// 全部根目录视图：同一应用在 Roaming、Local、LocalLow 中的文件夹合并为一项
+#[derive(Default)]
+pub struct AppsTabState {
+    // 汇总的根目录，跟随主页选中的根目录所属的用户
+    roots: Vec<ScanRoot>,
+    loaded_for: Option<String>,
+    groups: Vec<AppGroup>,
+    // 数据库中还没有扫描结果的根目录
+    unscanned: Vec<ScanRoot>,
+
+    expanded: HashSet<String>,
+    multi_root_only: bool,
+    confirm_delete: Option<PendingDelete>,
+    status: Option<String>,
+
+    // 后台删除任务，以及正在删除的应用名与文件夹
+    delete_worker: Option<DeleteWorker>,
+    deleting: (String, Vec<AppPart>),
+    delete_results: Vec<(AppPart, Result<DeleteReport, String>)>,
+
+    // 扫描未扫描的根目录
+    scan_rx: Option<Receiver<ScanMessage>>,
+    pending_scans: HashSet<String>,
+}
+
+impl AppsTabState {
+    pub fn show(&mut self, ui: &mut egui::Ui, clear_tab: &mut ClearTabState) {
+        if self.loaded_for.as_deref() != Some(clear_tab.selected_root.id.as_str()) {
+            self.loaded_for = Some(clear_tab.selected_root.id.clone());
+            self.roots = roots::sibling_roots(&clear_tab.selected_root);
+            self.expanded.clear();
+            self.reload();
+        }
+        self.poll_scans(ui.ctx());
+        self.poll_delete_worker(ui.ctx(), clear_tab);
+        self.handle_delete_confirmation(ui.ctx());
+
+        ui.horizontal(|ui| {
+            let labels: Vec<&str> = self.roots.iter().map(|root| root.label.as_str()).collect();
+            ui.label(format!("汇总: {}", labels.join("、")));
+            if ui.button("重新加载").clicked() {
+                self.reload();
+            }
+            ui.checkbox(&mut self.multi_root_only, "只显示跨多个根目录的应用");
+        });
+
+        if !self.unscanned.is_empty() {
+            ui.horizontal(|ui| {
+                let labels: Vec<&str> = self.unscanned.iter().map(|root| root.label.as_str()).collect();
+                ui.label(format!("尚未扫描: {}", labels.join("、")));
+                if self.scan_rx.is_some() {
+                    ui.spinner();
+                } else if ui.button("扫描这些根目录").clicked() {
+                    self.scan_unscanned();
+                }
+            });
+        }
+
+        let total: u64 = self.groups.iter().map(AppGroup::total).sum();
+        ui.label(format!(
+            "{} 个应用，合计 {}",
+            self.groups.len(),
+            utils::format_size(total)
+        ));
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        if let Some(worker) = &self.delete_worker {
+            clear_tab::show_delete_progress(ui, worker);
+        }
+
+        let mut clicked = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("apps_table").striped(true).show(ui, |ui| {
+                ui.label("应用");
+                ui.label("大小");
+                ui.label("根目录");
+                ui.label("最近使用");
+                ui.label("描述");
+                ui.label("操作");
+                ui.end_row();
+
+                for group in &self.groups {
+                    if self.multi_root_only && group.root_count() < 2 {
+                        continue;
+                    }
+                    if let Some(action) = self.show_group_row(ui, group, clear_tab) {
+                        clicked = Some((action, group.key.clone()));
+                    }
+                    ui.end_row();
+                    if self.expanded.contains(&group.key) {
+                        for part in &group.parts {
+                            show_part_row(ui, part, clear_tab);
+                            ui.end_row();
+                        }
+                    }
+                }
+            });
+        });
+        if let Some((action, key)) = clicked {
+            self.apply_action(action, &key, clear_tab);
+        }
+    }
+
+    fn apply_action(&mut self, action: GroupAction, key: &str, clear_tab: &mut ClearTabState) {
+        match action {
+            GroupAction::Toggle => {
+                if !self.expanded.remove(key) {
+                    self.expanded.insert(key.to_string());
+                }
+            }
+            GroupAction::Delete(mode) => {
+                let Some(group) = self.find_group(key) else {
+                    return;
+                };
+                self.confirm_delete = Some(PendingDelete {
+                    key: key.to_string(),
+                    mode,
+                    checked: group.parts.iter().map(part_path).collect(),
+                    confirmed_once: false,
+                });
+                self.status = None;
+            }
+            GroupAction::Ignore => {
+                let Some(group) = self.find_group(key) else {
+                    return;
+                };
+                for part in &group.parts {
+                    clear_tab.ignored_folders.insert(part.folder.clone());
+                    logger::log_info(&format!("文件夹 '{}' 已被忽略", part.folder));
+                }
+                ignore::save_ignored_folders(&clear_tab.ignored_folders);
+            }
+        }
+    }
+
+    // 应用一行，操作作用于该应用的所有文件夹
+    fn show_group_row(
+        &self,
+        ui: &mut egui::Ui,
+        group: &AppGroup,
+        clear_tab: &ClearTabState,
+    ) -> Option<GroupAction> {
+        let mut action = None;
+        let is_expanded = self.expanded.contains(&group.key);
+        let is_ignored = group
+            .parts
+            .iter()
+            .any(|part| clear_tab.ignored_folders.contains(&part.folder));
+
+        ui.horizontal(|ui| {
+            if ui.small_button(if is_expanded { "▼" } else { "▶" }).clicked() {
+                action = Some(GroupAction::Toggle);
+            }
+            let name = egui::RichText::new(&group.name);
+            ui.label(if is_ignored { name.color(egui::Color32::GRAY) } else { name });
+        });
+        ui.label(utils::format_size(group.total()));
+        let mut kinds: Vec<&str> = Vec::new();
+        for part in &group.parts {
+            if !kinds.contains(&part.root.kind.label()) {
+                kinds.push(part.root.kind.label());
+            }
+        }
+        ui.label(kinds.join("、"));
+        ui.label(utils::format_age(group.last_used()));
+        let description = group
+            .parts
+            .iter()
+            .find_map(|part| describe(part, clear_tab))
+            .unwrap_or_else(|| "无描述".to_string());
+        ui.label(description);
+
+        // 同一时间只运行一个删除任务
+        let can_delete = !is_ignored && self.delete_worker.is_none();
+        ui.horizontal(|ui| {
+            for mode in DeleteMode::ALL {
+                let button = egui::Button::new(format!("全部{}", mode.action_label()));
+                if ui.add_enabled(can_delete, button).on_hover_text(mode.label()).clicked() {
+                    action = Some(GroupAction::Delete(mode));
+                }
+            }
+            if ui.add_enabled(!is_ignored, egui::Button::new("全部忽略")).clicked() {
+                action = Some(GroupAction::Ignore);
+            }
+        });
+        action
+    }
+
+    fn reload(&mut self) {
+        let db = match Database::new(&get_default_db_path()) {
+            Ok(db) => db,
+            Err(e) => {
+                self.status = Some(format!("无法打开数据库: {}", e));
+                return;
+            }
+        };
+        let mut parts = Vec::new();
+        self.unscanned.clear();
+        for root in &self.roots {
+            match db.get_folders_by_type(&root.id) {
+                Ok(records) if !records.is_empty() => {
+                    parts.extend(records.iter().map(|record| AppPart {
+                        root: root.clone(),
+                        folder: record.folder_name.clone(),
+                        size: record.folder_size,
+                        last_used: FolderMeta::from(record).last_used(),
+                    }));
+                }
+                Ok(_) => self.unscanned.push(root.clone()),
+                Err(e) => logger::log_error(&format!("读取 {} 的扫描结果失败: {}", root.label, e)),
+            }
+        }
+        self.groups = apps::group_parts(parts);
+    }
+
+    fn scan_unscanned(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        self.pending_scans = self.unscanned.iter().map(|root| root.id.clone()).collect();
+        for root in &self.unscanned {
+            scanner::scan_appdata(tx.clone(), root);
+        }
+        self.scan_rx = Some(rx);
+        self.status = Some("扫描中...".to_string());
+    }
+
+    // 所有扫描完成后重新汇总
+    fn poll_scans(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.scan_rx else {
+            return;
+        };
+        for message in rx.try_iter() {
+            if matches!(message.event, ScanEvent::Completed { .. }) {
+                self.pending_scans.remove(&message.root_id);
+            }
+        }
+        if self.pending_scans.is_empty() {
+            self.scan_rx = None;
+            self.status = Some("扫描完成".to_string());
+            self.reload();
+        } else {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+
+    // 列出组内每个文件夹，取消勾选的不删除
+    fn handle_delete_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &mut self.confirm_delete else {
+            return;
+        };
+        let Some(group) = self.groups.iter().find(|group| group.key == pending.key).cloned() else {
+            self.confirm_delete = None;
+            return;
+        };
+        let mut result = None;
+        if pending.confirmed_once {
+            let message = pending.mode.confirm_message(&format!(
+                "{} 的 {} 个文件夹",
+                group.name,
+                pending.checked.len()
+            ));
+            result = confirmation::confirm_permanent(ctx, &message, &self.status);
+        } else {
+            egui::Window::new("确认操作")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(pending.mode.confirm_message(&format!("{} 的以下文件夹", group.name)));
+                    for part in &group.parts {
+                        let path = part_path(part);
+                        let mut checked = pending.checked.contains(&path);
+                        let label = format!(
+                            "{}（{}，{}）",
+                            path.display(),
+                            part.root.label,
+                            utils::format_size(part.size)
+                        );
+                        if ui.checkbox(&mut checked, label).changed() {
+                            if checked {
+                                pending.checked.insert(path);
+                            } else {
+                                pending.checked.remove(&path);
+                            }
+                        }
+                    }
+                    if let Some(status) = &self.status {
+                        ui.label(status);
+                    }
+                    ui.horizontal(|ui| {
+                        let button = egui::Button::new("确认");
+                        if ui.add_enabled(!pending.checked.is_empty(), button).clicked() {
+                            result = Some(true);
+                        }
+                        if ui.button("取消").clicked() {
+                            result = Some(false);
+                        }
+                    });
+                });
+            // 彻底删除先进入第二次确认
+            if result == Some(true) && pending.mode == DeleteMode::Permanent {
+                pending.confirmed_once = true;
+                result = None;
+            }
+        }
+        match result {
+            Some(true) => {
+                let parts: Vec<AppPart> = group
+                    .parts
+                    .iter()
+                    .filter(|part| pending.checked.contains(&part_path(part)))
+                    .cloned()
+                    .collect();
+                let mode = pending.mode;
+                self.confirm_delete = None;
+                self.start_delete(group.name, parts, mode);
+            }
+            Some(false) => self.confirm_delete = None,
+            None => {}
+        }
+    }
+
+    // 在后台删除勾选的文件夹；演练开关在确认时读取，删除过程中切换不影响本次任务
+    fn start_delete(&mut self, name: String, parts: Vec<AppPart>, mode: DeleteMode) {
+        let jobs = parts
+            .iter()
+            .map(|part| DeleteJob {
+                folder: part_path(part).to_string_lossy().to_string(),
+                path: part_path(part),
+                profile: part.root.profile.clone(),
+                size: part.size,
+            })
+            .collect();
+        self.delete_worker = Some(DeleteWorker::start(jobs, mode, dry_run::is_enabled()));
+        self.status = Some(format!("正在{} {} 的 {} 个文件夹...", mode.label(), name, parts.len()));
+        self.deleting = (name, parts);
+    }
+
+    // 每个文件夹删除完毕后更新主页列表与统计，全部完成后同步数据库缓存并汇总
+    fn poll_delete_worker(&mut self, ctx: &egui::Context, clear_tab: &mut ClearTabState) {
+        let Some(worker) = &mut self.delete_worker else {
+            return;
+        };
+        let (finished, done) = worker.poll();
+        let mode = worker.mode;
+        let log_ctx = LogContext::new("删除").with_target_name(self.deleting.0.clone());
+        for (folder, result) in finished {
+            let Some(part) = self.deleting.1.iter().find(|part| part_path(part).to_string_lossy() == folder) else {
+                continue;
+            };
+            match &result {
+                Ok(report) => {
+                    clear_tab.apply_folder_report(&part.root.id, &part.folder, report);
+                    if report.is_complete() && !report.planned {
+                        logger::log_structured_info(&log_ctx, &format!("已删除文件夹: {}", folder));
+                    }
+                }
+                Err(err) => logger::log_structured_error(&log_ctx, &format!("删除失败: {}", err)),
+            }
+            self.delete_results.push((part.clone(), result));
+        }
+        if !done {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+
+        self.delete_worker = None;
+        let (name, parts) = std::mem::take(&mut self.deleting);
+        let results = std::mem::take(&mut self.delete_results);
+        let reports: Vec<&DeleteReport> = results.iter().filter_map(|(_, result)| result.as_ref().ok()).collect();
+        if reports.iter().any(|report| report.planned) {
+            // 只记录到演练计划，不更新数据库与主页列表
+            self.status = Some(format!("演练：{} 的 {} 个文件夹已记录到演练计划", name, reports.len()));
+            return;
+        }
+
+        // 完全删除的文件夹从数据库缓存中移除
+        match Database::new(&get_default_db_path()) {
+            Ok(db) => {
+                for (part, result) in &results {
+                    if !matches!(result, Ok(report) if report.is_complete()) {
+                        continue;
+                    }
+                    if let Err(e) = db.remove_folder(&part.root.id, &part.folder) {
+                        logger::log_structured_error(&log_ctx, &format!("更新数据库失败: {}", e));
+                    }
+                }
+            }
+            Err(e) => logger::log_structured_error(&log_ctx, &format!("无法打开数据库: {}", e)),
+        }
+
+        let freed: u64 = reports.iter().map(|report| report.bytes_freed).sum();
+        // 取消不算失败：被取消的文件夹既不计入已删除也不计入失败
+        let deleted = reports.iter().filter(|report| report.is_complete()).count();
+        let failed = results.len() - reports.len() + reports.iter().filter(|report| !report.failures.is_empty()).count();
+        let mut status = if failed == 0 {
+            format!(
+                "已将 {} 的 {} 个文件夹{}，释放 {}",
+                name,
+                deleted,
+                mode.label(),
+                utils::format_size(freed)
+            )
+        } else {
+            clear_tab.report_window.open();
+            format!(
+                "删除 {} 时有 {} 个文件夹失败，释放 {}，详见删除报告",
+                name,
+                failed,
+                utils::format_size(freed)
+            )
+        };
+        if reports.iter().any(|report| report.cancelled) || results.len() < parts.len() {
+            status.push_str(&format!("，已取消，{} 个未处理", parts.len() - results.len()));
+        }
+        self.status = Some(status);
+        self.reload();
+    }
+
+    fn find_group(&self, key: &str) -> Option<AppGroup> {
+        self.groups.iter().find(|group| group.key == key).cloned()
+    }
+}
+
+// 展开后的一行：应用在某个根目录中的文件夹
+fn show_part_row(ui: &mut egui::Ui, part: &AppPart, clear_tab: &ClearTabState) {
+    ui.horizontal(|ui| {
+        ui.add_space(24.0);
+        ui.label(&part.folder);
+    });
+    ui.label(utils::format_size(part.size));
+    ui.label(&part.root.label);
+    ui.label(utils::format_age(part.last_used));
+    ui.label(describe(part, clear_tab).unwrap_or_else(|| "无描述".to_string()));
+    if ui.button("打开").clicked() {
+        if let Err(err) = open::open_folder(&part_path(part)) {
+            logger::log_error(&format!("无法打开文件夹: {}", err));
+        }
+    }
+}
+
+fn part_path(part: &AppPart) -> PathBuf {
+    part.root.path.join(&part.folder)
+}
+
+fn describe(part: &AppPart, clear_tab: &ClearTabState) -> Option<String> {
+    clear_tab
+        .folder_descriptions
+        .as_ref()
+        .and_then(|descriptions| descriptions.get_description(&part.folder, part.root.kind))
+}
@@ -1,42 +1,92 @@
-use crate::confirmation::show_confirmation;
 use crate::database::{Database, get_default_db_path, database_exists};
+use crate::delete::{DeleteMode, DeleteReport};
+use crate::logger::LogContext;
 use crate::stats::Stats;
 use crate::stats_logger::StatsLogger;
 use crate::yaml_loader::{load_folder_descriptions, FolderDescriptions};
+use crate::orphan::Ownership;
+use crate::roots::{self, ScanRoot};
+use crate::scanner::{FolderMeta, ScanErrorKind, ScanEvent, ScanMessage};
+use crate::size::FolderSize;
+use crate::tabs::{details_window, report_window, snapshot_window};
+use crate::tree::{self, FolderTree};
+use crate::treemap::{self, ColorMode, TreemapItem};
+use crate::watcher::FolderWatcher;
 use crate::{confirmation, delete, ignore, logger, move_module, open, scanner, utils};
 use eframe::egui::{self, Grid, ScrollArea};
-use std::collections::HashSet;
+use chrono::Utc;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::mpsc::{Receiver, Sender}; // 引入 StatsLogger 模块
+use std::time::Duration;
+
+// 生成描述的回调类型
+type DescriptionCallback = Box<dyn Fn(&str, &str) + Send>;
+type AllDescriptionsCallback = Box<dyn Fn(&Vec<(String, u64)>, &str) + Send>;
+
+// 表格中的一行：顶层文件夹或展开后的子文件夹
+struct FolderRow {
+    path: String, // 相对于根目录的路径，子文件夹以 / 分隔
+    depth: usize,
+    size: Option<FolderSize>, // None 表示仍在计算
+    link_target: Option<PathBuf>,
+}
 
 pub struct ClearTabState {
     // 基础字段
     pub is_scanning: bool,
     pub folder_data: Vec<(String, u64)>,
-    pub selected_appdata_folder: String,
-    pub tx: Option<Sender<(String, u64)>>,
-    pub rx: Option<Receiver<(String, u64)>>,
+    pub folder_meta: HashMap<String, FolderMeta>, // 磁盘占用与链接信息
+    pub tree: FolderTree,                         // 展开的子文件夹
+    pub selected_root: ScanRoot,
+    pub tx: Option<Sender<ScanMessage>>,
+    pub rx: Option<Receiver<ScanMessage>>,
     pub total_size: u64,
+    pub scan_errors: Vec<(PathBuf, ScanErrorKind)>, // 最近一次扫描中无法访问的路径
+
+    // 实时监视当前根目录的文件变化
+    pub watch_enabled: bool,
+    pub watcher: Option<FolderWatcher>,
 
     // 界面状态字段
     pub confirm_delete: Option<(String, bool)>,
+    pub delete_mode: DeleteMode, // 等待确认的删除方式
     pub status: Option<String>,
 
     // 排序相关字段
-    pub sort_criterion: Option<String>, // 排序标准:"name"或"size"
+    pub sort_criterion: Option<String>, // 排序标准:"name"、"size"或"last_used"
     pub sort_order: Option<String>,     // 排序顺序:"asc"或"desc"
 
+    // 过滤长期未使用的文件夹
+    pub stale_filter: bool,
+    pub stale_days: u32,
+
     // 文件夹描述相关
     pub folder_descriptions: Option<FolderDescriptions>,
     pub yaml_error_logged: bool,
     pub ignored_folders: HashSet<String>,
 
+    // 矩形树图
+    pub show_treemap: bool,
+    pub treemap_focus: Option<String>, // 当前进入的文件夹，None 表示根目录
+    pub treemap_color: ColorMode,
+
     // 移动模块
     pub move_module: move_module::MoveModule,
 
+    // 文件夹详情窗口
+    pub details_window: details_window::DetailsWindow,
+
+    // 扫描快照对比窗口
+    pub snapshot_window: snapshot_window::SnapshotWindow,
+    pub report_window: report_window::ReportWindow, // 删除报告
+    pub delete_worker: Option<delete::DeleteWorker>, // 后台删除任务
+    delete_results: Vec<delete::FolderResult>, // 本次任务中已完成的文件夹
+
     // 生成描述的回调函数
-    generate_description_callback: Option<Box<dyn Fn(&str) + Send>>,
-    generate_all_descriptions_callback: Option<Box<dyn Fn(&Vec<(String, u64)>, &str) + Send>>,
+    generate_description_callback: Option<DescriptionCallback>,
+    generate_all_descriptions_callback: Option<AllDescriptionsCallback>,
 
     // 多选操作
     pub selected_folders: HashSet<String>, // 新增字段，存储选中的文件夹
@@ -54,27 +104,47 @@ impl Default for ClearTabState {
             // 基础字段初始化
             is_scanning: false,
             folder_data: vec![],
-            selected_appdata_folder: "Roaming".to_string(),
+            folder_meta: HashMap::new(),
+            tree: FolderTree::default(),
+            selected_root: roots::default_root(),
             tx: Some(tx),
             rx: Some(rx),
             total_size: 0,
+            scan_errors: Vec::new(),
+            watch_enabled: false,
+            watcher: None,
 
             // 界面状态初始化
             confirm_delete: None,
+            delete_mode: DeleteMode::Trash,
             status: Some("未扫描".to_string()),
 
             // 排序相关初始化
             sort_criterion: None,
             sort_order: None,
+            stale_filter: false,
+            stale_days: 180,
 
             // 文件夹描述相关初始化
             folder_descriptions: None,
             yaml_error_logged: false,
             ignored_folders: ignore::load_ignored_folders(),
 
+            // 矩形树图初始化
+            show_treemap: false,
+            treemap_focus: None,
+            treemap_color: ColorMode::Category,
+
             // 移动模块初始化
             move_module: Default::default(),
 
+            // 详情窗口初始化
+            details_window: Default::default(),
+            snapshot_window: Default::default(),
+            report_window: Default::default(),
+            delete_worker: None,
+            delete_results: Vec::new(),
+
             // 回调函数初始化为 None
             generate_description_callback: None,
             generate_all_descriptions_callback: None,
@@ -92,8 +162,10 @@ impl Default for ClearTabState {
 // 其他代码保持不变
 
 impl ClearTabState {
-    // 新增：实现 handle_folder_operations 方法
-    fn handle_folder_operations(&mut self, ui: &mut egui::Ui, folder: &str, size: u64) {
+    // 显示一行文件夹（顶层或子文件夹）及其操作
+    fn handle_folder_operations(&mut self, ui: &mut egui::Ui, row: &FolderRow) {
+        let folder = row.path.as_str();
+
         // 显示复选框，用于多选操作
         let mut is_selected = self.selected_folders.contains(folder);
         if ui.checkbox(&mut is_selected, "").clicked() {
@@ -104,16 +176,57 @@ impl ClearTabState {
             }
         }
 
-        // 显示文件夹名称和大小
-        if self.ignored_folders.contains(folder) {
-            ui.add_enabled(
-                false,
-                egui::Label::new(egui::RichText::new(folder).color(egui::Color32::GRAY)),
-            );
-        } else {
-            ui.label(folder);
+        // 显示文件夹名称，按层级缩进；符号链接显示其目标且不可展开
+        let name = match &row.link_target {
+            Some(target) => format!("🔗 {} → {}", tree::node_name(folder), target.display()),
+            None => tree::node_name(folder).to_string(),
+        };
+        ui.horizontal(|ui| {
+            ui.add_space(row.depth as f32 * 16.0);
+            if row.link_target.is_none() {
+                let arrow = if self.tree.is_expanded(folder) { "▼" } else { "▶" };
+                if ui.small_button(arrow).clicked() {
+                    self.tree.toggle(&self.selected_root, folder);
+                }
+            }
+            if self.is_ignored(folder) {
+                ui.add_enabled(
+                    false,
+                    egui::Label::new(egui::RichText::new(name).color(egui::Color32::GRAY)),
+                );
+            } else {
+                ui.label(name);
+            }
+        });
+
+        // 显示大小与最近使用时间
+        match row.size {
+            Some(size) => {
+                ui.label(utils::format_size(size.apparent));
+                ui.label(utils::format_size(size.on_disk));
+                ui.label(utils::format_age(size.last_used()));
+            }
+            None => {
+                ui.label("计算中...");
+                ui.label("计算中...");
+                ui.label("计算中...");
+            }
+        }
+
+        // 只判断顶层文件夹的所属程序
+        match self.folder_meta.get(folder).map(|meta| meta.ownership) {
+            Some(ownership) if row.depth == 0 => {
+                let text = egui::RichText::new(ownership.label());
+                ui.label(match ownership {
+                    Ownership::NotFound => text.color(egui::Color32::from_rgb(214, 140, 52)),
+                    Ownership::Installed => text,
+                    Ownership::Unknown => text.color(egui::Color32::GRAY),
+                });
+            }
+            _ => {
+                ui.label("");
+            }
         }
-        ui.label(utils::format_size(size));
 
         // 显示描述
         self.show_folder_description(ui, folder);
@@ -121,13 +234,20 @@ impl ClearTabState {
         // 显示操作按钮
         self.show_folder_actions(ui, folder);
     }
+
+    // 文件夹或其任一上级在忽略名单中
+    fn is_ignored(&self, folder: &str) -> bool {
+        self.ignored_folders.contains(folder)
+            || tree::ancestors(folder).any(|ancestor| self.ignored_folders.contains(ancestor))
+    }
+
     pub fn new() -> Self {
         Self::default()
     }
 
     pub fn set_generate_description_callback<F>(&mut self, callback: F)
     where
-        F: Fn(&str) + Send + 'static,
+        F: Fn(&str, &str) + Send + 'static,
     {
         self.generate_description_callback = Some(Box::new(callback));
     }
@@ -139,72 +259,11 @@ impl ClearTabState {
         self.generate_all_descriptions_callback = Some(Box::new(callback));
     }
 
-    // 抽取文件夹操作逻辑到单独的方法
-    pub fn handle_delete_confirmation(
-        ctx: &egui::Context,
-        confirm_delete: &mut Option<(String, bool)>,
-        selected_appdata_folder: &str,
-        status: &mut Option<String>,
-        folder_data: &mut Vec<(String, u64)>, // 新增参数
-        stats: &mut Stats,                    // 新增参数
-        stats_logger: &StatsLogger,           // 新增参数
-    ) {
-        if let Some((folder_name, is_bulk)) = confirm_delete.clone() {
-            if is_bulk && folder_name == "BULK_DELETE" {
-                let message = "确定要批量删除选中的文件夹吗？";
-                if let Some(confirm) = show_confirmation(ctx, message, status) {
-                    if confirm {
-                        let selected_folders: Vec<String> = folder_data
-                            .iter()
-                            .filter(|(_folder, _)| confirm_delete.as_ref().map_or(false, |c| c.1))
-                            .map(|(folder, _)| folder.clone())
-                            .collect();
-
-                        for folder in &selected_folders {
-                            if let Some(base_path) = utils::get_appdata_dir(selected_appdata_folder)
-                            {
-                                let full_path = base_path.join(&folder);
-                                if let Err(err) =
-                                    delete::delete_folder(&full_path, stats, stats_logger)
-                                {
-                                    logger::log_error(&format!("批量删除失败: {}", err));
-                                } else {
-                                    logger::log_info(&format!("已删除文件夹: {}", folder));
-                                }
-                            }
-                        }
-                        folder_data.retain(|(folder, _)| !selected_folders.contains(folder));
-                        *status = Some("批量删除完成".to_string());
-                    }
-                    *confirm_delete = None;
-                }
-            } else {
-                let message = format!("确定要彻底删除文件夹 {} 吗？", folder_name);
-                if let Some(confirm) = show_confirmation(ctx, &message, status) {
-                    if confirm {
-                        if let Some(base_path) = utils::get_appdata_dir(selected_appdata_folder) {
-                            let full_path = base_path.join(&folder_name);
-                            if let Err(err) = delete::delete_folder(&full_path, stats, stats_logger)
-                            {
-                                logger::log_error(&format!("删除失败: {}", err));
-                            } else {
-                                logger::log_info(&format!("已删除文件夹: {}", folder_name));
-                                folder_data.retain(|(folder, _)| folder != &folder_name);
-                            }
-                            *status = Some(format!("文件夹 {} 已成功删除", folder_name));
-                        }
-                    }
-                    *confirm_delete = None;
-                }
-            }
-        }
-    }
-
     fn show_folder_description(&self, ui: &mut egui::Ui, folder: &str) {
         let description = self
             .folder_descriptions
             .as_ref()
-            .and_then(|desc| desc.get_description(folder, &self.selected_appdata_folder));
+            .and_then(|desc| desc.get_description(folder, self.selected_root.kind));
 
         match description {
             Some(desc) => ui.label(desc),
@@ -213,15 +272,20 @@ impl ClearTabState {
     }
 
     fn show_folder_actions(&mut self, ui: &mut egui::Ui, folder: &str) {
-        let is_ignored = self.ignored_folders.contains(folder);
+        let is_ignored = self.is_ignored(folder);
 
         if !is_ignored {
-            if ui.button("彻底删除").clicked() {
-                self.confirm_delete = Some((folder.to_string(), false));
-                self.status = None;
+            for mode in DeleteMode::ALL {
+                if ui.button(mode.action_label()).on_hover_text(mode.label()).clicked() {
+                    self.confirm_delete = Some((folder.to_string(), false));
+                    self.delete_mode = mode;
+                    self.status = None;
+                }
             }
             if ui.button("移动").clicked() {
                 self.move_module.show_window = true;
+                self.move_module.source_root = self.selected_root.path.clone();
+                self.move_module.profile = self.selected_root.profile.clone();
                 self.move_module.folder_name = folder.to_string();
             }
             if ui.button("忽略").clicked() {
@@ -231,37 +295,42 @@ impl ClearTabState {
             }
         } else {
             ui.add_enabled(false, |ui: &mut egui::Ui| {
-                let response1 = ui.button("彻底删除");
-                let response2 = ui.button("移动");
-                let response3 = ui.button("忽略");
-                response1 | response2 | response3
+                let mut response = ui.button(DeleteMode::ALL[0].action_label());
+                for mode in &DeleteMode::ALL[1..] {
+                    response |= ui.button(mode.action_label());
+                }
+                response | ui.button("移动") | ui.button("忽略")
             });
         }
 
         if ui.button("打开").clicked() {
-            if let Some(base_path) = utils::get_appdata_dir(&self.selected_appdata_folder) {
-                let full_path = base_path.join(folder);
-                if let Err(err) = open::open_folder(&full_path) {
-                    logger::log_error(&format!("无法打开文件夹: {}", err));
-                }
+            let full_path = self.selected_root.path.join(folder);
+            if let Err(err) = open::open_folder(&full_path) {
+                logger::log_error(&format!("无法打开文件夹: {}", err));
             }
         }
 
         if ui.button("生成描述").clicked() {
             self.generate_description(folder);
         }
+
+        if ui.button("详情").clicked() {
+            self.details_window.open(&self.selected_root, folder);
+        }
     }
 
     fn generate_description(&mut self, folder: &str) {
         if let Some(callback) = &self.generate_description_callback {
             self.status = Some(format!("正在为 {} 生成描述...", folder));
-            // 传递实际的文件夹名和当前选中的AppData文件夹
-            callback(folder);
+            // 传递实际的文件夹名和当前根目录的类别
+            callback(folder, self.selected_root.kind.as_str());
         }
     }
 
     pub fn show_sort_controls(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_treemap, "矩形树图");
+
             // 添加排序按钮
             ui.menu_button("排序", |ui| {
                 if ui.button("名称正序").clicked() {
@@ -280,7 +349,20 @@ impl ClearTabState {
                     self.sort_criterion = Some("size".to_string());
                     self.sort_order = Some("desc".to_string());
                 }
+                if ui.button("最久未使用优先").clicked() {
+                    self.sort_criterion = Some("last_used".to_string());
+                    self.sort_order = Some("asc".to_string());
+                }
+                if ui.button("最近使用优先").clicked() {
+                    self.sort_criterion = Some("last_used".to_string());
+                    self.sort_order = Some("desc".to_string());
+                }
             });
+
+            // 只显示长期未使用的文件夹
+            ui.checkbox(&mut self.stale_filter, "只显示超过");
+            ui.add(egui::DragValue::new(&mut self.stale_days).range(1..=3650));
+            ui.label("天未使用");
             
             // 数据库状态显示
             self.show_database_status(ui);
@@ -290,6 +372,9 @@ impl ClearTabState {
         self.total_size = self.folder_data.iter().map(|(_, size)| size).sum();
 
         ui.horizontal(|ui| {
+            // 显示当前配置文件
+            ui.label(format!("配置文件: {}", self.selected_root.profile));
+
             // 显示总大小
             ui.label(format!("总大小: {}", utils::format_size(self.total_size)));
 
@@ -338,38 +423,93 @@ impl ClearTabState {
         Grid::new("folders_table").striped(true).show(ui, |ui| {
             ui.label("文件夹");
             ui.label("大小");
+            ui.label("磁盘占用");
+            ui.label("最近使用");
+            ui.label("所属程序");
             ui.label("描述");
             ui.label("操作");
             ui.end_row();
 
-            // 先排序
-            if let Some(criterion) = &self.sort_criterion {
-                self.folder_data.sort_by(|a, b| {
-                    if *criterion == "name" {
-                        if self.sort_order == Some("asc".to_string()) {
-                            a.0.cmp(&b.0)
-                        } else {
-                            b.0.cmp(&a.0)
-                        }
-                    } else {
-                        if self.sort_order == Some("asc".to_string()) {
-                            a.1.cmp(&b.1)
-                        } else {
-                            b.1.cmp(&a.1)
-                        }
+            // 顶层文件夹，展开的文件夹紧随其后显示子文件夹
+            let mut rows: Vec<FolderRow> = self
+                .folder_data
+                .iter()
+                .map(|(folder, size)| {
+                    let meta = self.folder_meta.get(folder).cloned().unwrap_or_default();
+                    FolderRow {
+                        path: folder.clone(),
+                        depth: 0,
+                        size: Some(FolderSize {
+                            apparent: *size,
+                            on_disk: meta.disk_size,
+                            last_modified: meta.last_modified,
+                            last_accessed: meta.last_accessed,
+                        }),
+                        link_target: meta.link_target,
                     }
-                });
+                })
+                .collect();
+            self.show_rows(ui, &mut rows);
+        });
+    }
+
+    // 排序、过滤后逐行显示，并递归显示已展开文件夹的子文件夹
+    fn show_rows(&mut self, ui: &mut egui::Ui, rows: &mut [FolderRow]) {
+        if self.sort_criterion.is_some() {
+            rows.sort_by(|a, b| self.compare_rows(a, b));
+        }
+        for row in rows.iter() {
+            if !self.matches_stale_filter(row) {
+                continue;
             }
+            self.handle_folder_operations(ui, row);
+            ui.end_row();
 
-            // 创建一个临时向量来存储需要处理的数据
-            let folder_data = self.folder_data.clone();
+            if self.tree.is_expanded(&row.path) {
+                let mut children: Vec<FolderRow> = self
+                    .tree
+                    .children(&row.path)
+                    .into_iter()
+                    .map(|(path, node)| FolderRow {
+                        path,
+                        depth: row.depth + 1,
+                        size: node.size,
+                        link_target: node.link_target,
+                    })
+                    .collect();
+                self.show_rows(ui, &mut children);
+            }
+        }
+    }
 
-            // 使用临时数据进行遍历
-            for (folder, size) in folder_data {
-                self.handle_folder_operations(ui, &folder, size);
-                ui.end_row();
+    // 按当前排序方式比较两行
+    fn compare_rows(&self, a: &FolderRow, b: &FolderRow) -> Ordering {
+        let ordering = match self.sort_criterion.as_deref() {
+            Some("name") => tree::node_name(&a.path).cmp(tree::node_name(&b.path)),
+            Some("last_used") => {
+                let last_used = |row: &FolderRow| row.size.and_then(|size| size.last_used());
+                last_used(a).cmp(&last_used(b))
             }
-        });
+            _ => {
+                let size = |row: &FolderRow| row.size.map(|size| size.apparent).unwrap_or(0);
+                size(a).cmp(&size(b))
+            }
+        };
+        if self.sort_order.as_deref() == Some("asc") {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+
+    // 启用过滤时，只保留超过指定天数未使用的文件夹（时间未知的不显示）
+    fn matches_stale_filter(&self, row: &FolderRow) -> bool {
+        if !self.stale_filter {
+            return true;
+        }
+        row.size
+            .and_then(|size| size.last_used())
+            .is_some_and(|last_used| (Utc::now() - last_used).num_days() >= i64::from(self.stale_days))
     }
 
     pub fn show(&mut self, ui: &mut egui::Ui) {
@@ -380,35 +520,58 @@ impl ClearTabState {
         }
 
         // 删除确认弹窗逻辑
+        let target = confirmation::DeleteTarget {
+            mode: self.delete_mode,                   // 移到回收站或彻底删除
+            root: &self.selected_root,                // 传递选中的根目录
+            folder_data: &self.folder_data,           // 传递文件夹数据
+            selected_folders: &self.selected_folders, // 传递选中的文件夹集合
+        };
         confirmation::handle_delete_confirmation(
             ui.ctx(),                      // 传递上下文
             &mut self.confirm_delete,      // 传递确认删除状态
-            &self.selected_appdata_folder, // 传递选中的 AppData 文件夹
+            target,
             &mut self.status,              // 传递状态
-            &mut self.folder_data,         // 传递文件夹数据
-            &mut self.selected_folders,    // 传递选中的文件夹集合
-            &mut self.stats,               // 传递统计数据
-            &self.stats_logger,            // 传递统计日志记录器
+            &mut self.delete_worker,       // 后台删除任务
         );
 
+        // 接收后台删除的进度与结果
+        self.poll_delete_worker();
+
         // 扫描按钮和生成描述按钮放在一起
         ui.horizontal(|ui| {
-            if ui.button("立即扫描").clicked() && !self.is_scanning {
+            // 删除过程中扫描会把删除了一半的文件夹重新加入列表
+            let deleting = self.delete_worker.is_some();
+            let scan_button = ui
+                .add_enabled(!deleting, egui::Button::new("立即扫描"))
+                .on_disabled_hover_text("删除完成或取消后再扫描");
+            if scan_button.clicked() && !self.is_scanning {
                 self.is_scanning = true;
                 self.folder_data.clear();
+                self.folder_meta.clear();
+                self.tree.clear();
+                self.treemap_focus = None;
                 self.status = Some("扫描中...".to_string());
 
                 let tx = self.tx.clone().unwrap();
-                let folder_type = self.selected_appdata_folder.clone();
+                scanner::scan_appdata(tx, &self.selected_root);
+            }
 
-                scanner::scan_appdata(tx, &folder_type);
+            if ui.button("快照对比").clicked() {
+                self.snapshot_window.open(&self.selected_root);
             }
 
+            if ui.button("删除报告").clicked() {
+                self.report_window.open();
+            }
+
+            ui.checkbox(&mut self.watch_enabled, "实时监视")
+                .on_hover_text("文件夹出现、变化或消失时自动更新大小，无需重新扫描");
+
             // 一键生成所有描述按钮
             if ui.button("一键生成所有描述").clicked() {
                 if let Some(callback) = &self.generate_all_descriptions_callback {
                     self.status = Some("正在生成描述...".to_string());
-                    callback(&self.folder_data, &self.selected_appdata_folder);
+                    callback(&self.folder_data, self.selected_root.kind.as_str());
                 }
             }
         });
@@ -416,42 +579,326 @@ impl ClearTabState {
         // 添加批量操作按钮
         self.show_bulk_actions(ui);
 
-        // 接收扫描结果
+        // 接收扫描事件
         if let Some(rx) = &self.rx {
-            while let Ok((folder, size)) = rx.try_recv() {
-                // 检查是否接收到扫描完成标志
-                if folder == "__SCAN_COMPLETE__" {
-                    self.is_scanning = false;
-                    self.status = Some("扫描完成".to_string());
-                } else if folder.starts_with("__STATUS__") {
-                    // 处理状态消息
-                    let status_msg = folder.strip_prefix("__STATUS__").unwrap_or(&folder);
-                    self.status = Some(status_msg.to_string());
-                } else {
-                    self.folder_data.push((folder, size));
+            let messages: Vec<ScanMessage> = rx.try_iter().collect();
+            for message in messages {
+                // 丢弃切换根目录前仍在运行的扫描发来的事件
+                if message.root_id == self.selected_root.id {
+                    self.apply_scan_event(message.event);
                 }
             }
         }
 
+        // 按开关启动或停止监视，切换根目录后跟随新的根目录
+        self.sync_watcher();
+
+        // 接收子文件夹大小
+        self.tree.poll();
+
+        // 扫描与删除期间结果逐个到达，定时重绘以便及时显示
+        if self.is_scanning || self.tree.is_loading() || self.delete_worker.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        } else if self.watcher.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+        }
+
         // 显示状态
         if let Some(status) = &self.status {
             ui.label(status);
         }
 
+        // 删除进度与取消按钮
+        self.show_delete_progress(ui);
+
+        // 显示扫描中无法访问的路径
+        if !self.scan_errors.is_empty() {
+            egui::CollapsingHeader::new(format!("无法访问的路径 ({})", self.scan_errors.len()))
+                .id_salt("scan_errors")
+                .show(ui, |ui| {
+                    for (path, kind) in &self.scan_errors {
+                        ui.label(format!("{} - {}", path.display(), kind));
+                    }
+                });
+        }
+
         // 排序控件
         self.show_sort_controls(ui);
 
+        // 矩形树图显示在文件夹列表右侧
+        if self.show_treemap {
+            egui::SidePanel::right("treemap_panel")
+                .resizable(true)
+                .default_width(360.0)
+                .show_inside(ui, |ui| self.show_treemap_panel(ui));
+        }
+
         // 文件夹列表
         ScrollArea::vertical().show(ui, |ui| {
             self.show_folder_grid(ui);
         });
     }
 
+    fn show_treemap_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.treemap_color, ColorMode::Category, "按类别");
+            ui.radio_value(&mut self.treemap_color, ColorMode::Age, "按最近使用");
+        });
+        ui.horizontal(|ui| match self.treemap_focus.clone() {
+            Some(focus) => {
+                if ui.button("⬆ 上一级").clicked() {
+                    self.treemap_focus = focus.rsplit_once('/').map(|(parent, _)| parent.to_string());
+                }
+                ui.label(focus);
+            }
+            None => {
+                ui.label(&self.selected_root.label);
+            }
+        });
+
+        // 点击矩形进入该文件夹，子文件夹大小由文件夹树在后台计算
+        let items = self.treemap_items();
+        if let Some(index) = treemap::show(ui, &items, self.treemap_color) {
+            let path = items[index].path.clone();
+            if !self.tree.is_expanded(&path) {
+                self.tree.toggle(&self.selected_root, &path);
+            }
+            self.treemap_focus = Some(path);
+        }
+    }
+
+    // 当前层级的矩形，与表格使用同一份扫描数据
+    fn treemap_items(&self) -> Vec<TreemapItem> {
+        match &self.treemap_focus {
+            None => self
+                .folder_data
+                .iter()
+                .map(|(folder, size)| {
+                    let meta = self.folder_meta.get(folder);
+                    TreemapItem {
+                        path: folder.clone(),
+                        label: folder.clone(),
+                        size: *size,
+                        last_used: meta.and_then(|meta| meta.last_used()),
+                        is_link: meta.is_some_and(|meta| meta.link_target.is_some()),
+                    }
+                })
+                .collect(),
+            Some(focus) => self
+                .tree
+                .children(focus)
+                .into_iter()
+                .map(|(path, node)| TreemapItem {
+                    label: tree::node_name(&path).to_string(),
+                    size: node.size.map(|size| size.apparent).unwrap_or(0),
+                    last_used: node.size.and_then(|size| size.last_used()),
+                    is_link: node.link_target.is_some(),
+                    path,
+                })
+                .collect(),
+        }
+    }
+
+    // 根据扫描事件更新界面数据，同名文件夹只保留一行
+    fn apply_scan_event(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Started { from_cache } => {
+                self.scan_errors.clear();
+                self.status = Some(if from_cache {
+                    "从缓存加载数据...".to_string()
+                } else {
+                    "扫描中...".to_string()
+                });
+            }
+            ScanEvent::FolderAdded { name, size } => self.upsert_folder(name, size),
+            ScanEvent::FolderChanged { name, new_size, .. } => self.upsert_folder(name, new_size),
+            ScanEvent::FolderMeta { name, meta } => {
+                self.folder_meta.insert(name, *meta);
+            }
+            ScanEvent::FolderRemoved { name } => {
+                self.folder_data.retain(|(folder, _)| folder != &name);
+                self.folder_meta.remove(&name);
+                self.selected_folders.remove(&name);
+            }
+            ScanEvent::Progress { done, total } => {
+                self.status = Some(format!("正在检查文件系统变化... ({}/{})", done, total));
+            }
+            ScanEvent::Error { path, kind } => {
+                logger::log_error(&format!(
+                    "[{}] 扫描 {} 失败: {}",
+                    self.selected_root.profile,
+                    path.display(),
+                    kind
+                ));
+                self.scan_errors.push((path, kind));
+            }
+            ScanEvent::Completed { stats } => {
+                self.is_scanning = false;
+                let mut status = format!(
+                    "扫描完成: {} 个文件夹，新增 {}，变化 {}，移除 {}",
+                    stats.total_folders, stats.added, stats.changed, stats.removed
+                );
+                if stats.from_cache + stats.recomputed > 0 {
+                    status.push_str(&format!(
+                        "（缓存 {}，重新计算 {}）",
+                        stats.from_cache, stats.recomputed
+                    ));
+                }
+                if stats.errors > 0 {
+                    status.push_str(&format!("，{} 个路径无法访问", stats.errors));
+                }
+                self.status = Some(status);
+            }
+        }
+    }
+
+    // 每个文件夹删除完毕后更新列表与统计，全部完成后汇总
+    fn poll_delete_worker(&mut self) {
+        let Some(worker) = &mut self.delete_worker else {
+            return;
+        };
+        let (finished, done) = worker.poll();
+        let mode = worker.mode;
+        let total_folders = worker.total_folders;
+        let log_ctx = LogContext::new("删除")
+            .with_target_type(self.selected_root.profile.clone())
+            .with_target_name(self.selected_root.label.clone());
+        for (folder, result) in finished {
+            match result {
+                Err(err) => {
+                    logger::log_structured_error(&log_ctx, &format!("删除失败: {}", err));
+                    self.delete_results.push((folder, Err(err)));
+                }
+                Ok(report) => {
+                    delete::record_stats(&report, &mut self.stats, &self.stats_logger);
+                    self.apply_delete_report(&folder, &report);
+                    if report.is_complete() && !report.planned {
+                        logger::log_structured_info(&log_ctx, &format!("已删除文件夹: {}", folder));
+                        // 已完全删除的文件夹取消勾选；未能完全删除的保留勾选，便于重试
+                        self.selected_folders.remove(&folder);
+                    }
+                    self.delete_results.push((folder, Ok(report)));
+                }
+            }
+        }
+        if !done {
+            return;
+        }
+
+        let results = std::mem::take(&mut self.delete_results);
+        self.delete_worker = None;
+        self.prune_deleted_subfolders();
+        let reports: Vec<&DeleteReport> = results.iter().filter_map(|(_, result)| result.as_ref().ok()).collect();
+        // 取消不算失败：被取消的文件夹既不计入已删除也不计入失败
+        let failed = results.len() - reports.len()
+            + reports.iter().filter(|report| !report.failures.is_empty()).count();
+        if reports.iter().any(|report| report.planned) {
+            self.status = Some(match results.as_slice() {
+                [(folder, Ok(_))] => format!("演练：文件夹 {} 已记录到演练计划", folder),
+                _ => format!("演练：{} 个文件夹已记录到演练计划", reports.len()),
+            });
+            return;
+        }
+        self.status = Some(match results.as_slice() {
+            [(_, Err(err))] if total_folders == 1 => err.clone(),
+            [(folder, Ok(report))] if total_folders == 1 => {
+                let summary = report.summary();
+                if report.is_complete() {
+                    format!("文件夹 {} 已{}，{}", folder, mode.label(), summary)
+                } else {
+                    format!("文件夹 {} 未能完全删除，{}", folder, summary)
+                }
+            }
+            _ => {
+                let deleted = reports.iter().filter(|report| report.is_complete()).count();
+                let freed: u64 = reports.iter().map(|report| report.bytes_freed).sum();
+                let cancelled = reports.iter().any(|report| report.cancelled) || results.len() < total_folders;
+                let mut status = format!(
+                    "批量删除完成：{} 个文件夹，释放 {}，{} 个失败",
+                    deleted,
+                    utils::format_size(freed),
+                    failed
+                );
+                if cancelled {
+                    status.push_str(&format!("，已取消，{} 个未处理", total_folders - results.len()));
+                }
+                status
+            }
+        });
+        if reports.iter().any(|report| !report.failures.is_empty()) || (total_folders > 1 && failed > 0) {
+            self.report_window.open();
+        }
+    }
+
+    // 完全删除的文件夹从列表中移除，部分删除的扣除已释放的大小；演练的报告不改动列表
+    fn apply_delete_report(&mut self, folder_name: &str, report: &DeleteReport) {
+        if report.planned {
+            return;
+        }
+        if report.is_complete() {
+            self.folder_data.retain(|(folder, _)| folder != folder_name);
+            self.folder_meta.remove(folder_name);
+        } else if let Some(entry) = self.folder_data.iter_mut().find(|(folder, _)| folder == folder_name) {
+            entry.1 = entry.1.saturating_sub(report.bytes_freed);
+        }
+    }
+
+    fn show_delete_progress(&mut self, ui: &mut egui::Ui) {
+        if let Some(worker) = &self.delete_worker {
+            show_delete_progress(ui, worker);
+        }
+    }
+
+    /// 其他标签页删除了某个根目录中的顶层文件夹：计入统计，属于当前根目录时同步列表
+    pub fn apply_folder_report(&mut self, root_id: &str, folder_name: &str, report: &DeleteReport) {
+        delete::record_stats(report, &mut self.stats, &self.stats_logger);
+        if self.selected_root.id != root_id {
+            return;
+        }
+        self.apply_delete_report(folder_name, report);
+        if report.is_complete() && !report.planned {
+            self.selected_folders.remove(folder_name);
+        }
+    }
+
+    // 删除子文件夹后从树中移除，并扣除所在顶层文件夹的大小
+    fn prune_deleted_subfolders(&mut self) {
+        // 矩形树图所在的文件夹被删除时回到根目录
+        if let Some(focus) = &self.treemap_focus {
+            if !self.selected_root.path.join(focus).exists() {
+                self.treemap_focus = None;
+            }
+        }
+
+        for (path, freed) in self.tree.prune_missing(&self.selected_root.path) {
+            let Some((top, _)) = path.split_once('/') else {
+                continue;
+            };
+            if let Some(entry) = self.folder_data.iter_mut().find(|(folder, _)| folder == top) {
+                entry.1 = entry.1.saturating_sub(freed.apparent);
+            }
+            if let Some(meta) = self.folder_meta.get_mut(top) {
+                meta.disk_size = meta.disk_size.saturating_sub(freed.on_disk);
+            }
+        }
+    }
+
+    fn upsert_folder(&mut self, name: String, size: u64) {
+        match self.folder_data.iter_mut().find(|(folder, _)| *folder == name) {
+            Some(entry) => entry.1 = size,
+            None => self.folder_data.push((name, size)),
+        }
+    }
+
     pub fn show_bulk_actions(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            if ui.button("批量删除").clicked() {
+            for mode in DeleteMode::ALL {
+                let button = ui.button(format!("批量{}", mode.action_label()));
+                if !button.on_hover_text(mode.label()).clicked() {
+                    continue;
+                }
                 for folder in &self.selected_folders {
-                    if self.ignored_folders.contains(folder) {
+                    if self.is_ignored(folder) {
                         self.status = Some(format!("文件夹 '{}' 在忽略名单中，无法删除", folder));
                         logger::log_info(&format!("文件夹 '{}' 在忽略名单中，无法删除", folder));
                         return;
@@ -460,6 +907,7 @@ impl ClearTabState {
 
                 if !self.selected_folders.is_empty() {
                     self.confirm_delete = Some(("BULK_DELETE".to_string(), true));
+                    self.delete_mode = mode;
                     self.status = None; // 确保状态信息不影响按钮显示
                 } else {
                     self.status = Some("未选择任何文件夹，无法执行批量删除".to_string());
@@ -477,19 +925,32 @@ impl ClearTabState {
         });
     }
 
-    // 设置选中的AppData文件夹
-    pub fn set_selected_appdata_folder(&mut self, folder: String) {
-        self.selected_appdata_folder = folder.clone();
+    // 设置选中的根目录；后台删除的结果只对当前根目录的列表有效，删除过程中不切换
+    pub fn set_selected_root(&mut self, root: ScanRoot) {
+        if self.delete_worker.is_some() {
+            self.status = Some("正在删除，请等待完成或取消后再切换根目录".to_string());
+            return;
+        }
+        self.selected_root = root;
         self.folder_data.clear();
+        self.folder_meta.clear();
+        self.tree.clear();
+        self.treemap_focus = None;
+        self.selected_folders.clear();
+        self.scan_errors.clear();
         self.is_scanning = false;
         self.status = Some("未扫描".to_string());
 
         // 尝试加载数据库缓存（如果有）
-        if let Ok(db) = crate::database::Database::new("appdata_cleaner.db") {
-            if db.has_data_for_type(&folder).unwrap_or(false) {
+        if let Ok(db) = Database::new(&get_default_db_path()) {
+            if db.has_data_for_type(&self.selected_root.id).unwrap_or(false) {
                 // 有缓存则直接加载
-                if let Ok(records) = db.get_folders_by_type(&folder) {
+                if let Ok(records) = db.get_folders_by_type(&self.selected_root.id) {
                     self.folder_data = records.iter().map(|r| (r.folder_name.clone(), r.folder_size)).collect();
+                    self.folder_meta = records
+                        .iter()
+                        .map(|r| (r.folder_name.clone(), FolderMeta::from(r)))
+                        .collect();
                     self.is_scanning = false;
                     self.status = Some("已加载缓存".to_string());
                     return;
@@ -500,8 +961,29 @@ impl ClearTabState {
         self.is_scanning = true;
         self.status = Some("扫描中...".to_string());
         if let Some(tx) = self.tx.clone() {
-            let folder_type = self.selected_appdata_folder.clone();
-            crate::scanner::scan_appdata(tx, &folder_type);
+            scanner::scan_appdata(tx, &self.selected_root);
+        }
+    }
+
+    fn sync_watcher(&mut self) {
+        if !self.watch_enabled {
+            self.watcher = None;
+            return;
+        }
+        if self.watcher.as_ref().is_some_and(|watcher| watcher.root_id == self.selected_root.id) {
+            return;
+        }
+        let Some(tx) = self.tx.clone() else {
+            return;
+        };
+        match FolderWatcher::start(&self.selected_root, tx) {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => {
+                logger::log_error(&format!("无法监视 {}: {}", self.selected_root.path.display(), e));
+                self.status = Some(format!("无法监视: {}", e));
+                self.watch_enabled = false;
+                self.watcher = None;
+            }
         }
     }
 
@@ -511,3 +993,26 @@ impl ClearTabState {
             load_folder_descriptions("folders_description.yaml", &mut self.yaml_error_logged);
     }
 }
+
+/// 后台删除的进度条与取消按钮，各标签页共用
+pub fn show_delete_progress(ui: &mut egui::Ui, worker: &delete::DeleteWorker) {
+    let (files, bytes) = worker.progress();
+    let text = format!(
+        "正在{}：{} / {} 个文件夹，已删除 {} 个文件，{} / {}",
+        worker.mode.label(),
+        worker.finished_folders,
+        worker.total_folders,
+        files,
+        utils::format_size(bytes),
+        utils::format_size(worker.total_bytes)
+    );
+    ui.horizontal(|ui| {
+        ui.add(egui::ProgressBar::new(worker.fraction()).desired_width(240.0));
+        ui.label(text);
+        if worker.is_cancelling() {
+            ui.label("正在取消...");
+        } else if ui.button("取消").on_hover_text("删除完当前文件后停止").clicked() {
+            worker.cancel();
+        }
+    });
+}
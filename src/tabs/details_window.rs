@@ -0,0 +1,140 @@
+use crate::database::{get_default_db_path, Database, FolderDetails};
+use crate::details::{self, TOP_FILES};
+use crate::logger::{self, LogContext};
+use crate::roots::ScanRoot;
+use crate::utils;
+use chrono::{DateTime, Utc};
+use eframe::egui::{self, Grid, ScrollArea};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// 文件夹详情窗口
+#[derive(Default)]
+pub struct DetailsWindow {
+    pub show_window: bool,
+    root: Option<ScanRoot>,
+    folder_name: String, // 相对于根目录的路径
+    details: Option<FolderDetails>,
+    receiver: Option<Receiver<FolderDetails>>,
+}
+
+impl DetailsWindow {
+    /// 打开某个文件夹的详情，优先显示数据库中保存的结果
+    pub fn open(&mut self, root: &ScanRoot, folder_name: &str) {
+        self.show_window = true;
+        self.root = Some(root.clone());
+        self.folder_name = folder_name.to_string();
+        self.details = Database::new(&get_default_db_path())
+            .and_then(|db| db.get_folder_details(&root.id, folder_name))
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取文件夹详情失败: {}", e));
+                None
+            });
+        if self.details.is_none() {
+            self.start_compute();
+        } else {
+            self.receiver = None;
+        }
+    }
+
+    // 在后台统计并保存到数据库
+    fn start_compute(&mut self) {
+        let Some(root) = self.root.clone() else {
+            return;
+        };
+        let folder_name = self.folder_name.clone();
+        let (tx, rx) = mpsc::channel();
+        self.receiver = Some(rx);
+        self.details = None;
+
+        thread::spawn(move || {
+            let ctx = LogContext::new("详情")
+                .with_target_type(root.profile.clone())
+                .with_target_name(folder_name.clone());
+            let details = details::compute_details(&root.path.join(&folder_name), TOP_FILES);
+            match Database::new(&get_default_db_path())
+                .and_then(|db| db.save_folder_details(&root.id, &folder_name, &details))
+            {
+                Ok(()) => logger::log_structured_info(&ctx, "文件夹详情统计完成"),
+                Err(e) => logger::log_structured_error(&ctx, &format!("保存文件夹详情失败: {}", e)),
+            }
+            let _ = tx.send(details);
+        });
+    }
+
+    pub fn show_details_window(&mut self, ctx: &egui::Context) {
+        if let Some(rx) = &self.receiver {
+            if let Ok(details) = rx.try_recv() {
+                self.details = Some(details);
+                self.receiver = None;
+            } else {
+                ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            }
+        }
+
+        if !self.show_window {
+            return;
+        }
+        let mut open = self.show_window;
+        egui::Window::new(format!("文件夹详情: {}", self.folder_name))
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| match self.details.clone() {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("正在统计...");
+                    });
+                }
+                Some(details) => {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "统计于 {}",
+                            details.computed_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                        ));
+                        if ui.button("重新统计").clicked() {
+                            self.start_compute();
+                        }
+                    });
+                    ui.label(format!("最早修改: {}", format_time(details.oldest_mtime)));
+                    ui.label(format!("最近修改: {}", format_time(details.newest_mtime)));
+                    ui.separator();
+                    show_details_tables(ui, &details);
+                }
+            });
+        self.show_window = open;
+    }
+}
+
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "无文件".to_string())
+}
+
+fn show_details_tables(ui: &mut egui::Ui, details: &FolderDetails) {
+    let total: u64 = details.extensions.iter().map(|ext| ext.bytes).sum();
+
+    ui.heading("按类型");
+    Grid::new("details_extensions").striped(true).show(ui, |ui| {
+        for ext in &details.extensions {
+            ui.label(&ext.label);
+            let fraction = if total > 0 { ext.bytes as f32 / total as f32 } else { 0.0 };
+            ui.add(egui::ProgressBar::new(fraction).desired_width(160.0));
+            ui.label(utils::format_size(ext.bytes));
+            ui.label(format!("{} 个文件", ext.files));
+            ui.end_row();
+        }
+    });
+
+    ui.separator();
+    ui.heading(format!("最大的 {} 个文件", details.largest_files.len()));
+    ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+        Grid::new("details_largest_files").striped(true).show(ui, |ui| {
+            for (path, size) in &details.largest_files {
+                ui.label(path);
+                ui.label(utils::format_size(*size));
+                ui.end_row();
+            }
+        });
+    });
+}
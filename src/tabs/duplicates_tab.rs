@@ -0,0 +1,258 @@
+use crate::confirmation;
+use crate::database::{get_default_db_path, Database};
+use crate::dry_run;
+use crate::duplicates::{self, DedupeAction, DedupeResult, DuplicateGroup, DuplicateMessage};
+use crate::roots::{self, ScanRoot};
+use crate::utils;
+use eframe::egui::{self, ScrollArea};
+use native_dialog::FileDialog;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+pub struct DuplicatesTabState {
+    // 参与查找的目录及是否勾选
+    pub folders: Vec<(PathBuf, bool)>,
+    pub min_size_kb: u64,
+
+    // 查找结果
+    pub groups: Vec<DuplicateGroup>,
+    pub keep: Vec<usize>, // 每组保留的文件序号
+    pub status: Option<String>,
+    rx: Option<Receiver<DuplicateMessage>>,
+
+    // 等待确认的操作：组序号与处理方式
+    pending: Option<(usize, DedupeAction)>,
+    // 后台执行中的操作：组序号、保留的文件序号、确认时是否为演练与结果
+    dedupe_rx: Option<Receiver<(usize, usize, bool, DedupeResult)>>,
+}
+
+impl Default for DuplicatesTabState {
+    fn default() -> Self {
+        Self {
+            // 默认勾选本机的所有根目录
+            folders: roots::native_roots().into_iter().map(|root| (root.path, true)).collect(),
+            min_size_kb: 1,
+            groups: Vec::new(),
+            keep: Vec::new(),
+            status: None,
+            rx: None,
+            pending: None,
+            dedupe_rx: None,
+        }
+    }
+}
+
+impl DuplicatesTabState {
+    pub fn show(&mut self, ui: &mut egui::Ui, current_root: &ScanRoot) {
+        self.poll(ui.ctx());
+        self.poll_dedupe(ui.ctx());
+        self.handle_confirmation(ui.ctx());
+
+        ui.heading("查找目录");
+        let mut removed = None;
+        for (index, (folder, checked)) in self.folders.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(checked, folder.display().to_string());
+                if ui.small_button("移除").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.folders.remove(index);
+        }
+
+        let is_running = self.rx.is_some() || self.dedupe_rx.is_some();
+        ui.horizontal(|ui| {
+            if ui.button("加入当前目标").clicked() {
+                self.add_folder(current_root.path.clone());
+            }
+            if ui.button("添加文件夹...").clicked() {
+                if let Ok(Some(folder)) = FileDialog::new().show_open_single_dir() {
+                    self.add_folder(folder);
+                }
+            }
+            ui.separator();
+            ui.label("最小文件大小 (KB):");
+            ui.add(egui::DragValue::new(&mut self.min_size_kb).range(0..=1_048_576));
+            if ui.add_enabled(!is_running, egui::Button::new("开始查找")).clicked() {
+                self.start_search();
+            }
+            if is_running {
+                ui.spinner();
+            }
+        });
+
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        ui.separator();
+
+        let wasted: u64 = self.groups.iter().map(DuplicateGroup::wasted).sum();
+        ui.label(format!(
+            "共 {} 组重复文件，可释放 {}",
+            self.groups.len(),
+            utils::format_size(wasted)
+        ));
+
+        ScrollArea::vertical().show(ui, |ui| {
+            for (index, group) in self.groups.iter().enumerate() {
+                let title = format!(
+                    "{} 个副本 × {}，可释放 {}",
+                    group.files.len(),
+                    utils::format_size(group.size),
+                    utils::format_size(group.wasted())
+                );
+                egui::CollapsingHeader::new(title)
+                    .id_salt(("duplicate_group", &group.hash, index))
+                    .show(ui, |ui| {
+                        ui.label("选择要保留的文件:");
+                        for (file_index, file) in group.files.iter().enumerate() {
+                            ui.radio_value(&mut self.keep[index], file_index, file.display().to_string());
+                        }
+                        ui.horizontal(|ui| {
+                            let enabled = self.pending.is_none() && !is_running;
+                            for action in DedupeAction::ALL {
+                                if ui.add_enabled(enabled, egui::Button::new(action.label())).clicked() {
+                                    self.pending = Some((index, action));
+                                }
+                            }
+                        });
+                    });
+            }
+        });
+    }
+
+    fn add_folder(&mut self, folder: PathBuf) {
+        if !folder.as_os_str().is_empty() && !self.folders.iter().any(|(existing, _)| existing == &folder) {
+            self.folders.push((folder, true));
+        }
+    }
+
+    fn start_search(&mut self) {
+        let folders: Vec<PathBuf> = self
+            .folders
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(folder, _)| folder.clone())
+            .collect();
+        if folders.is_empty() {
+            self.status = Some("请至少勾选一个目录".to_string());
+            return;
+        }
+        let min_size = self.min_size_kb * 1024;
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        self.groups.clear();
+        self.keep.clear();
+        self.status = Some("正在查找...".to_string());
+
+        thread::spawn(move || {
+            let groups = duplicates::find_duplicates(&folders, min_size, Some(&tx));
+            let _ = tx.send(DuplicateMessage::Finished(groups));
+        });
+    }
+
+    // 接收后台查找的进度与结果
+    fn poll(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        while let Ok(message) = rx.try_recv() {
+            match message {
+                DuplicateMessage::Progress(progress) => self.status = Some(progress),
+                DuplicateMessage::Finished(groups) => {
+                    self.status = Some(format!("查找完成，共 {} 组", groups.len()));
+                    self.keep = vec![0; groups.len()];
+                    self.groups = groups;
+                    self.rx = None;
+                    return;
+                }
+            }
+        }
+        ctx.request_repaint_after(Duration::from_millis(200));
+    }
+
+    fn handle_confirmation(&mut self, ctx: &egui::Context) {
+        let Some((index, action)) = self.pending else {
+            return;
+        };
+        let Some(group) = self.groups.get(index) else {
+            self.pending = None;
+            return;
+        };
+        let keep = self.keep[index];
+        let message = format!(
+            "保留 {}，对其余 {} 个文件执行“{}”？",
+            group.files[keep].display(),
+            group.files.len() - 1,
+            action.label()
+        );
+        let confirmed = match action {
+            DedupeAction::Delete(mode) => confirmation::confirm_deletion(ctx, mode, &message, &None),
+            DedupeAction::HardLink => confirmation::show_confirmation(ctx, &message, &None),
+        };
+        match confirmed {
+            Some(true) => {
+                // 重新计算哈希并逐个处理，文件多时耗时较长，放到后台线程
+                // 演练开关在确认时读取，处理过程中切换不影响本次操作
+                let (group, dry_run) = (group.clone(), dry_run::is_enabled());
+                let (tx, rx) = mpsc::channel();
+                thread::spawn(move || {
+                    let result = match Database::new(&get_default_db_path()) {
+                        Ok(db) => duplicates::dedupe(&db, &group, keep, action, dry_run),
+                        // 无法保存删除报告时不处理任何副本
+                        Err(e) => {
+                            let reason = format!("无法打开数据库: {}", e);
+                            let others = group.files.iter().enumerate().filter(|(i, _)| *i != keep);
+                            (0, others.map(|(_, file)| (file.clone(), reason.clone())).collect())
+                        }
+                    };
+                    let _ = tx.send((index, keep, dry_run, result));
+                });
+                self.dedupe_rx = Some(rx);
+                self.status = Some(format!("正在{}...", action.label()));
+                self.pending = None;
+            }
+            Some(false) => self.pending = None,
+            None => {}
+        }
+    }
+
+    // 接收后台处理的结果，只留下保留的文件和处理失败的文件；演练时整组保留
+    fn poll_dedupe(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.dedupe_rx else {
+            return;
+        };
+        let Ok((index, keep, dry_run, (freed, failures))) = rx.try_recv() else {
+            ctx.request_repaint_after(Duration::from_millis(200));
+            return;
+        };
+        self.dedupe_rx = None;
+        if dry_run {
+            self.status = Some(if failures.is_empty() {
+                "演练：已记录到演练计划".to_string()
+            } else {
+                format!("演练：已记录到演练计划，{} 个文件处理失败", failures.len())
+            });
+            return;
+        }
+        self.status = Some(format!(
+            "已释放 {}，{} 个文件处理失败",
+            utils::format_size(freed),
+            failures.len()
+        ));
+        let kept = self.groups[index].files[keep].clone();
+        let mut remaining: Vec<PathBuf> = failures.into_iter().map(|(file, _)| file).collect();
+        if remaining.is_empty() {
+            self.groups.remove(index);
+            self.keep.remove(index);
+        } else {
+            remaining.insert(0, kept);
+            self.groups[index].files = remaining;
+            self.keep[index] = 0;
+        }
+    }
+}
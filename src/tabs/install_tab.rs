@@ -0,0 +1,296 @@
+use crate::confirmation;
+use crate::database::{get_default_db_path, Database, FootprintItem, FootprintKind, InstallRecord};
+use crate::delete::DeleteMode;
+use crate::dry_run;
+use crate::footprint;
+use crate::logger;
+use crate::roots::{self, ScanRoot};
+use crate::utils;
+use eframe::egui::{self, Grid, ScrollArea};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct InstallTabState {
+    pub baseline_name: String,
+    pub baselines: Vec<InstallRecord>,
+    pub footprints: Vec<InstallRecord>,
+    pub status: Option<String>,
+
+    // 当前查看的足迹清单及其条目
+    selected_footprint: Option<(InstallRecord, Vec<FootprintItem>)>,
+    confirm_removal: Option<DeleteMode>,
+
+    // 后台任务完成后返回状态信息
+    rx: Option<Receiver<String>>,
+    loaded: bool,
+}
+
+impl InstallTabState {
+    pub fn show(&mut self, ui: &mut egui::Ui, current_root: &ScanRoot) {
+        if !self.loaded {
+            self.reload();
+        }
+        self.poll(ui.ctx());
+        self.handle_removal_confirmation(ui.ctx());
+        let is_running = self.rx.is_some();
+
+        ui.heading("安装前基线");
+        ui.label("安装或运行软件前先记录所有根目录的状态，之后对比即可得到它留下的文件");
+        ui.horizontal(|ui| {
+            ui.label("名称:");
+            ui.text_edit_singleline(&mut self.baseline_name);
+            let can_start = !is_running && !self.baseline_name.trim().is_empty();
+            if ui.add_enabled(can_start, egui::Button::new("记录基线")).clicked() {
+                self.start_baseline(current_root);
+            }
+            if is_running {
+                ui.spinner();
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+
+        let mut generate = None;
+        let mut delete_baseline = None;
+        Grid::new("install_baselines").striped(true).show(ui, |ui| {
+            for baseline in &self.baselines {
+                ui.label(&baseline.name);
+                ui.label(format_time(baseline));
+                ui.label(format!("{} 个条目", baseline.item_count));
+                if ui.add_enabled(!is_running, egui::Button::new("生成足迹")).clicked() {
+                    generate = Some(baseline.clone());
+                }
+                if ui.button("删除").clicked() {
+                    delete_baseline = Some(baseline.id);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(baseline) = generate {
+            self.start_footprint(baseline);
+        }
+        if let Some(id) = delete_baseline {
+            self.run_db(|db| db.delete_baseline(id));
+        }
+
+        ui.separator();
+        ui.heading("足迹清单");
+        let mut selected = None;
+        let mut delete_footprint = None;
+        Grid::new("install_footprints").striped(true).show(ui, |ui| {
+            for record in &self.footprints {
+                ui.label(&record.name);
+                ui.label(format_time(record));
+                ui.label(format!("{} 项", record.item_count));
+                if ui.button("查看").clicked() {
+                    selected = Some(record.clone());
+                }
+                if ui.button("删除清单").clicked() {
+                    delete_footprint = Some(record.id);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(record) = selected {
+            match Database::new(&get_default_db_path()).and_then(|db| db.load_footprint(record.id)) {
+                Ok(items) => self.selected_footprint = Some((record, items)),
+                Err(e) => self.status = Some(format!("读取足迹清单失败: {}", e)),
+            }
+        }
+        if let Some(id) = delete_footprint {
+            if self.selected_footprint.as_ref().is_some_and(|(record, _)| record.id == id) {
+                self.selected_footprint = None;
+            }
+            self.run_db(|db| db.delete_footprint(id));
+        }
+
+        self.show_selected_footprint(ui);
+    }
+
+    fn show_selected_footprint(&mut self, ui: &mut egui::Ui) {
+        let is_running = self.rx.is_some();
+        let Some((record, items)) = &self.selected_footprint else {
+            return;
+        };
+        ui.separator();
+        let removable: u64 = items
+            .iter()
+            .filter(|item| item.kind != FootprintKind::Modified)
+            .map(|item| item.size)
+            .sum();
+        ui.horizontal(|ui| {
+            ui.heading(&record.name);
+            ui.label(format!("可删除 {}", utils::format_size(removable)));
+            for mode in DeleteMode::ALL {
+                let label = format!("一键{}足迹", mode.action_label());
+                let button = ui.add_enabled(!is_running, egui::Button::new(label));
+                if button.on_hover_text(mode.label()).clicked() {
+                    self.confirm_removal = Some(mode);
+                }
+            }
+        });
+        ui.label("修改过的文件在安装前就已存在，删除足迹时会保留；新增目录中生成足迹后才出现的文件也会保留");
+        ScrollArea::vertical().id_salt("footprint_items").show(ui, |ui| {
+            Grid::new("footprint_items_grid").striped(true).show(ui, |ui| {
+                for item in items {
+                    ui.label(item.kind.label());
+                    ui.label(&item.path);
+                    ui.label(utils::format_size(item.size));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn handle_removal_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(mode) = self.confirm_removal else {
+            return;
+        };
+        let Some((record, items)) = &self.selected_footprint else {
+            self.confirm_removal = None;
+            return;
+        };
+        let message = mode.confirm_message(&format!("足迹“{}”中新增的目录与文件", record.name));
+        match confirmation::confirm_deletion(ctx, mode, &message, &self.status) {
+            Some(true) => {
+                let (name, items) = (record.name.clone(), items.clone());
+                self.start_removal(name, items, mode);
+                self.confirm_removal = None;
+            }
+            Some(false) => self.confirm_removal = None,
+            None => {}
+        }
+    }
+
+    // 在后台按所选方式删除足迹，完成后返回结果；演练开关在确认时读取
+    fn start_removal(&mut self, name: String, items: Vec<FootprintItem>, mode: DeleteMode) {
+        self.status = Some(format!("正在{}足迹“{}”...", mode.action_label(), name));
+        let dry_run = dry_run::is_enabled();
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        thread::spawn(move || {
+            let db = match Database::new(&get_default_db_path()) {
+                Ok(db) => db,
+                Err(e) => {
+                    let _ = tx.send(format!("无法打开数据库: {}", e));
+                    return;
+                }
+            };
+            let (freed, failures) = footprint::remove_footprint(&db, &name, &items, mode, dry_run);
+            if dry_run {
+                let _ = tx.send(format!("演练：足迹“{}”已记录到演练计划", name));
+                return;
+            }
+            let status = format!(
+                "已{}足迹“{}”，释放 {}，{} 项失败",
+                mode.action_label(),
+                name,
+                utils::format_size(freed),
+                failures.len()
+            );
+            logger::log_info(&status);
+            let _ = tx.send(status);
+        });
+    }
+
+    // 记录本机所有根目录以及当前选中的根目录
+    fn start_baseline(&mut self, current_root: &ScanRoot) {
+        let mut root_paths: Vec<String> = roots::native_roots()
+            .into_iter()
+            .map(|root| root.path.to_string_lossy().to_string())
+            .collect();
+        let current = current_root.path.to_string_lossy().to_string();
+        if !current.is_empty() && !root_paths.contains(&current) {
+            root_paths.push(current);
+        }
+        let name = self.baseline_name.trim().to_string();
+        self.status = Some(format!("正在记录基线“{}”...", name));
+
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        thread::spawn(move || {
+            let entries = footprint::capture_roots(&root_paths);
+            let count: usize = entries.values().map(|files| files.len()).sum();
+            let status = match Database::new(&get_default_db_path()).and_then(|db| db.save_baseline(&name, &entries)) {
+                Ok(_) => format!("基线“{}”已记录，共 {} 个条目", name, count),
+                Err(e) => format!("保存基线失败: {}", e),
+            };
+            logger::log_info(&status);
+            let _ = tx.send(status);
+        });
+    }
+
+    // 对比基线与当前状态，保存为足迹清单
+    fn start_footprint(&mut self, baseline: InstallRecord) {
+        self.status = Some(format!("正在对比基线“{}”...", baseline.name));
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        thread::spawn(move || {
+            let result = Database::new(&get_default_db_path()).and_then(|db| {
+                let mut items = Vec::new();
+                for (root_path, files) in db.load_baseline(baseline.id)? {
+                    let root = Path::new(&root_path);
+                    items.extend(footprint::compute_footprint(root, &files, &footprint::capture(root)));
+                }
+                db.save_footprint(&baseline.name, &items)?;
+                Ok(items.len())
+            });
+            let status = match result {
+                Ok(count) => format!("已生成足迹“{}”，共 {} 项", baseline.name, count),
+                Err(e) => format!("生成足迹失败: {}", e),
+            };
+            logger::log_info(&status);
+            let _ = tx.send(status);
+        });
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(status) => {
+                self.status = Some(status);
+                self.rx = None;
+                self.reload();
+            }
+            Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(200)),
+            Err(mpsc::TryRecvError::Disconnected) => self.rx = None,
+        }
+    }
+
+    fn run_db<F>(&mut self, action: F)
+    where
+        F: FnOnce(&Database) -> rusqlite::Result<()>,
+    {
+        if let Err(e) = Database::new(&get_default_db_path()).and_then(|db| action(&db)) {
+            self.status = Some(format!("数据库操作失败: {}", e));
+        }
+        self.reload();
+    }
+
+    fn reload(&mut self) {
+        self.loaded = true;
+        let result = Database::new(&get_default_db_path())
+            .and_then(|db| Ok((db.list_baselines()?, db.list_footprints()?)));
+        match result {
+            Ok((baselines, footprints)) => {
+                self.baselines = baselines;
+                self.footprints = footprints;
+            }
+            Err(e) => self.status = Some(format!("读取安装追踪记录失败: {}", e)),
+        }
+    }
+}
+
+fn format_time(record: &InstallRecord) -> String {
+    record
+        .created_at
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
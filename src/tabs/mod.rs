@@ -1,2 +1,12 @@
+pub mod apps_tab;
 pub mod clear_tab;
-pub mod ai_ui_tab;
\ No newline at end of file
+pub mod duplicates_tab;
+pub mod install_tab;
+pub mod quarantine_tab;
+pub mod rules_tab;
+pub mod search_tab;
+pub mod ai_ui_tab;
+pub mod details_window;
+pub mod plan_window;
+pub mod report_window;
+pub mod snapshot_window;
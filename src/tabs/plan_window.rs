@@ -0,0 +1,73 @@
+use crate::dry_run::{self, PlannedAction};
+use crate::utils;
+use eframe::egui::{self, Grid, ScrollArea};
+use native_dialog::FileDialog;
+use std::fs;
+
+/// 演练计划窗口，演练模式开启时显示
+#[derive(Default)]
+pub struct PlanWindow {
+    status: Option<String>,
+}
+
+impl PlanWindow {
+    pub fn show_plan_window(&mut self, ctx: &egui::Context) {
+        if !dry_run::is_enabled() {
+            return;
+        }
+        let actions = dry_run::actions();
+        let summary = dry_run::summarize(&actions);
+        egui::Window::new("演练计划").default_width(600.0).show(ctx, |ui| {
+            ui.label("演练模式已开启，删除、规则清理与移动只会记录在这里，不会改动磁盘");
+            ui.label(format!(
+                "删除 {} 个文件，释放 {}；移动 {} 个文件（{}）；创建 {} 个链接",
+                summary.files_removed,
+                utils::format_size(summary.bytes_reclaimed),
+                summary.files_moved,
+                utils::format_size(summary.bytes_moved),
+                summary.links_created
+            ));
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!actions.is_empty(), egui::Button::new("导出 JSON")).clicked() {
+                    self.export(&actions);
+                }
+                if ui.button("清空").clicked() {
+                    dry_run::clear();
+                    self.status = None;
+                }
+            });
+            if let Some(status) = &self.status {
+                ui.label(status);
+            }
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                Grid::new("dry_run_actions").striped(true).show(ui, |ui| {
+                    for action in &actions {
+                        ui.label(&action.operation);
+                        match &action.target {
+                            Some(target) => ui.label(format!("{} -> {}", action.path, target)),
+                            None => ui.label(&action.path),
+                        };
+                        ui.label(format!("{} 个文件", action.files));
+                        ui.label(utils::format_size(action.bytes));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    }
+
+    fn export(&mut self, actions: &[PlannedAction]) {
+        let dialog = FileDialog::new()
+            .set_filename("dry_run_plan.json")
+            .add_filter("JSON", &["json"]);
+        let Ok(Some(path)) = dialog.show_save_single_file() else {
+            return;
+        };
+        let result = dry_run::to_json(actions)
+            .and_then(|json| fs::write(&path, json).map_err(|e| format!("写入文件失败: {}", e)));
+        self.status = Some(match result {
+            Ok(()) => format!("已导出到 {}", path.display()),
+            Err(e) => e,
+        });
+    }
+}
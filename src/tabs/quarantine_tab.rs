@@ -0,0 +1,219 @@
+use crate::database::{get_default_db_path, Database, QuarantineEntry};
+use crate::logger::{self, LogContext};
+use crate::quarantine;
+use crate::tabs::clear_tab::ClearTabState;
+use crate::{confirmation, dry_run, open, utils};
+use chrono::Utc;
+use eframe::egui::{self, Grid, ScrollArea};
+use std::path::Path;
+
+// 隔离条目上点击的操作
+enum EntryAction {
+    Restore(QuarantineEntry),
+    Purge(QuarantineEntry),
+}
+
+/// 隔离区：还原或彻底删除隔离的文件夹，设置自动清除的天数
+#[derive(Default)]
+pub struct QuarantineTabState {
+    pub entries: Vec<QuarantineEntry>,
+    pub expiry_days: u32,
+    pub status: Option<String>,
+    confirm_purge: Option<QuarantineEntry>,
+    loaded: bool,
+}
+
+impl QuarantineTabState {
+    pub fn show(&mut self, ui: &mut egui::Ui, clear_tab: &mut ClearTabState) {
+        if !self.loaded {
+            self.reload();
+        }
+        self.handle_purge_confirmation(ui.ctx());
+
+        ui.label("隔离的文件夹保存在程序目录的 quarantine 文件夹中，到期前可以还原到原位置");
+        ui.horizontal(|ui| {
+            ui.label("保留天数:");
+            ui.add(egui::DragValue::new(&mut self.expiry_days).range(0..=3650));
+            ui.label("（0 表示不自动清除）");
+            if ui.button("保存").clicked() {
+                self.save_expiry_days();
+            }
+            if ui.button("立即清除过期项").clicked() {
+                self.purge_expired();
+            }
+            if ui.button("刷新").clicked() {
+                self.reload();
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+
+        let total: u64 = self.entries.iter().map(|entry| entry.size).sum();
+        ui.label(format!("共 {} 项，{}", self.entries.len(), utils::format_size(total)));
+
+        let now = Utc::now();
+        let mut clicked = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("quarantine_entries").striped(true).show(ui, |ui| {
+                ui.label("原位置");
+                ui.label("大小");
+                ui.label("隔离时间");
+                ui.label("到期");
+                ui.label("操作");
+                ui.end_row();
+                for entry in &self.entries {
+                    ui.label(&entry.original_path);
+                    ui.label(utils::format_size(entry.size));
+                    ui.label(
+                        entry
+                            .quarantined_at
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string(),
+                    );
+                    ui.label(format_expiry(entry, self.expiry_days, now));
+                    ui.horizontal(|ui| {
+                        if ui.button("还原").clicked() {
+                            clicked = Some(EntryAction::Restore(entry.clone()));
+                        }
+                        if ui.button("彻底删除").clicked() {
+                            clicked = Some(EntryAction::Purge(entry.clone()));
+                        }
+                        if ui.button("打开").clicked() {
+                            if let Err(err) = open::open_folder(Path::new(&entry.stored_path)) {
+                                logger::log_error(&format!("无法打开文件夹: {}", err));
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+
+        match clicked {
+            Some(EntryAction::Restore(entry)) => self.restore(&entry, clear_tab),
+            Some(EntryAction::Purge(entry)) => {
+                self.confirm_purge = Some(entry);
+                self.status = None;
+            }
+            None => {}
+        }
+    }
+
+    fn reload(&mut self) {
+        self.loaded = true;
+        match Database::new(&get_default_db_path()) {
+            Ok(db) => {
+                self.expiry_days = quarantine::expiry_days(&db);
+                match db.list_quarantine_entries() {
+                    Ok(entries) => self.entries = entries,
+                    Err(e) => self.status = Some(format!("读取隔离区失败: {}", e)),
+                }
+            }
+            Err(e) => self.status = Some(format!("无法打开数据库: {}", e)),
+        }
+    }
+
+    fn save_expiry_days(&mut self) {
+        let result = Database::new(&get_default_db_path())
+            .map_err(|e| format!("无法打开数据库: {}", e))
+            .and_then(|db| quarantine::set_expiry_days(&db, self.expiry_days));
+        self.status = Some(match result {
+            Ok(()) if self.expiry_days == 0 => "已关闭自动清除".to_string(),
+            Ok(()) => format!("隔离的文件夹将在 {} 天后自动清除", self.expiry_days),
+            Err(e) => e,
+        });
+    }
+
+    fn purge_expired(&mut self) {
+        let status = match Database::new(&get_default_db_path()) {
+            Ok(db) => {
+                let (purged, freed, errors) = quarantine::purge_expired(&db, self.expiry_days, Utc::now());
+                if dry_run::is_enabled() {
+                    format!("演练：{} 项过期条目已记录到演练计划", purged)
+                } else {
+                    format!(
+                        "已清除 {} 项过期条目，释放 {}，{} 项失败",
+                        purged,
+                        utils::format_size(freed),
+                        errors.len()
+                    )
+                }
+            }
+            Err(e) => format!("无法打开数据库: {}", e),
+        };
+        self.reload();
+        self.status = Some(status);
+    }
+
+    // 还原到原位置；位于主页当前根目录时同步加入主页列表
+    fn restore(&mut self, entry: &QuarantineEntry, clear_tab: &mut ClearTabState) {
+        let ctx = LogContext::new("隔离区")
+            .with_target_type(entry.profile.clone())
+            .with_target_name(entry.original_path.clone());
+        let result = Database::new(&get_default_db_path())
+            .map_err(|e| format!("无法打开数据库: {}", e))
+            .and_then(|db| quarantine::restore(&db, entry));
+        match result {
+            Ok(()) if dry_run::is_enabled() => {
+                self.status = Some(format!("演练：还原到 {} 已记录到演练计划", entry.original_path));
+            }
+            Ok(()) => {
+                logger::log_structured_info(&ctx, "已还原");
+                let original = Path::new(&entry.original_path);
+                if original.parent() == Some(clear_tab.selected_root.path.as_path()) {
+                    if let Some(name) = original.file_name() {
+                        let name = name.to_string_lossy().to_string();
+                        if !clear_tab.folder_data.iter().any(|(folder, _)| *folder == name) {
+                            clear_tab.folder_data.push((name, entry.size));
+                        }
+                    }
+                }
+                self.status = Some(format!("已还原到 {}", entry.original_path));
+            }
+            Err(e) => {
+                logger::log_structured_error(&ctx, &e);
+                self.status = Some(e);
+            }
+        }
+        self.reload();
+    }
+
+    fn handle_purge_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(entry) = self.confirm_purge.clone() else {
+            return;
+        };
+        let message = format!("确定要彻底删除隔离的 {} 吗？此操作无法撤销！", entry.original_path);
+        match confirmation::show_confirmation(ctx, &message, &self.status) {
+            Some(true) => {
+                let result = Database::new(&get_default_db_path())
+                    .map_err(|e| format!("无法打开数据库: {}", e))
+                    .and_then(|db| quarantine::purge(&db, &entry));
+                self.confirm_purge = None;
+                self.reload();
+                self.status = Some(match result {
+                    Ok(()) if dry_run::is_enabled() => "演练：已记录到演练计划".to_string(),
+                    Ok(()) => format!("已彻底删除，释放 {}", utils::format_size(entry.size)),
+                    Err(e) => e,
+                });
+            }
+            Some(false) => self.confirm_purge = None,
+            None => {}
+        }
+    }
+}
+
+fn format_expiry(entry: &QuarantineEntry, days: u32, now: chrono::DateTime<Utc>) -> String {
+    if days == 0 {
+        return "不自动清除".to_string();
+    }
+    if quarantine::is_expired(entry, days, now) {
+        return "已过期".to_string();
+    }
+    let remaining = entry.quarantined_at + chrono::Duration::days(days as i64) - now;
+    match remaining.num_days() {
+        0 => "今天".to_string(),
+        left => format!("{} 天后", left),
+    }
+}
@@ -0,0 +1,63 @@
+use crate::database::{get_default_db_path, Database, DeleteReportRecord};
+use crate::{logger, utils};
+use chrono::Local;
+use eframe::egui::{self, ScrollArea};
+
+/// 删除报告窗口：最近的删除结果及其中失败的路径
+#[derive(Default)]
+pub struct ReportWindow {
+    pub show_window: bool,
+    reports: Vec<DeleteReportRecord>,
+}
+
+impl ReportWindow {
+    /// 从数据库读取最近的报告并打开窗口
+    pub fn open(&mut self) {
+        self.show_window = true;
+        self.reports = Database::new(&get_default_db_path())
+            .and_then(|db| db.list_delete_reports(100))
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取删除报告失败: {}", e));
+                Vec::new()
+            });
+    }
+
+    pub fn show_report_window(&mut self, ctx: &egui::Context) {
+        if !self.show_window {
+            return;
+        }
+        let mut open = self.show_window;
+        egui::Window::new("删除报告")
+            .open(&mut open)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                if self.reports.is_empty() {
+                    ui.label("暂无删除记录");
+                }
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (index, report) in self.reports.iter().enumerate() {
+                        let title = format!(
+                            "{} {} {}  释放 {}，{} 个文件",
+                            report.deleted_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                            report.operation,
+                            report.path,
+                            utils::format_size(report.bytes_freed),
+                            report.files_removed
+                        );
+                        if report.failures.is_empty() {
+                            ui.label(title);
+                            continue;
+                        }
+                        egui::CollapsingHeader::new(format!("{}，{} 项失败", title, report.failures.len()))
+                            .id_salt(("delete_report", index))
+                            .show(ui, |ui| {
+                                for (path, reason) in &report.failures {
+                                    ui.label(format!("{} - {}", path, reason));
+                                }
+                            });
+                    }
+                });
+            });
+        self.show_window = open;
+    }
+}
@@ -0,0 +1,345 @@
+use crate::cleanerml::{self, ImportReport, UnsupportedAction};
+use crate::delete::{self, DeleteMode, DeleteReport, DeleteWorker};
+use crate::dry_run;
+use crate::roots::{self, ScanRoot};
+use crate::rules::{self, CleanTarget, CleaningRule, RuleFile, SafetyLevel};
+use crate::tabs::clear_tab::{self, ClearTabState};
+use crate::{confirmation, logger, utils};
+use eframe::egui::{self, Grid, ScrollArea};
+use native_dialog::FileDialog;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+pub struct RulesTabState {
+    pub rules: Vec<CleaningRule>,
+    pub max_safety: SafetyLevel,
+    pub mode: DeleteMode, // 清理方式，默认移到回收站
+
+    // 预览结果及每项是否勾选
+    pub targets: Vec<(CleanTarget, bool)>,
+    pub status: Option<String>,
+    // 最近一次导入 CleanerML 时无法转换的动作
+    pub unsupported: Vec<UnsupportedAction>,
+    rx: Option<Receiver<Vec<CleanTarget>>>,
+    // 等待确认的清理方式，点击按钮时确定
+    confirm_clean: Option<DeleteMode>,
+    // 后台清理任务及已处理条目的结果
+    delete_worker: Option<DeleteWorker>,
+    delete_results: Vec<Result<DeleteReport, String>>,
+    loaded: bool,
+}
+
+impl Default for RulesTabState {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_safety: SafetyLevel::Safe,
+            mode: DeleteMode::Trash,
+            targets: Vec::new(),
+            status: None,
+            unsupported: Vec::new(),
+            rx: None,
+            confirm_clean: None,
+            delete_worker: None,
+            delete_results: Vec::new(),
+            loaded: false,
+        }
+    }
+}
+
+impl RulesTabState {
+    pub fn show(&mut self, ui: &mut egui::Ui, clear_tab: &mut ClearTabState) {
+        if !self.loaded {
+            self.reload_rules();
+        }
+        self.poll(ui.ctx());
+        self.poll_delete_worker(ui.ctx(), clear_tab);
+        self.handle_confirmation(ui.ctx());
+
+        // 清理期间不重新预览，以免替换正在清理的条目
+        let is_running = self.rx.is_some() || self.delete_worker.is_some();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} 中共 {} 条规则", rules::RULES_FILE, self.rules.len()));
+            if ui.button("重新加载规则").clicked() {
+                self.reload_rules();
+            }
+            if ui.button("导入 BleachBit CleanerML...").clicked() {
+                let dialog = FileDialog::new().add_filter("CleanerML", &["xml"]);
+                if let Ok(files) = dialog.show_open_multiple_file() {
+                    self.import_cleanerml(&files);
+                }
+            }
+        });
+        if !self.unsupported.is_empty() {
+            egui::CollapsingHeader::new(format!("无法导入的动作 ({})", self.unsupported.len()))
+                .id_salt("cleanerml_unsupported")
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .id_salt("cleanerml_unsupported_list")
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for action in &self.unsupported {
+                                ui.label(format!(
+                                    "{}/{} [{}] {} - {}",
+                                    action.cleaner, action.option, action.command, action.path, action.reason
+                                ));
+                            }
+                        });
+                });
+        }
+        ui.horizontal(|ui| {
+            ui.label("最高安全等级:");
+            egui::ComboBox::from_id_salt("rules_max_safety")
+                .selected_text(self.max_safety.label())
+                .show_ui(ui, |ui| {
+                    for level in SafetyLevel::ALL {
+                        ui.selectable_value(&mut self.max_safety, level, level.label());
+                    }
+                });
+            if ui.add_enabled(!is_running, egui::Button::new("预览")).clicked() {
+                self.start_plan(&clear_tab.selected_root);
+            }
+            if is_running {
+                ui.spinner();
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        if let Some(worker) = &self.delete_worker {
+            clear_tab::show_delete_progress(ui, worker);
+        }
+        if self.targets.is_empty() {
+            return;
+        }
+
+        let selected: Vec<&CleanTarget> = self
+            .targets
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(target, _)| target)
+            .collect();
+        let selected_size: u64 = selected.iter().map(|target| target.size).sum();
+        let selected_count = selected.len();
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "已选 {} 项，可释放 {}",
+                selected_count,
+                utils::format_size(selected_size)
+            ));
+            egui::ComboBox::from_id_salt("rules_delete_mode")
+                .selected_text(self.mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in DeleteMode::ALL {
+                        ui.selectable_value(&mut self.mode, mode, mode.label());
+                    }
+                });
+            let can_clean = selected_count > 0 && !is_running;
+            if ui.add_enabled(can_clean, egui::Button::new("按规则清理")).clicked() {
+                self.confirm_clean = Some(self.mode);
+            }
+        });
+
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("rule_targets").striped(true).show(ui, |ui| {
+                ui.label("");
+                ui.label("应用");
+                ui.label("安全等级");
+                ui.label("根目录");
+                ui.label("路径");
+                ui.label("大小");
+                ui.end_row();
+                for (target, checked) in &mut self.targets {
+                    ui.checkbox(checked, "");
+                    ui.label(&target.app);
+                    ui.label(target.safety.label());
+                    ui.label(&target.root_label);
+                    let kind = if target.is_dir { "📁" } else { "📄" };
+                    ui.label(format!("{} {}", kind, target.path.display()));
+                    ui.label(utils::format_size(target.size));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn reload_rules(&mut self) {
+        self.loaded = true;
+        match RuleFile::load_from_yaml(rules::RULES_FILE) {
+            Ok(file) => self.rules = file.rules,
+            Err(e) => {
+                self.rules.clear();
+                self.status = Some(e);
+            }
+        }
+    }
+
+    // 转换选中的 CleanerML 文件，新规则追加到规则文件中
+    fn import_cleanerml(&mut self, files: &[PathBuf]) {
+        // 以磁盘上的规则为准；规则文件存在但无法解析时不覆盖
+        match RuleFile::load_from_yaml(rules::RULES_FILE) {
+            Ok(file) => self.rules = file.rules,
+            Err(_) if !Path::new(rules::RULES_FILE).exists() => self.rules.clear(),
+            Err(e) => {
+                self.status = Some(format!("{}，请先修正规则文件再导入", e));
+                return;
+            }
+        }
+
+        let mut report = ImportReport::default();
+        let mut errors = Vec::new();
+        for file in files {
+            let result = fs::read_to_string(file)
+                .map_err(|e| e.to_string())
+                .and_then(|xml| cleanerml::parse_cleanerml(&xml));
+            match result {
+                Ok(file_report) => report.merge(file_report),
+                Err(e) => errors.push(format!("{}: {}", file.display(), e)),
+            }
+        }
+
+        let mut added = 0;
+        for rule in report.rules {
+            if !self.rules.contains(&rule) {
+                self.rules.push(rule);
+                added += 1;
+            }
+        }
+        let file = RuleFile {
+            rules: self.rules.clone(),
+        };
+        if let Err(e) = file.save_to_yaml(rules::RULES_FILE) {
+            errors.push(e);
+        }
+        for error in &errors {
+            logger::log_error(&format!("导入 CleanerML 失败: {}", error));
+        }
+        logger::log_info(&format!(
+            "从 {} 个 CleanerML 文件导入 {} 条规则，{} 个动作无法导入",
+            files.len(),
+            added,
+            report.unsupported.len()
+        ));
+        self.status = Some(format!(
+            "导入 {} 条规则，{} 个动作无法导入，{} 个文件失败",
+            added,
+            report.unsupported.len(),
+            errors.len()
+        ));
+        self.unsupported = report.unsupported;
+    }
+
+    // 在当前用户的所有根目录中匹配规则
+    fn start_plan(&mut self, current_root: &ScanRoot) {
+        let roots = roots::sibling_roots(current_root);
+        let rules = self.rules.clone();
+        let max_safety = self.max_safety;
+        self.targets.clear();
+        self.status = Some("正在匹配规则...".to_string());
+
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        thread::spawn(move || {
+            let _ = tx.send(rules::plan(&rules, &roots, max_safety));
+        });
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(targets) => {
+                let total: u64 = targets.iter().map(|target| target.size).sum();
+                self.status = Some(format!(
+                    "匹配到 {} 项，共 {}",
+                    targets.len(),
+                    utils::format_size(total)
+                ));
+                self.targets = targets.into_iter().map(|target| (target, true)).collect();
+                self.rx = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(200)),
+            Err(mpsc::TryRecvError::Disconnected) => self.rx = None,
+        }
+    }
+
+    fn handle_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(mode) = self.confirm_clean else {
+            return;
+        };
+        let selected: Vec<CleanTarget> = self
+            .targets
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(target, _)| target.clone())
+            .collect();
+        let message = mode.confirm_message(&format!("按规则匹配到的 {} 项（应用文件夹本身会保留）", selected.len()));
+        match confirmation::confirm_deletion(ctx, mode, &message, &self.status) {
+            Some(true) => {
+                // 在后台逐项清理；演练开关在确认时读取，清理过程中切换不影响本次任务
+                let jobs = rules::delete_jobs(&selected);
+                self.delete_worker = Some(DeleteWorker::start(jobs, mode, dry_run::is_enabled()));
+                self.status = Some(format!("正在按规则{} {} 项...", mode.label(), selected.len()));
+                self.confirm_clean = None;
+            }
+            Some(false) => self.confirm_clean = None,
+            None => {}
+        }
+    }
+
+    // 每项清理完毕后更新统计，完全删除的条目移出列表；全部完成后汇总
+    fn poll_delete_worker(&mut self, ctx: &egui::Context, clear_tab: &mut ClearTabState) {
+        let Some(worker) = &mut self.delete_worker else {
+            return;
+        };
+        let (finished, done) = worker.poll();
+        let (mode, total) = (worker.mode, worker.total_folders);
+        for (path, result) in finished {
+            match &result {
+                Ok(report) => {
+                    delete::record_stats(report, &mut clear_tab.stats, &clear_tab.stats_logger);
+                    // 目录中有文件删除失败时保留该条目
+                    if report.is_complete() && !report.planned {
+                        self.targets.retain(|(target, _)| target.path.to_string_lossy() != path);
+                    }
+                }
+                Err(err) => logger::log_error(&format!("按规则清理 {} 失败: {}", path, err)),
+            }
+            self.delete_results.push(result);
+        }
+        if !done {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+
+        self.delete_worker = None;
+        let results = std::mem::take(&mut self.delete_results);
+        let reports: Vec<&DeleteReport> = results.iter().filter_map(|result| result.as_ref().ok()).collect();
+        if reports.iter().any(|report| report.planned) {
+            self.status = Some(format!("演练：{} 项已记录到演练计划", reports.len()));
+            return;
+        }
+        let freed: u64 = reports.iter().map(|report| report.bytes_freed).sum();
+        logger::log_info(&format!("按规则{}释放 {}", mode.label(), utils::format_size(freed)));
+        // 取消不算失败：被取消的条目既不计入已清理也不计入失败
+        let failed = results.len() - reports.len() + reports.iter().filter(|report| !report.failures.is_empty()).count();
+        let mut status = if failed == 0 {
+            format!("已清理，释放 {}", utils::format_size(freed))
+        } else {
+            clear_tab.report_window.open();
+            format!(
+                "已清理，释放 {}，{} 项失败，详见删除报告",
+                utils::format_size(freed),
+                failed
+            )
+        };
+        if reports.iter().any(|report| report.cancelled) || results.len() < total {
+            status.push_str(&format!("，已取消，{} 项未处理", total - results.len()));
+        }
+        self.status = Some(status);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::database::{get_default_db_path, Database};
+use crate::delete::{DeleteJob, DeleteMode, DeleteReport, DeleteWorker};
+use crate::dry_run;
+use crate::logger::{self, LogContext};
+use crate::roots::{self, ScanRoot};
+use crate::search::{self, SearchHit, SearchMessage};
+use crate::tabs::clear_tab::{self, ClearTabState};
+use crate::yaml_loader::load_folder_descriptions;
+use crate::{confirmation, delete, open, utils};
+use eframe::egui::{self, Grid, ScrollArea};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+pub struct SearchTabState {
+    pub query: String,
+    pub max_depth: usize,
+
+    // 搜索结果与勾选的路径
+    pub hits: Vec<SearchHit>,
+    pub selected: HashSet<PathBuf>,
+    pub status: Option<String>,
+    rx: Option<Receiver<SearchMessage>>,
+    confirm_delete: Option<DeleteMode>,
+
+    // 后台删除任务，以及正在删除的搜索结果
+    delete_worker: Option<DeleteWorker>,
+    deleting: Vec<SearchHit>,
+    delete_results: Vec<Result<DeleteReport, String>>,
+}
+
+impl Default for SearchTabState {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            max_depth: 3,
+            hits: Vec::new(),
+            selected: HashSet::new(),
+            status: None,
+            rx: None,
+            confirm_delete: None,
+            delete_worker: None,
+            deleting: Vec::new(),
+            delete_results: Vec::new(),
+        }
+    }
+}
+
+impl SearchTabState {
+    pub fn show(&mut self, ui: &mut egui::Ui, clear_tab: &mut ClearTabState) {
+        self.poll(ui.ctx());
+        self.poll_delete_worker(ui.ctx(), clear_tab);
+        self.handle_delete_confirmation(ui.ctx());
+
+        // 删除期间不重新搜索，以免替换正在删除的结果
+        let is_running = self.rx.is_some() || self.delete_worker.is_some();
+        ui.horizontal(|ui| {
+            ui.label("应用名称:");
+            let response = ui.text_edit_singleline(&mut self.query);
+            let entered = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            ui.label("查找深度:");
+            ui.add(egui::DragValue::new(&mut self.max_depth).range(1..=8));
+            let can_search = !is_running && !self.query.trim().is_empty();
+            if (ui.add_enabled(can_search, egui::Button::new("搜索")).clicked() || entered) && can_search {
+                self.start_search(&clear_tab.selected_root);
+            }
+            if is_running {
+                ui.spinner();
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        if let Some(worker) = &self.delete_worker {
+            clear_tab::show_delete_progress(ui, worker);
+        }
+        if self.hits.is_empty() {
+            return;
+        }
+
+        let selected_size: u64 = self
+            .hits
+            .iter()
+            .filter(|hit| self.selected.contains(&hit.path))
+            .map(|hit| hit.size.apparent)
+            .sum();
+        ui.horizontal(|ui| {
+            if ui.button("全选").clicked() {
+                self.selected = self.hits.iter().map(|hit| hit.path.clone()).collect();
+            }
+            if ui.button("取消全选").clicked() {
+                self.selected.clear();
+            }
+            let can_delete = !is_running && !self.selected.is_empty();
+            for mode in DeleteMode::ALL {
+                let label = format!("批量{}", mode.action_label());
+                let button = ui.add_enabled(can_delete, egui::Button::new(label));
+                if button.on_hover_text(mode.label()).clicked() {
+                    self.confirm_delete = Some(mode);
+                }
+            }
+            ui.label(format!(
+                "已选 {} 个，共 {}",
+                self.selected.len(),
+                utils::format_size(selected_size)
+            ));
+        });
+
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("search_results").striped(true).show(ui, |ui| {
+                ui.label("");
+                ui.label("路径");
+                ui.label("根目录");
+                ui.label("大小");
+                ui.label("最近使用");
+                ui.label("匹配");
+                ui.label("操作");
+                ui.end_row();
+
+                for hit in &self.hits {
+                    let mut checked = self.selected.contains(&hit.path);
+                    if ui.checkbox(&mut checked, "").changed() {
+                        if checked {
+                            self.selected.insert(hit.path.clone());
+                        } else {
+                            self.selected.remove(&hit.path);
+                        }
+                    }
+                    ui.label(&hit.relative).on_hover_text(hit.path.display().to_string());
+                    ui.label(&hit.root.label);
+                    ui.label(utils::format_size(hit.size.apparent));
+                    ui.label(utils::format_age(hit.size.last_used()));
+                    ui.label(hit.matched.label());
+                    if ui.button("打开").clicked() {
+                        if let Err(err) = open::open_folder(&hit.path) {
+                            logger::log_error(&format!("无法打开文件夹: {}", err));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    // 在当前用户的所有根目录中搜索
+    fn start_search(&mut self, current_root: &ScanRoot) {
+        let roots = roots::sibling_roots(current_root);
+        let query = self.query.trim().to_string();
+        let max_depth = self.max_depth;
+        self.hits.clear();
+        self.selected.clear();
+        self.status = Some(format!("正在搜索 \"{}\"...", query));
+
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        thread::spawn(move || {
+            let mut yaml_error_logged = false;
+            let descriptions = load_folder_descriptions("folders_description.yaml", &mut yaml_error_logged);
+            let hits = search::search(&roots, &query, max_depth, descriptions.as_ref(), Some(&tx));
+            let _ = tx.send(SearchMessage::Finished(hits));
+        });
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        for message in rx.try_iter() {
+            match message {
+                SearchMessage::Progress(status) => self.status = Some(status),
+                SearchMessage::Finished(hits) => {
+                    let total: u64 = hits.iter().map(|hit| hit.size.apparent).sum();
+                    self.status = Some(format!(
+                        "找到 {} 个文件夹，共 {}",
+                        hits.len(),
+                        utils::format_size(total)
+                    ));
+                    self.hits = hits;
+                    self.rx = None;
+                    return;
+                }
+            }
+        }
+        ctx.request_repaint_after(Duration::from_millis(200));
+    }
+
+    fn handle_delete_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(mode) = self.confirm_delete else {
+            return;
+        };
+        let message = mode.confirm_message(&format!("选中的 {} 个文件夹", self.selected.len()));
+        match confirmation::confirm_deletion(ctx, mode, &message, &self.status) {
+            Some(true) => {
+                self.start_delete(mode);
+                self.confirm_delete = None;
+            }
+            Some(false) => self.confirm_delete = None,
+            None => {}
+        }
+    }
+
+    // 在后台删除勾选的文件夹；演练开关在确认时读取，删除过程中切换不影响本次任务
+    fn start_delete(&mut self, mode: DeleteMode) {
+        self.deleting = self
+            .hits
+            .iter()
+            .filter(|hit| self.selected.contains(&hit.path))
+            .cloned()
+            .collect();
+        let jobs = self
+            .deleting
+            .iter()
+            .map(|hit| DeleteJob {
+                folder: hit.path.to_string_lossy().to_string(),
+                path: hit.path.clone(),
+                profile: hit.root.profile.clone(),
+                size: hit.size.apparent,
+            })
+            .collect();
+        self.delete_worker = Some(DeleteWorker::start(jobs, mode, dry_run::is_enabled()));
+        self.status = Some(format!("正在{} {} 个文件夹...", mode.label(), self.deleting.len()));
+    }
+
+    // 每个文件夹删除完毕后移出搜索结果并更新统计，顶层文件夹同时从数据库缓存与主页列表中移除
+    fn poll_delete_worker(&mut self, ctx: &egui::Context, clear_tab: &mut ClearTabState) {
+        let Some(worker) = &mut self.delete_worker else {
+            return;
+        };
+        let (finished, done) = worker.poll();
+        let log_ctx = LogContext::new("删除").with_target_name(format!("搜索: {}", self.query));
+        let mut db = None;
+        for (folder, result) in finished {
+            let Some(hit) = self.deleting.iter().find(|hit| hit.path.to_string_lossy() == folder) else {
+                continue;
+            };
+            let report = match &result {
+                Ok(report) => report,
+                Err(err) => {
+                    logger::log_structured_error(&log_ctx, &format!("批量删除失败: {}", err));
+                    self.delete_results.push(result);
+                    continue;
+                }
+            };
+            if hit.relative.contains('/') {
+                delete::record_stats(report, &mut clear_tab.stats, &clear_tab.stats_logger);
+            } else {
+                clear_tab.apply_folder_report(&hit.root.id, &hit.relative, report);
+            }
+            if report.is_complete() && !report.planned {
+                logger::log_structured_info(&log_ctx, &format!("已删除文件夹: {}", folder));
+                if !hit.relative.contains('/') {
+                    match db.get_or_insert_with(|| Database::new(&get_default_db_path())) {
+                        Ok(db) => {
+                            if let Err(e) = db.remove_folder(&hit.root.id, &hit.relative) {
+                                logger::log_structured_error(&log_ctx, &format!("更新数据库失败: {}", e));
+                            }
+                        }
+                        Err(e) => logger::log_structured_error(&log_ctx, &format!("无法打开数据库: {}", e)),
+                    }
+                }
+                self.selected.remove(&hit.path);
+                let path = hit.path.clone();
+                self.hits.retain(|hit| hit.path != path);
+            }
+            self.delete_results.push(result);
+        }
+        if !done {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+
+        self.delete_worker = None;
+        let total = std::mem::take(&mut self.deleting).len();
+        let results = std::mem::take(&mut self.delete_results);
+        let reports: Vec<&DeleteReport> = results.iter().filter_map(|result| result.as_ref().ok()).collect();
+        if reports.iter().any(|report| report.planned) {
+            // 只记录到演练计划，保留搜索结果
+            self.status = Some(format!("演练：{} 个文件夹已记录到演练计划", reports.len()));
+            return;
+        }
+        let deleted = reports.iter().filter(|report| report.is_complete()).count();
+        let freed: u64 = reports.iter().map(|report| report.bytes_freed).sum();
+        // 取消不算失败：被取消的文件夹既不计入已删除也不计入失败
+        let failed = results.len() - reports.len() + reports.iter().filter(|report| !report.failures.is_empty()).count();
+        let mut status = if failed == 0 {
+            format!("已删除 {} 个文件夹，释放 {}", deleted, utils::format_size(freed))
+        } else {
+            clear_tab.report_window.open();
+            format!(
+                "已删除 {} 个文件夹，释放 {}，{} 个失败，详见删除报告",
+                deleted,
+                utils::format_size(freed),
+                failed
+            )
+        };
+        if reports.iter().any(|report| report.cancelled) || results.len() < total {
+            status.push_str(&format!("，已取消，{} 个未处理", total - results.len()));
+        }
+        self.status = Some(status);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::database::{get_default_db_path, Database, ScanSession};
+use crate::logger;
+use crate::roots::ScanRoot;
+use crate::snapshot::{self, DiffKind, FolderDiff};
+use crate::utils;
+use eframe::egui::{self, Grid, ScrollArea};
+
+/// 快照对比窗口
+#[derive(Default)]
+pub struct SnapshotWindow {
+    pub show_window: bool,
+    root_label: String,
+    folder_type: String,
+    sessions: Vec<ScanSession>,
+    old_id: Option<i64>,
+    new_id: Option<i64>,
+    diffs: Vec<FolderDiff>,
+}
+
+impl SnapshotWindow {
+    /// 打开某个根目录的快照列表，默认对比最近两次扫描
+    pub fn open(&mut self, root: &ScanRoot) {
+        self.show_window = true;
+        self.root_label = root.label.clone();
+        self.folder_type = root.id.clone();
+        self.reload();
+    }
+
+    fn reload(&mut self) {
+        self.sessions = Database::new(&get_default_db_path())
+            .and_then(|db| db.list_snapshots(&self.folder_type))
+            .unwrap_or_else(|e| {
+                logger::log_error(&format!("读取扫描快照失败: {}", e));
+                Vec::new()
+            });
+        self.new_id = self.sessions.first().map(|session| session.id);
+        self.old_id = self.sessions.get(1).map(|session| session.id);
+        self.compare();
+    }
+
+    fn compare(&mut self) {
+        self.diffs.clear();
+        let (Some(old_id), Some(new_id)) = (self.old_id, self.new_id) else {
+            return;
+        };
+        let result = Database::new(&get_default_db_path()).and_then(|db| {
+            Ok((db.get_snapshot_folders(old_id)?, db.get_snapshot_folders(new_id)?))
+        });
+        match result {
+            Ok((old, new)) => self.diffs = snapshot::diff_folders(&old, &new),
+            Err(e) => logger::log_error(&format!("读取快照内容失败: {}", e)),
+        }
+    }
+
+    fn session_label(&self, id: Option<i64>) -> String {
+        self.sessions
+            .iter()
+            .find(|session| Some(session.id) == id)
+            .map(|session| {
+                format!(
+                    "#{} {}（{} 个文件夹，{}）",
+                    session.id,
+                    session.scanned_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                    session.folder_count,
+                    utils::format_size(session.total_size)
+                )
+            })
+            .unwrap_or_else(|| "选择快照".to_string())
+    }
+
+    pub fn show_snapshot_window(&mut self, ctx: &egui::Context) {
+        if !self.show_window {
+            return;
+        }
+        let mut open = self.show_window;
+        egui::Window::new(format!("扫描快照对比: {}", self.root_label))
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                if self.sessions.len() < 2 {
+                    ui.label("至少需要两次扫描才能对比，请先再次扫描");
+                    return;
+                }
+
+                let mut changed = false;
+                let mut deleted = None;
+                for (label, side) in [("之前", 0), ("之后", 1)] {
+                    let current = if side == 0 { self.old_id } else { self.new_id };
+                    let mut choice = current;
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        egui::ComboBox::from_id_salt(("snapshot_choice", side))
+                            .width(380.0)
+                            .selected_text(self.session_label(current))
+                            .show_ui(ui, |ui| {
+                                for session in &self.sessions {
+                                    let text = self.session_label(Some(session.id));
+                                    ui.selectable_value(&mut choice, Some(session.id), text);
+                                }
+                            });
+                        if ui.small_button("删除").clicked() {
+                            deleted = current;
+                        }
+                    });
+                    if choice != current {
+                        changed = true;
+                        if side == 0 {
+                            self.old_id = choice;
+                        } else {
+                            self.new_id = choice;
+                        }
+                    }
+                }
+                if let Some(id) = deleted {
+                    match Database::new(&get_default_db_path()).and_then(|db| db.delete_snapshot(id)) {
+                        Ok(()) => logger::log_info(&format!("已删除扫描快照 #{}", id)),
+                        Err(e) => logger::log_error(&format!("删除扫描快照失败: {}", e)),
+                    }
+                    self.reload();
+                } else if changed {
+                    self.compare();
+                }
+
+                ui.separator();
+                let count = |kind: DiffKind| self.diffs.iter().filter(|diff| diff.kind == kind).count();
+                let total: i64 = self.diffs.iter().map(FolderDiff::delta).sum();
+                ui.label(format!(
+                    "新增 {}，移除 {}，增长 {}，缩小 {}，合计 {}",
+                    count(DiffKind::Added),
+                    count(DiffKind::Removed),
+                    count(DiffKind::Grown),
+                    count(DiffKind::Shrunk),
+                    snapshot::format_delta(total)
+                ));
+
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    Grid::new("snapshot_diff").striped(true).show(ui, |ui| {
+                        ui.label("文件夹");
+                        ui.label("变化");
+                        ui.label("之前");
+                        ui.label("之后");
+                        ui.label("差值");
+                        ui.end_row();
+                        for diff in &self.diffs {
+                            ui.label(&diff.name);
+                            ui.label(diff.kind.label());
+                            ui.label(utils::format_size(diff.old_size));
+                            ui.label(utils::format_size(diff.new_size));
+                            ui.label(snapshot::format_delta(diff.delta()));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show_window = open;
+    }
+}
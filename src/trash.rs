@@ -0,0 +1,268 @@
+//! 按 freedesktop.org 回收站规范把文件移到回收站
+//!
+//! 与家目录在同一分区的文件移到 `$XDG_DATA_HOME/Trash`；其他分区使用
+//! `$topdir/.Trash/$uid`（需带粘滞位），否则使用 `$topdir/.Trash-$uid`。
+//! 每个文件在 `info` 中有一个 `.trashinfo`，记录原路径与删除时间，
+//! 文件管理器据此还原。Windows 上交给系统移到回收站
+
+use crate::roots::{RootKind, ScanRoot};
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 回收站中不编码的字符，其余字节按 RFC 2396 编码
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-_.!~*'()/".contains(&byte)
+}
+
+/// 对路径做百分号编码，用于 `.trashinfo` 的 `Path=` 行
+pub fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| {
+            if is_unreserved(byte) {
+                (byte as char).to_string()
+            } else {
+                format!("%{:02X}", byte)
+            }
+        })
+        .collect()
+}
+
+/// 把文件或文件夹移到所在分区对应的回收站，返回在回收站中的新路径
+///
+/// 符号链接只移动链接本身
+#[cfg(unix)]
+pub fn move_to_trash(path: &Path) -> io::Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = absolute_path(path)?;
+    let device = fs::symlink_metadata(&path)?.dev();
+    let home_trash = ScanRoot::native(RootKind::XdgData)
+        .map(|root| root.path.join("Trash"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "无法确定家目录"))?;
+
+    // 家目录回收站所在的分区：回收站尚不存在时看其上级目录
+    let home_device = home_trash
+        .ancestors()
+        .find_map(|dir| fs::metadata(dir).ok())
+        .map(|metadata| metadata.dev());
+    if home_device == Some(device) {
+        return trash_in(&path, &home_trash, None);
+    }
+
+    let topdir = mount_point(&path, device);
+    let trash_dir = topdir_trash(&topdir)?;
+    trash_in(&path, &trash_dir, Some(&topdir))
+}
+
+/// Windows 上经由 `SHFileOperationW` 移到回收站，回收站中的位置由系统决定，返回原路径
+///
+/// 文件过大无法放入回收站时由系统询问是否彻底删除
+#[cfg(windows)]
+pub fn move_to_trash(path: &Path) -> io::Result<PathBuf> {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+
+    // shellapi.h 中的 SHFILEOPSTRUCTW，32 位系统上按 1 字节对齐
+    #[cfg_attr(target_pointer_width = "32", repr(C, packed(1)))]
+    #[cfg_attr(target_pointer_width = "64", repr(C))]
+    struct ShFileOpStruct {
+        hwnd: *mut c_void,
+        func: u32,
+        from: *const u16,
+        to: *const u16,
+        flags: u16,
+        any_operations_aborted: i32,
+        name_mappings: *mut c_void,
+        progress_title: *const u16,
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHFileOperationW(operation: *mut ShFileOpStruct) -> i32;
+    }
+
+    const FO_DELETE: u32 = 3;
+    const FOF_SILENT: u16 = 0x0004;
+    const FOF_NOCONFIRMATION: u16 = 0x0010;
+    const FOF_ALLOWUNDO: u16 = 0x0040;
+    const FOF_NOERRORUI: u16 = 0x0400;
+    const FOF_WANTNUKEWARNING: u16 = 0x4000;
+
+    // 相对路径不会移到回收站；路径列表以两个 0 结尾
+    let path = std::path::absolute(path)?;
+    fs::symlink_metadata(&path)?;
+    let mut from: Vec<u16> = path.as_os_str().encode_wide().collect();
+    from.extend([0, 0]);
+    let mut operation = ShFileOpStruct {
+        hwnd: std::ptr::null_mut(),
+        func: FO_DELETE,
+        from: from.as_ptr(),
+        to: std::ptr::null(),
+        flags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_SILENT | FOF_NOERRORUI | FOF_WANTNUKEWARNING,
+        any_operations_aborted: 0,
+        name_mappings: std::ptr::null_mut(),
+        progress_title: std::ptr::null(),
+    };
+    let code = unsafe { SHFileOperationW(&mut operation) };
+    if code != 0 {
+        return Err(io::Error::other(format!("移到回收站失败，错误码 0x{:X}", code)));
+    }
+    if operation.any_operations_aborted != 0 || fs::symlink_metadata(&path).is_ok() {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "移到回收站已取消"));
+    }
+    Ok(path)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn move_to_trash(_path: &Path) -> io::Result<PathBuf> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "当前平台不支持回收站，请使用彻底删除"))
+}
+
+// 转为绝对路径，只规范化上级目录，保留最后一段（可能是符号链接）
+#[cfg(unix)]
+fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "路径没有文件名"))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize()?,
+        _ => std::env::current_dir()?,
+    };
+    Ok(parent.join(name))
+}
+
+// 向上查找，直到上级目录位于其他分区
+#[cfg(unix)]
+fn mount_point(path: &Path, device: u64) -> PathBuf {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut topdir = path.to_path_buf();
+    while let Some(parent) = topdir.parent() {
+        match fs::metadata(parent) {
+            Ok(metadata) if metadata.dev() == device => topdir = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    topdir
+}
+
+// 分区根目录下的回收站：管理员创建的 .Trash 须为带粘滞位的真实目录
+#[cfg(unix)]
+fn topdir_trash(topdir: &Path) -> io::Result<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let uid = unsafe { libc::getuid() };
+    let shared = topdir.join(".Trash");
+    if let Ok(metadata) = fs::symlink_metadata(&shared) {
+        if metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0 {
+            let user_dir = shared.join(uid.to_string());
+            if fs::DirBuilder::new().recursive(true).mode(0o700).create(&user_dir).is_ok() {
+                return Ok(user_dir);
+            }
+        }
+    }
+
+    let user_trash = topdir.join(format!(".Trash-{}", uid));
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(&user_trash)?;
+    Ok(user_trash)
+}
+
+/// 移到指定的回收站目录
+///
+/// `topdir` 为分区根目录时 `Path=` 记录相对路径，否则记录绝对路径。
+/// 先以独占方式创建 `.trashinfo` 占用名称，重名时依次尝试 `名称.2`、`名称.3`
+pub fn trash_in(path: &Path, trash_dir: &Path, topdir: Option<&Path>) -> io::Result<PathBuf> {
+    let files_dir = trash_dir.join("files");
+    let info_dir = trash_dir.join("info");
+    fs::create_dir_all(&files_dir)?;
+    fs::create_dir_all(&info_dir)?;
+
+    let recorded = match topdir.and_then(|topdir| path.strip_prefix(topdir).ok()) {
+        Some(relative) => relative.to_path_buf(),
+        None => path.to_path_buf(),
+    };
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&recorded.to_string_lossy()),
+        Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+
+    let base_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "路径没有文件名"))?
+        .to_string_lossy()
+        .to_string();
+    for attempt in 1.. {
+        let name = if attempt == 1 {
+            base_name.clone()
+        } else {
+            format!("{}.{}", base_name, attempt)
+        };
+        let info_path = info_dir.join(format!("{}.trashinfo", name));
+        let mut info = match OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        let target = files_dir.join(&name);
+        if fs::symlink_metadata(&target).is_ok() {
+            // 回收站中残留了没有 info 的同名文件，换下一个名称
+            drop(info);
+            let _ = fs::remove_file(&info_path);
+            continue;
+        }
+        let result = info.write_all(content.as_bytes()).and_then(|_| fs::rename(path, &target));
+        return match result {
+            Ok(()) => Ok(target),
+            Err(e) => {
+                let _ = fs::remove_file(&info_path);
+                Err(e)
+            }
+        };
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_in() {
+        let temp_dir = std::env::temp_dir().join("test_trash_in");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let trash_dir = temp_dir.join("Trash");
+        let folder = temp_dir.join("data/My App");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("cache.bin"), "cache").unwrap();
+
+        // 家目录回收站记录绝对路径
+        let trashed = trash_in(&folder, &trash_dir, None).unwrap();
+        assert_eq!(trashed, trash_dir.join("files/My App"));
+        assert!(!folder.exists());
+        assert!(trashed.join("cache.bin").exists());
+        let info = fs::read_to_string(trash_dir.join("info/My App.trashinfo")).unwrap();
+        assert!(info.starts_with("[Trash Info]\n"));
+        assert!(info.contains(&format!("Path={}\n", encode_path(&folder.to_string_lossy()))));
+        assert!(info.contains("My%20App"));
+        assert!(info.contains("DeletionDate="));
+
+        // 重名时使用新名称；分区回收站记录相对路径
+        fs::create_dir_all(&folder).unwrap();
+        let trashed = trash_in(&folder, &trash_dir, Some(&temp_dir)).unwrap();
+        assert_eq!(trashed, trash_dir.join("files/My App.2"));
+        let info = fs::read_to_string(trash_dir.join("info/My App.2.trashinfo")).unwrap();
+        assert!(info.contains("Path=data/My%20App\n"));
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(encode_path("/home/u/.config/a b"), "/home/u/.config/a%20b");
+        assert_eq!(encode_path("/tmp/中"), "/tmp/%E4%B8%AD");
+    }
+}
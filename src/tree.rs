@@ -0,0 +1,317 @@
+use crate::roots::ScanRoot;
+use crate::scanner;
+use crate::size::{self, FolderSize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// 子文件夹树中的一个节点
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TreeNode {
+    /// 子文件夹大小，`None` 表示仍在后台计算
+    pub size: Option<FolderSize>,
+    /// 子文件夹本身是符号链接时的目标，链接不可展开
+    pub link_target: Option<PathBuf>,
+}
+
+// 后台线程发送给界面的子文件夹结果
+enum TreeEvent {
+    /// 某个文件夹的直接子文件夹（相对路径与链接目标）
+    Listed {
+        root_id: String,
+        parent: String,
+        children: Vec<(String, Option<PathBuf>)>,
+    },
+    /// 某个子文件夹的大小计算完成
+    Sized {
+        root_id: String,
+        path: String,
+        size: FolderSize,
+    },
+}
+
+/// 可逐级展开的文件夹树
+///
+/// 节点以相对于扫描根目录的路径标识，层级之间用 `/` 分隔，
+/// 因此可以直接与根目录拼接后交给删除、移动等操作。
+/// 子文件夹在首次展开时由后台线程列出并逐个计算大小
+pub struct FolderTree {
+    root_id: String,
+    expanded: HashSet<String>,
+    children: HashMap<String, Vec<String>>, // 已列出的子文件夹，按名称排序
+    nodes: HashMap<String, TreeNode>,
+    listing: HashSet<String>, // 正在后台列出子文件夹的节点
+    tx: Sender<TreeEvent>,
+    rx: Receiver<TreeEvent>,
+}
+
+impl Default for FolderTree {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            root_id: String::new(),
+            expanded: HashSet::new(),
+            children: HashMap::new(),
+            nodes: HashMap::new(),
+            listing: HashSet::new(),
+            tx,
+            rx,
+        }
+    }
+}
+
+/// 拼接子文件夹的相对路径
+pub fn child_path(parent: &str, name: &str) -> String {
+    format!("{}/{}", parent, name)
+}
+
+/// 相对路径的最后一级名称
+pub fn node_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// 相对路径的所有上级路径，由近及远
+pub fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    path.char_indices()
+        .rev()
+        .filter(|(_, c)| *c == '/')
+        .map(move |(index, _)| &path[..index])
+}
+
+impl FolderTree {
+    pub fn is_expanded(&self, path: &str) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// 展开或折叠节点，首次展开时在后台列出子文件夹
+    pub fn toggle(&mut self, root: &ScanRoot, path: &str) {
+        if self.root_id != root.id {
+            self.clear();
+            self.root_id = root.id.clone();
+        }
+        if !self.expanded.remove(path) {
+            self.expanded.insert(path.to_string());
+            if !self.children.contains_key(path) {
+                // 占位，避免重复派发
+                self.children.insert(path.to_string(), Vec::new());
+                self.listing.insert(path.to_string());
+                spawn_listing(self.tx.clone(), root, path.to_string());
+            }
+        }
+    }
+
+    /// 已展开节点的子节点
+    pub fn children(&self, path: &str) -> Vec<(String, TreeNode)> {
+        self.children
+            .get(path)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child| (child.clone(), self.nodes.get(child).cloned().unwrap_or_default()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 是否还有子文件夹在计算大小
+    pub fn is_loading(&self) -> bool {
+        !self.listing.is_empty() || self.nodes.values().any(|node| node.size.is_none())
+    }
+
+    /// 接收后台线程的结果，丢弃切换根目录前发出的事件
+    pub fn poll(&mut self) {
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                TreeEvent::Listed { root_id, parent, children } if root_id == self.root_id => {
+                    for (child, link_target) in &children {
+                        self.nodes.insert(
+                            child.clone(),
+                            TreeNode {
+                                size: None,
+                                link_target: link_target.clone(),
+                            },
+                        );
+                    }
+                    self.listing.remove(&parent);
+                    self.children
+                        .insert(parent, children.into_iter().map(|(child, _)| child).collect());
+                }
+                TreeEvent::Sized { root_id, path, size } if root_id == self.root_id => {
+                    if let Some(node) = self.nodes.get_mut(&path) {
+                        node.size = Some(size);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 清空整棵树（切换根目录或重新扫描时）
+    pub fn clear(&mut self) {
+        // 仍在运行的后台线程的结果随之失效
+        self.root_id.clear();
+        self.expanded.clear();
+        self.children.clear();
+        self.nodes.clear();
+        self.listing.clear();
+    }
+
+    /// 移除磁盘上已不存在的节点，并从上级节点的大小中扣除
+    ///
+    /// 返回被移除的最上层路径及其大小，顶层文件夹的大小由调用方更新
+    pub fn prune_missing(&mut self, root_path: &Path) -> Vec<(String, FolderSize)> {
+        let mut missing: Vec<String> = self
+            .nodes
+            .keys()
+            .chain(self.children.keys())
+            .filter(|path| fs::symlink_metadata(root_path.join(path)).is_err())
+            .cloned()
+            .collect();
+        // 上级路径排在前面，其下级随之一并移除
+        missing.sort();
+        missing.dedup();
+
+        let mut removed = Vec::new();
+        for path in missing {
+            if ancestors(&path).any(|ancestor| removed.iter().any(|(r, _)| r == ancestor)) {
+                continue;
+            }
+            let freed = self
+                .nodes
+                .remove(&path)
+                .and_then(|node| node.size)
+                .unwrap_or_default();
+            self.remove_descendants(&path);
+
+            if let Some((parent, _)) = path.rsplit_once('/') {
+                if let Some(siblings) = self.children.get_mut(parent) {
+                    siblings.retain(|sibling| sibling != &path);
+                }
+            }
+            for ancestor in ancestors(&path) {
+                if let Some(size) = self.nodes.get_mut(ancestor).and_then(|node| node.size.as_mut()) {
+                    size.apparent = size.apparent.saturating_sub(freed.apparent);
+                    size.on_disk = size.on_disk.saturating_sub(freed.on_disk);
+                }
+            }
+            removed.push((path, freed));
+        }
+        removed
+    }
+
+    fn remove_descendants(&mut self, path: &str) {
+        let prefix = format!("{}/", path);
+        self.nodes.retain(|node, _| !node.starts_with(&prefix));
+        self.children
+            .retain(|node, _| node != path && !node.starts_with(&prefix));
+        self.expanded
+            .retain(|node| node != path && !node.starts_with(&prefix));
+        self.listing
+            .retain(|node| node != path && !node.starts_with(&prefix));
+    }
+}
+
+// 在后台列出子文件夹，先发送列表，再逐个计算大小
+fn spawn_listing(tx: Sender<TreeEvent>, root: &ScanRoot, parent: String) {
+    let root_id = root.id.clone();
+    let dir = root.path.join(&parent);
+
+    thread::spawn(move || {
+        let mut folders = scanner::list_top_level_folders(&dir).unwrap_or_default();
+        folders.sort();
+        let children: Vec<(String, Option<PathBuf>)> = folders
+            .iter()
+            .map(|(name, path)| (child_path(&parent, name), size::link_target(path)))
+            .collect();
+        let paths: Vec<String> = children.iter().map(|(child, _)| child.clone()).collect();
+
+        if tx
+            .send(TreeEvent::Listed {
+                root_id: root_id.clone(),
+                parent,
+                children,
+            })
+            .is_err()
+        {
+            return;
+        }
+
+        for ((_, folder), path) in folders.iter().zip(paths) {
+            let size = size::measure(folder, &mut Vec::new());
+            if tx
+                .send(TreeEvent::Sized {
+                    root_id: root_id.clone(),
+                    path,
+                    size,
+                })
+                .is_err()
+            {
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roots::RootKind;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_expand_and_prune() {
+        let temp_dir = std::env::temp_dir().join("test_folder_tree");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("Google/Chrome/Cache")).unwrap();
+        fs::create_dir_all(temp_dir.join("Google/Drive")).unwrap();
+        fs::write(temp_dir.join("Google/Chrome/Cache/data"), vec![0u8; 3000]).unwrap();
+        fs::write(temp_dir.join("Google/Drive/db"), vec![0u8; 200]).unwrap();
+
+        let root = ScanRoot {
+            id: "test".to_string(),
+            kind: RootKind::Local,
+            path: temp_dir.clone(),
+            label: "test".to_string(),
+            profile: "test".to_string(),
+        };
+        let mut tree = FolderTree::default();
+        tree.toggle(&root, "Google");
+        tree.toggle(&root, "Google/Chrome");
+
+        // 等待后台线程完成
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            tree.poll();
+            let listed = tree.children("Google").len() == 2 && tree.children("Google/Chrome").len() == 1;
+            if (listed && !tree.is_loading()) || Instant::now() > deadline {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let children = tree.children("Google");
+        assert_eq!(children[0].0, "Google/Chrome");
+        assert_eq!(children[0].1.size.map(|s| s.apparent), Some(3000));
+        assert_eq!(children[1].1.size.map(|s| s.apparent), Some(200));
+        assert_eq!(node_name("Google/Chrome/Cache"), "Cache");
+        assert_eq!(ancestors("Google/Chrome/Cache").collect::<Vec<_>>(), vec!["Google/Chrome", "Google"]);
+
+        // 删除孙节点后，上级节点的大小随之减少
+        fs::remove_dir_all(temp_dir.join("Google/Chrome/Cache")).unwrap();
+        let removed = tree.prune_missing(&temp_dir);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].0, "Google/Chrome/Cache");
+        assert_eq!(removed[0].1.apparent, 3000);
+        assert_eq!(tree.children("Google/Chrome"), Vec::new());
+        assert_eq!(tree.children("Google")[0].1.size.map(|s| s.apparent), Some(0));
+
+        // 折叠后不再显示为展开
+        tree.toggle(&root, "Google");
+        assert!(!tree.is_expanded("Google"));
+
+        // 清理
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,251 @@
+use crate::utils;
+use chrono::{DateTime, Utc};
+use eframe::egui::{self, Color32, Rect, Sense, Stroke};
+
+/// 矩形的着色方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 按文件夹类别（缓存、日志、临时文件等）
+    Category,
+    /// 按最近使用时间
+    Age,
+}
+
+/// 根据文件夹名称推断的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Cache,
+    Log,
+    Temp,
+    Other,
+}
+
+impl Category {
+    /// 按名称中的关键字归类，不区分大小写
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if name.contains("cache") {
+            Category::Cache
+        } else if name.contains("log") || name.contains("crash") || name.contains("dump") {
+            Category::Log
+        } else if name.contains("temp") || name.contains("tmp") {
+            Category::Temp
+        } else {
+            Category::Other
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Cache => "缓存",
+            Category::Log => "日志",
+            Category::Temp => "临时文件",
+            Category::Other => "其他",
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            Category::Cache => Color32::from_rgb(214, 140, 52),
+            Category::Log => Color32::from_rgb(86, 132, 196),
+            Category::Temp => Color32::from_rgb(196, 82, 82),
+            Category::Other => Color32::from_rgb(96, 148, 112),
+        }
+    }
+}
+
+/// 矩形树图中的一项
+pub struct TreemapItem {
+    /// 相对于根目录的路径，点击后返回
+    pub path: String,
+    pub label: String,
+    pub size: u64,
+    pub last_used: Option<DateTime<Utc>>,
+    /// 符号链接不可进入
+    pub is_link: bool,
+}
+
+impl TreemapItem {
+    fn color(&self, mode: ColorMode) -> Color32 {
+        if self.is_link {
+            return Color32::from_gray(120);
+        }
+        match mode {
+            ColorMode::Category => Category::from_name(&self.label).color(),
+            ColorMode::Age => match self.last_used {
+                Some(last_used) => age_color((Utc::now() - last_used).num_days()),
+                None => Color32::from_gray(90),
+            },
+        }
+    }
+}
+
+// 一个月内为绿色，逐渐过渡到一年以上的红色
+fn age_color(days: i64) -> Color32 {
+    let t = (days.clamp(30, 365) - 30) as f32 / 335.0;
+    let lerp = |a: f32, b: f32| (a + (b - a) * t) as u8;
+    Color32::from_rgb(lerp(80.0, 200.0), lerp(170.0, 70.0), lerp(90.0, 60.0))
+}
+
+/// 绘制矩形树图，返回被点击的项的序号
+pub fn show(ui: &mut egui::Ui, items: &[TreemapItem], mode: ColorMode) -> Option<usize> {
+    let (bounds, _) = ui.allocate_exact_size(ui.available_size(), Sense::hover());
+    let sizes: Vec<u64> = items.iter().map(|item| item.size).collect();
+    let rects = layout(&sizes, bounds);
+    let painter = ui.painter_at(bounds);
+    let mut clicked = None;
+
+    for (index, (item, rect)) in items.iter().zip(rects).enumerate() {
+        if !rect.is_positive() {
+            continue;
+        }
+        let response = ui.interact(rect, ui.id().with(("treemap", &item.path)), Sense::click());
+        let mut fill = item.color(mode);
+        if response.hovered() {
+            fill = fill.gamma_multiply(1.3);
+        }
+        painter.rect_filled(rect, 0.0, fill);
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::from_gray(30)));
+
+        // 矩形足够大时才显示名称
+        if rect.width() > 48.0 && rect.height() > 18.0 {
+            painter
+                .with_clip_rect(rect.shrink(2.0))
+                .text(
+                    rect.left_top() + egui::vec2(4.0, 3.0),
+                    egui::Align2::LEFT_TOP,
+                    &item.label,
+                    egui::FontId::proportional(12.0),
+                    Color32::WHITE,
+                );
+        }
+
+        let response = response.on_hover_text(format!(
+            "{}\n{}\n{}",
+            item.path,
+            utils::format_size(item.size),
+            Category::from_name(&item.label).label()
+        ));
+        if response.clicked() && !item.is_link {
+            clicked = Some(index);
+        }
+    }
+
+    clicked
+}
+
+/// 按 squarified 算法把 `bounds` 划分为与 `sizes` 成比例的矩形
+///
+/// 返回的矩形与输入顺序一致，大小为 0 的项返回 `Rect::NOTHING`
+pub fn layout(sizes: &[u64], bounds: Rect) -> Vec<Rect> {
+    let mut rects = vec![Rect::NOTHING; sizes.len()];
+    let total: f64 = sizes.iter().map(|size| *size as f64).sum();
+    let area = (bounds.width() * bounds.height()) as f64;
+    if total <= 0.0 || area <= 0.0 {
+        return rects;
+    }
+
+    // 从大到小依次放置，面积按比例换算到像素
+    let mut order: Vec<usize> = (0..sizes.len()).filter(|i| sizes[*i] > 0).collect();
+    order.sort_by(|a, b| sizes[*b].cmp(&sizes[*a]));
+    let areas: Vec<f64> = sizes.iter().map(|size| *size as f64 * area / total).collect();
+
+    let mut remaining = bounds;
+    let mut row: Vec<usize> = Vec::new();
+    let mut next = 0;
+    while next < order.len() {
+        let side = remaining.width().min(remaining.height()) as f64;
+        let mut candidate = row.clone();
+        candidate.push(order[next]);
+        if row.is_empty() || worst_ratio(&candidate, &areas, side) <= worst_ratio(&row, &areas, side) {
+            row = candidate;
+            next += 1;
+        } else {
+            remaining = place_row(&row, &areas, remaining, &mut rects);
+            row.clear();
+        }
+    }
+    if !row.is_empty() {
+        place_row(&row, &areas, remaining, &mut rects);
+    }
+    rects
+}
+
+// 一行中最差的长宽比，越接近 1 越好
+fn worst_ratio(row: &[usize], areas: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().map(|i| areas[*i]).sum();
+    row.iter()
+        .map(|i| {
+            let area = areas[*i];
+            (side * side * area / (sum * sum)).max(sum * sum / (side * side * area))
+        })
+        .fold(0.0, f64::max)
+}
+
+// 沿较短的一边放置一行，返回剩余区域
+fn place_row(row: &[usize], areas: &[f64], bounds: Rect, rects: &mut [Rect]) -> Rect {
+    let sum: f64 = row.iter().map(|i| areas[*i]).sum();
+    if bounds.width() >= bounds.height() {
+        // 左侧竖排
+        let width = (sum / bounds.height() as f64) as f32;
+        let mut y = bounds.min.y;
+        for i in row {
+            let height = (areas[*i] / width as f64) as f32;
+            rects[*i] = Rect::from_min_size(egui::pos2(bounds.min.x, y), egui::vec2(width, height));
+            y += height;
+        }
+        Rect::from_min_max(egui::pos2(bounds.min.x + width, bounds.min.y), bounds.max)
+    } else {
+        // 顶部横排
+        let height = (sum / bounds.width() as f64) as f32;
+        let mut x = bounds.min.x;
+        for i in row {
+            let width = (areas[*i] / height as f64) as f32;
+            rects[*i] = Rect::from_min_size(egui::pos2(x, bounds.min.y), egui::vec2(width, height));
+            x += width;
+        }
+        Rect::from_min_max(egui::pos2(bounds.min.x, bounds.min.y + height), bounds.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_fills_bounds_proportionally() {
+        let bounds = Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(400.0, 300.0));
+        let sizes = [600, 0, 100, 300, 200, 50, 50];
+        let rects = layout(&sizes, bounds);
+
+        assert_eq!(rects.len(), sizes.len());
+        assert_eq!(rects[1], Rect::NOTHING);
+
+        let total: u64 = sizes.iter().sum();
+        let bounds_area = bounds.width() * bounds.height();
+        for (size, rect) in sizes.iter().zip(&rects) {
+            if *size == 0 {
+                continue;
+            }
+            // 面积与大小成比例，且都在边界内
+            let expected = bounds_area * *size as f32 / total as f32;
+            assert!((rect.width() * rect.height() - expected).abs() < 1.0);
+            assert!(bounds.expand(0.01).contains_rect(*rect));
+        }
+    }
+
+    #[test]
+    fn test_layout_empty() {
+        let bounds = Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(100.0, 100.0));
+        assert_eq!(layout(&[0, 0], bounds), vec![Rect::NOTHING; 2]);
+        assert!(layout(&[], bounds).is_empty());
+    }
+
+    #[test]
+    fn test_category_from_name() {
+        assert_eq!(Category::from_name("GPUCache"), Category::Cache);
+        assert_eq!(Category::from_name("CrashDumps"), Category::Log);
+        assert_eq!(Category::from_name("Temp"), Category::Temp);
+        assert_eq!(Category::from_name("Google"), Category::Other);
+    }
+}
@@ -1,10 +1,23 @@
+use crate::dry_run;
+use crate::tabs::plan_window::PlanWindow;
 use crate::logger;
+use crate::quarantine;
+use crate::roots;
+use crate::wine::{self, WinePrefix};
 use crate::ai_config::{AIConfig, AIHandler};
 use eframe::egui;
+use native_dialog::FileDialog;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::Receiver;
 use crate::tabs::ai_ui_tab::AIConfigurationUI;
 use crate::tabs::clear_tab::ClearTabState;
+use crate::tabs::apps_tab::AppsTabState;
+use crate::tabs::duplicates_tab::DuplicatesTabState;
+use crate::tabs::install_tab::InstallTabState;
+use crate::tabs::quarantine_tab::QuarantineTabState;
+use crate::tabs::rules_tab::RulesTabState;
+use crate::tabs::search_tab::SearchTabState;
 
 pub struct AppDataCleaner {
     // 标签页状态
@@ -20,6 +33,29 @@ pub struct AppDataCleaner {
     // 清理标签页状态
     clear_tab: ClearTabState,
 
+    // 全部根目录标签页状态
+    apps_tab: AppsTabState,
+    // 搜索标签页状态
+    search_tab: SearchTabState,
+    // 规则清理标签页状态
+    rules_tab: RulesTabState,
+    // 重复文件标签页状态
+    duplicates_tab: DuplicatesTabState,
+    // 安装追踪标签页状态
+    install_tab: InstallTabState,
+    // 隔离区标签页状态
+    quarantine_tab: QuarantineTabState,
+
+    // 演练计划窗口
+    plan_window: PlanWindow,
+
+    // 已找到的 Wine/Proton 前缀
+    wine_prefixes: Vec<WinePrefix>,
+
+    // 离线扫描：挂载的 Windows Users 目录及其中的用户配置文件
+    offline_users_dir: Option<PathBuf>,
+    offline_profiles: Vec<String>,
+
     // AI UI标签页
     ai_ui: AIConfigurationUI,
     ai_rx: Option<Receiver<(String, String, String)>>, // 添加 AI 响应接收器
@@ -55,25 +91,26 @@ impl Default for AppDataCleaner {
 
         let ai_ui = AIConfigurationUI::new(ai_config.clone(), ai_handler.clone());
 
+        // 在后台清除隔离区中的过期条目
+        std::thread::spawn(quarantine::auto_purge);
+
         // 创建清理标签页状态
         let mut clear_tab = ClearTabState::default();
         
         // 设置回调函数 - 使用 String 而不是引用
         {
             let ai_handler_clone = ai_handler.clone();
-            clear_tab.set_generate_description_callback(move |folder| {
+            clear_tab.set_generate_description_callback(move |folder, selected_folder| {
                 let folder_name = folder.to_string();
                 // 这里使用了副本，不再引用原始对象
-                let selected_folder_clone = "Roaming".to_string(); // 默认值，将在实际使用时更新
+                let selected_folder_clone = selected_folder.to_string();
                 let handler = ai_handler_clone.clone();
                 
                 std::thread::spawn(move || {
                     let rt = tokio::runtime::Runtime::new().unwrap();
                     rt.block_on(async {
-                        if let Ok(mut handler) = handler.lock() {
-                            if let Err(e) = handler.generate_single_description(folder_name.clone(), selected_folder_clone).await {
-                                logger::log_error(&format!("生成描述失败: {}", e));
-                            }
+                        if let Err(e) = AIHandler::generate_single_description(&handler, folder_name.clone(), selected_folder_clone).await {
+                            logger::log_error(&format!("生成描述失败: {}", e));
                         }
                     });
                 });
@@ -91,10 +128,8 @@ impl Default for AppDataCleaner {
                 std::thread::spawn(move || {
                     let rt = tokio::runtime::Runtime::new().unwrap();
                     rt.block_on(async {
-                        if let Ok(mut handler) = handler.lock() {
-                            if let Err(e) = handler.generate_all_descriptions(folder_data.clone(), selected_folder).await {
-                                logger::log_error(&format!("批量生成描述失败: {}", e));
-                            }
+                        if let Err(e) = AIHandler::generate_all_descriptions(&handler, folder_data.clone(), selected_folder).await {
+                            logger::log_error(&format!("批量生成描述失败: {}", e));
                         }
                     });
                 });
@@ -114,6 +149,16 @@ impl Default for AppDataCleaner {
 
             // 清理标签页初始化 
             clear_tab,
+            apps_tab: AppsTabState::default(),
+            search_tab: SearchTabState::default(),
+            rules_tab: RulesTabState::default(),
+            duplicates_tab: DuplicatesTabState::default(),
+            install_tab: InstallTabState::default(),
+            quarantine_tab: QuarantineTabState::default(),
+            plan_window: PlanWindow::default(),
+            wine_prefixes: wine::find_prefixes(),
+            offline_users_dir: None,
+            offline_profiles: Vec::new(),
 
             // AI相关初始化
             ai_ui,
@@ -149,23 +194,88 @@ impl AppDataCleaner {
             ui.horizontal(|ui| {  
                 // 左侧标签页和选项
                 ui.selectable_value(&mut self.current_tab, "主页".to_string(), "主页");
+                ui.selectable_value(&mut self.current_tab, "全部根目录".to_string(), "全部根目录");
+                ui.selectable_value(&mut self.current_tab, "搜索".to_string(), "搜索");
+                ui.selectable_value(&mut self.current_tab, "规则清理".to_string(), "规则清理");
+                ui.selectable_value(&mut self.current_tab, "重复文件".to_string(), "重复文件");
+                ui.selectable_value(&mut self.current_tab, "安装追踪".to_string(), "安装追踪");
+                ui.selectable_value(&mut self.current_tab, "隔离区".to_string(), "隔离区");
                 ui.selectable_value(&mut self.current_tab, "AI配置".to_string(), "AI配置");
                 ui.label("|"); // 添加分隔符
                 ui.checkbox(&mut self.is_logging_enabled, "启用日志");
+                let mut dry_run_enabled = dry_run::is_enabled();
+                if ui.checkbox(&mut dry_run_enabled, "演练模式").changed() {
+                    dry_run::set_enabled(dry_run_enabled);
+                    logger::log_info(if dry_run_enabled { "演练模式已开启" } else { "演练模式已关闭" });
+                }
 
                 // 添加一个弹性空间，将后面的内容推到右侧
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     // 切换文件夹按钮
                     ui.menu_button("切换文件夹", |ui| {
-                        for folder in ["Roaming", "Local", "LocalLow"] {
-                            if ui.button(folder).clicked() {
-                                self.clear_tab.set_selected_appdata_folder(folder.to_string());
-                                ui.close_menu();
+                        let mut chosen_root = None;
+                        for root in roots::native_roots() {
+                            if ui.button(&root.label).clicked() {
+                                chosen_root = Some(root);
                             }
                         }
+
+                        // Wine 与 Proton 前缀中的 AppData
+                        if !self.wine_prefixes.is_empty() {
+                            ui.separator();
+                        }
+                        for prefix in &self.wine_prefixes {
+                            ui.menu_button(prefix.label(), |ui| {
+                                for root in prefix.roots() {
+                                    // 只显示前缀内的相对路径
+                                    let label = root
+                                        .path
+                                        .strip_prefix(&prefix.path)
+                                        .unwrap_or(&root.path)
+                                        .display()
+                                        .to_string();
+                                    if ui.button(label).clicked() {
+                                        chosen_root = Some(root);
+                                    }
+                                }
+                            });
+                        }
+                        if ui.button("重新查找 Wine 前缀").clicked() {
+                            self.wine_prefixes = wine::find_prefixes();
+                        }
+
+                        // 挂载的 Windows 分区或磁盘镜像中的用户配置文件
+                        ui.separator();
+                        if let Some(users_dir) = &self.offline_users_dir {
+                            for profile in &self.offline_profiles {
+                                ui.menu_button(format!("离线: {}", profile), |ui| {
+                                    for root in roots::profile_roots(users_dir, profile) {
+                                        if ui.button(root.kind.label()).clicked() {
+                                            chosen_root = Some(root);
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                        if ui.button("选择离线 Users 目录...").clicked() {
+                            if let Ok(Some(users_dir)) = FileDialog::new().show_open_single_dir() {
+                                self.offline_profiles = roots::list_profiles(&users_dir);
+                                logger::log_info(&format!(
+                                    "在 {} 中找到 {} 个用户配置文件",
+                                    users_dir.display(),
+                                    self.offline_profiles.len()
+                                ));
+                                self.offline_users_dir = Some(users_dir);
+                            }
+                        }
+
+                        if let Some(root) = chosen_root {
+                            self.clear_tab.set_selected_root(root);
+                            ui.close_menu();
+                        }
                     });
                     // 当前目标文件夹显示
-                    ui.label(format!("当前目标: {}", self.clear_tab.selected_appdata_folder));
+                    ui.label(format!("当前目标: {}", self.clear_tab.selected_root.label));
                     
                     ui.separator(); // 分隔符
                     
@@ -221,6 +331,12 @@ impl AppDataCleaner {
             egui::CentralPanel::default().show(ctx, |ui| {
                 match self.current_tab.as_str() {
                     "主页" => self.clear_tab.show(ui),
+                    "全部根目录" => self.apps_tab.show(ui, &mut self.clear_tab),
+                    "搜索" => self.search_tab.show(ui, &mut self.clear_tab),
+                    "规则清理" => self.rules_tab.show(ui, &mut self.clear_tab),
+                    "重复文件" => self.duplicates_tab.show(ui, &self.clear_tab.selected_root),
+                    "安装追踪" => self.install_tab.show(ui, &self.clear_tab.selected_root),
+                    "隔离区" => self.quarantine_tab.show(ui, &mut self.clear_tab),
                     "AI配置" => self.ai_ui.draw_config_ui(ui),
                     _ => self.clear_tab.show(ui),
                 }
@@ -236,6 +352,10 @@ impl AppDataCleaner {
 
         // 移动窗口
         self.clear_tab.move_module.show_move_window(ctx);
+        self.clear_tab.details_window.show_details_window(ctx);
+        self.clear_tab.snapshot_window.show_snapshot_window(ctx);
+        self.clear_tab.report_window.show_report_window(ctx);
+        self.plan_window.show_plan_window(ctx);
     }
 }
 
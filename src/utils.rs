@@ -1,3 +1,8 @@
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
+use sha2::{Digest, Sha256};
+
 pub fn format_size(size: u64) -> String {
     const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
     let mut size = size as f64;
@@ -9,25 +14,17 @@ pub fn format_size(size: u64) -> String {
     format!("{:.2} {}", size, UNITS[unit])
 }
 
-//use std::env;
-
-use dirs_next as dirs;
-use std::path::PathBuf;
-
-pub fn get_appdata_dir(folder_type: &str) -> Option<PathBuf> {
-    match folder_type {
-        "Roaming" => dirs::data_dir(),
-        "Local" => dirs::cache_dir(),
-        "LocalLow" => Some(PathBuf::from("C:/Users/Default/AppData/LocalLow")), 
-        _ => None,
+/// 将时间显示为距今的天数
+pub fn format_age(time: Option<DateTime<Utc>>) -> String {
+    match time {
+        Some(time) => match (Utc::now() - time).num_days() {
+            days if days <= 0 => "今天".to_string(),
+            days => format!("{} 天前", days),
+        },
+        None => "未知".to_string(),
     }
 }
 
-use std::fs;
-use std::path::Path;
-use sha2::{Digest, Sha256};
-
-#[allow(dead_code)]
 pub fn hash_file(path: &Path) -> Result<String, std::io::Error> {
     let mut file = fs::File::open(path)?;
     let mut hasher = Sha256::new();
@@ -0,0 +1,126 @@
+use crate::logger::{self, LogContext};
+use crate::orphan::InstalledApps;
+use crate::roots::ScanRoot;
+use crate::scanner::{self, ScanMessage};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Component, Path};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 最后一次变化后等待多久再重新计算，避免写入过程中反复统计
+const QUIET_PERIOD: Duration = Duration::from_secs(2);
+/// 持续变化时最长的等待时间
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// 监视根目录的文件变化，增量更新有变化的顶层文件夹
+///
+/// 结果通过与扫描相同的通道以 `ScanMessage` 发送，界面按 `root_id` 过滤。
+/// 丢弃该结构即停止监视
+pub struct FolderWatcher {
+    pub root_id: String,
+    _watcher: RecommendedWatcher,
+}
+
+impl FolderWatcher {
+    pub fn start(root: &ScanRoot, tx: Sender<ScanMessage>) -> notify::Result<Self> {
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(event_tx)?;
+        watcher.watch(&root.path, RecursiveMode::Recursive)?;
+
+        let root = root.clone();
+        logger::log_structured_info(&watch_log_context(&root), "开始监视文件变化");
+        let root_id = root.id.clone();
+        thread::spawn(move || watch_loop(root, event_rx, tx));
+
+        Ok(Self {
+            root_id,
+            _watcher: watcher,
+        })
+    }
+}
+
+fn watch_log_context(root: &ScanRoot) -> LogContext {
+    LogContext::new("监视")
+        .with_target_type(root.profile.clone())
+        .with_target_name(root.label.clone())
+}
+
+// 收集一段时间内变化的顶层文件夹，再一次性重新计算；
+// 监视器被丢弃后通道断开，线程随之退出
+fn watch_loop(root: ScanRoot, events: Receiver<notify::Result<Event>>, tx: Sender<ScanMessage>) {
+    let ctx = watch_log_context(&root);
+    let installed_apps = if root.kind.is_xdg() {
+        InstalledApps::collect()
+    } else {
+        InstalledApps::default()
+    };
+
+    while let Ok(first) = events.recv() {
+        let mut changed = BTreeSet::new();
+        collect_changed(&root.path, first, &mut changed, &ctx);
+
+        let started = Instant::now();
+        loop {
+            let timeout = QUIET_PERIOD.min(MAX_DELAY.saturating_sub(started.elapsed()));
+            match events.recv_timeout(timeout) {
+                Ok(event) => collect_changed(&root.path, event, &mut changed, &ctx),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        if changed.is_empty() {
+            continue;
+        }
+        let names: Vec<String> = changed.into_iter().collect();
+        logger::log_structured_info(&ctx, &format!("检测到变化: {}", names.join(", ")));
+        if let Err(e) = scanner::refresh_folders(tx.clone(), &root, &names, &installed_apps) {
+            logger::log_structured_error(&ctx, &format!("更新文件夹失败: {}", e));
+            // 界面已关闭接收端时停止监视
+            if e.is::<mpsc::SendError<()>>() {
+                return;
+            }
+        }
+    }
+}
+
+fn collect_changed(
+    root: &Path,
+    event: notify::Result<Event>,
+    changed: &mut BTreeSet<String>,
+    ctx: &LogContext,
+) {
+    match event {
+        // 读取文件不改变大小
+        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+        Ok(event) => changed.extend(event.paths.iter().filter_map(|path| top_level_name(root, path))),
+        Err(e) => logger::log_structured_error(ctx, &format!("监视出错: {}", e)),
+    }
+}
+
+/// 变化的路径所属的顶层文件夹名，根目录本身返回 `None`
+pub fn top_level_name(root: &Path, path: &Path) -> Option<String> {
+    match path.strip_prefix(root).ok()?.components().next()? {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_level_name() {
+        let root = Path::new("/home/u/.config");
+        assert_eq!(
+            top_level_name(root, Path::new("/home/u/.config/app/a/b.json")),
+            Some("app".to_string())
+        );
+        assert_eq!(top_level_name(root, Path::new("/home/u/.config/app")), Some("app".to_string()));
+        assert_eq!(top_level_name(root, root), None);
+        assert_eq!(top_level_name(root, Path::new("/tmp/other")), None);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::logger;
+use crate::roots::{self, ScanRoot};
+use dirs_next as dirs;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 前缀的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefixSource {
+    /// 普通 Wine 前缀（~/.wine、$WINEPREFIX、winetricks、Bottles）
+    Wine,
+    /// Steam Proton 前缀，附带游戏的 AppID
+    Proton(String),
+}
+
+/// 一个 Wine 前缀及其中的用户
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinePrefix {
+    /// 前缀目录（包含 drive_c）
+    pub path: PathBuf,
+    pub source: PrefixSource,
+    /// drive_c/users 下拥有 AppData 的用户
+    pub users: Vec<String>,
+}
+
+impl WinePrefix {
+    /// 界面上显示的名称
+    pub fn label(&self) -> String {
+        match &self.source {
+            PrefixSource::Wine => format!("Wine {}", display_path(&self.path)),
+            PrefixSource::Proton(app_id) => format!("Proton {}", app_id),
+        }
+    }
+
+    /// 前缀中每个用户的 Roaming、Local、LocalLow 根目录
+    pub fn roots(&self) -> Vec<ScanRoot> {
+        self.users
+            .iter()
+            .flat_map(|user| {
+                let appdata = self.path.join("drive_c").join("users").join(user).join("AppData");
+                roots::appdata_roots(
+                    &appdata,
+                    &format!("wine:{}:{}", self.path.display(), user),
+                    &format!("{} ({})", self.label(), user),
+                    user,
+                )
+            })
+            .collect()
+    }
+}
+
+/// 查找当前用户的所有 Wine 与 Proton 前缀
+pub fn find_prefixes() -> Vec<WinePrefix> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let prefixes = find_prefixes_in(&home, env::var_os("WINEPREFIX").map(PathBuf::from));
+    logger::log_info(&format!("找到 {} 个 Wine/Proton 前缀", prefixes.len()));
+    prefixes
+}
+
+// 在指定家目录下查找前缀，便于测试
+fn find_prefixes_in(home: &Path, wineprefix: Option<PathBuf>) -> Vec<WinePrefix> {
+    let mut candidates: Vec<(PathBuf, PrefixSource)> = Vec::new();
+
+    // 普通 Wine 前缀
+    if let Some(path) = wineprefix {
+        candidates.push((path, PrefixSource::Wine));
+    }
+    candidates.push((home.join(".wine"), PrefixSource::Wine));
+    for parent in [
+        home.join(".local/share/wineprefixes"), // winetricks
+        home.join(".local/share/bottles/bottles"), // Bottles
+        home.join(".var/app/com.usebottles.bottles/data/bottles/bottles"), // Flatpak 版 Bottles
+    ] {
+        for dir in list_subdirs(&parent) {
+            candidates.push((dir, PrefixSource::Wine));
+        }
+    }
+
+    // Steam Proton 前缀：steamapps/compatdata/<AppID>/pfx
+    for library in steam_libraries(home) {
+        for dir in list_subdirs(&library.join("steamapps").join("compatdata")) {
+            let app_id = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            candidates.push((dir.join("pfx"), PrefixSource::Proton(app_id)));
+        }
+    }
+
+    // 同一前缀可能经由符号链接出现多次（如 ~/.steam/steam）
+    let mut seen = HashSet::new();
+    let mut prefixes = Vec::new();
+    for (path, source) in candidates {
+        let Ok(canonical) = fs::canonicalize(&path) else {
+            continue;
+        };
+        if !seen.insert(canonical) {
+            continue;
+        }
+        let users = roots::list_profiles(&path.join("drive_c").join("users"));
+        if !users.is_empty() {
+            prefixes.push(WinePrefix { path, source, users });
+        }
+    }
+    prefixes
+}
+
+// Steam 的所有库目录：默认安装位置以及 libraryfolders.vdf 中登记的其他库
+fn steam_libraries(home: &Path) -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    for steam_root in [
+        home.join(".steam/steam"),
+        home.join(".local/share/Steam"),
+        home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+    ] {
+        if !steam_root.is_dir() {
+            continue;
+        }
+        let vdf = steam_root.join("steamapps").join("libraryfolders.vdf");
+        if let Ok(content) = fs::read_to_string(&vdf) {
+            libraries.extend(parse_library_folders(&content));
+        }
+        libraries.push(steam_root);
+    }
+    libraries
+}
+
+// 从 libraryfolders.vdf 中提取 "path" 项
+fn parse_library_folders(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .filter_map(|line| {
+            // 形如: "path"		"/mnt/games/SteamLibrary"
+            let mut parts = line.trim().split('"').filter(|part| !part.trim().is_empty());
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key.eq_ignore_ascii_case("path") => {
+                    Some(PathBuf::from(value.replace("\\\\", "\\")))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn list_subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+// 家目录下的路径以 ~ 显示
+fn display_path(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(relative) => format!("~/{}", relative.display()),
+        None => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roots::RootKind;
+
+    fn make_prefix(prefix: &Path, users: &[&str]) {
+        for user in users {
+            fs::create_dir_all(prefix.join("drive_c/users").join(user).join("AppData/Roaming")).unwrap();
+        }
+        // 没有 AppData 的公共用户不应出现
+        fs::create_dir_all(prefix.join("drive_c/users/Public")).unwrap();
+    }
+
+    #[test]
+    fn test_find_prefixes_in_home() {
+        let home = std::env::temp_dir().join("test_find_wine_prefixes");
+        let _ = fs::remove_dir_all(&home);
+
+        make_prefix(&home.join(".wine"), &["alice"]);
+        make_prefix(&home.join("Games/custom"), &["bob"]);
+
+        // 主 Steam 库与登记在 libraryfolders.vdf 中的第二个库
+        let steam = home.join(".local/share/Steam");
+        let extra_library = home.join("SteamLibrary");
+        make_prefix(&steam.join("steamapps/compatdata/570/pfx"), &["steamuser"]);
+        make_prefix(&extra_library.join("steamapps/compatdata/1091500/pfx"), &["steamuser"]);
+        fs::write(
+            steam.join("steamapps/libraryfolders.vdf"),
+            format!(
+                "\"libraryfolders\"\n{{\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n",
+                extra_library.display()
+            ),
+        )
+        .unwrap();
+        // ~/.steam/steam 通常是指向同一安装的符号链接
+        #[cfg(unix)]
+        {
+            fs::create_dir_all(home.join(".steam")).unwrap();
+            std::os::unix::fs::symlink(&steam, home.join(".steam/steam")).unwrap();
+        }
+
+        let prefixes = find_prefixes_in(&home, Some(home.join("Games/custom")));
+        let sources: Vec<PrefixSource> = prefixes.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                PrefixSource::Wine,
+                PrefixSource::Wine,
+                PrefixSource::Proton("1091500".to_string()),
+                PrefixSource::Proton("570".to_string()),
+            ]
+        );
+        assert_eq!(prefixes[0].users, vec!["bob".to_string()]);
+        assert_eq!(prefixes[1].users, vec!["alice".to_string()]);
+
+        // 每个用户对应 Roaming、Local、LocalLow 三个根目录，且标识互不相同
+        let roots = prefixes[1].roots();
+        assert_eq!(roots.len(), 3);
+        assert_eq!(roots[0].kind, RootKind::Roaming);
+        assert_eq!(roots[0].path, home.join(".wine/drive_c/users/alice/AppData/Roaming"));
+        assert_eq!(roots[0].profile, "alice");
+        let ids: HashSet<String> = prefixes.iter().flat_map(|p| p.roots()).map(|r| r.id).collect();
+        assert_eq!(ids.len(), 12);
+
+        // 清理
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn test_parse_library_folders() {
+        let content = "\"libraryfolders\"\n{\n\t\"0\"\n\t{\n\t\t\"path\"\t\t\"/home/u/.local/share/Steam\"\n\t\t\"label\"\t\t\"\"\n\t}\n\t\"1\"\n\t{\n\t\t\"path\"\t\t\"/mnt/games/SteamLibrary\"\n\t}\n}\n";
+        assert_eq!(
+            parse_library_folders(content),
+            vec![
+                PathBuf::from("/home/u/.local/share/Steam"),
+                PathBuf::from("/mnt/games/SteamLibrary"),
+            ]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::roots::RootKind;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -9,6 +10,14 @@ pub struct FolderDescriptions {
     pub Roaming: HashMap<String, String>,
     pub Local: HashMap<String, String>,
     pub LocalLow: HashMap<String, String>,
+    #[serde(default)]
+    pub XdgConfig: HashMap<String, String>,
+    #[serde(default)]
+    pub XdgCache: HashMap<String, String>,
+    #[serde(default)]
+    pub XdgData: HashMap<String, String>,
+    #[serde(default)]
+    pub XdgState: HashMap<String, String>,
 }
 
 impl FolderDescriptions {
@@ -26,13 +35,17 @@ impl FolderDescriptions {
         Ok(descriptions)
     }
 
-    pub fn get_description(&self, folder_name: &str, folder_type: &str) -> Option<String> {
-        match folder_type {
-            "Roaming" => self.Roaming.get(folder_name).cloned(),
-            "Local" => self.Local.get(folder_name).cloned(),
-            "LocalLow" => self.LocalLow.get(folder_name).cloned(),
-            _ => None,
-        }
+    pub fn get_description(&self, folder_name: &str, kind: RootKind) -> Option<String> {
+        let descriptions = match kind {
+            RootKind::Roaming => &self.Roaming,
+            RootKind::Local => &self.Local,
+            RootKind::LocalLow => &self.LocalLow,
+            RootKind::XdgConfig => &self.XdgConfig,
+            RootKind::XdgCache => &self.XdgCache,
+            RootKind::XdgData => &self.XdgData,
+            RootKind::XdgState => &self.XdgState,
+        };
+        descriptions.get(folder_name).cloned()
     }
 }
 